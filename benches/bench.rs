@@ -21,6 +21,15 @@ fn bench_parse(c: &mut Criterion) {
         c.bench_function(&format!("{filename} parse/jsonbb"), |b| {
             b.iter(|| json.parse::<jsonbb::Value>().unwrap())
         });
+        c.bench_function(&format!("{filename} parse/jsonbb-serde"), |b| {
+            use serde::de::DeserializeSeed;
+            b.iter(|| {
+                let mut builder = jsonbb::Builder::with_capacity(json.len());
+                let mut deserializer = serde_json::Deserializer::from_str(&json);
+                builder.deserialize(&mut deserializer).unwrap();
+                builder.finish()
+            })
+        });
         #[cfg(feature = "simd-json")]
         c.bench_function(&format!("{filename} parse/jsonbb-simd"), |b| {
             b.iter_batched(
@@ -29,9 +38,17 @@ fn bench_parse(c: &mut Criterion) {
                 BatchSize::SmallInput,
             )
         });
+        #[cfg(feature = "sonic-rs")]
+        c.bench_function(&format!("{filename} parse/jsonbb-sonic"), |b| {
+            b.iter(|| jsonbb::Value::from_text_sonic(json.as_bytes()).unwrap())
+        });
         c.bench_function(&format!("{filename} parse/serde_json"), |b| {
             b.iter(|| json.parse::<serde_json::Value>().unwrap())
         });
+        #[cfg(feature = "sonic-rs")]
+        c.bench_function(&format!("{filename} parse/sonic-rs"), |b| {
+            b.iter(|| sonic_rs::from_str::<sonic_rs::Value>(&json).unwrap())
+        });
         c.bench_function(&format!("{filename} parse/jsonb"), |b| {
             b.iter(|| jsonb::parse_value(json.as_bytes()).unwrap().to_vec())
         });
@@ -84,6 +101,13 @@ fn bench_to_string(c: &mut Criterion) {
         c.bench_function(&format!("{filename} to_string/jsonbb"), |b| {
             b.iter(|| v.to_string())
         });
+        c.bench_function(&format!("{filename} to_writer/jsonbb"), |b| {
+            let mut buf = Vec::with_capacity(json.len());
+            b.iter(|| {
+                buf.clear();
+                v.to_writer(&mut buf, &jsonbb::WriteOptions::new()).unwrap();
+            })
+        });
         let v: serde_json::Value = json.parse().unwrap();
         c.bench_function(&format!("{filename} to_string/serde_json"), |b| {
             b.iter(|| v.to_string())
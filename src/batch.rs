@@ -0,0 +1,188 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing batches of JSON texts.
+
+use std::ops::Range;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::parser::{self, ParseOptions};
+use crate::{Builder, Error, Value};
+
+/// Parses a batch of JSON texts.
+///
+/// Returns the result of each text in the same order. Each thread reuses a buffer to parse into,
+/// so that every value is allocated only once with its exact size.
+///
+/// With the `rayon` feature, the texts are parsed in parallel.
+///
+/// # Example
+///
+/// ```
+/// let texts: &[&[u8]] = &[b"[1, 2]", b"{", b"null"];
+/// let values = jsonbb::parse_batch(texts);
+/// assert_eq!(values[0].as_ref().unwrap().to_string(), "[1,2]");
+/// assert!(values[1].is_err());
+/// assert!(values[2].as_ref().unwrap().is_null());
+/// ```
+pub fn parse_batch(texts: &[&[u8]]) -> Vec<Result<Value, Error>> {
+    let parse = |scratch: &mut Vec<u8>, text: &&[u8]| {
+        scratch.clear();
+        parse_into(text, scratch).map(|_| Value::from_bytes(scratch))
+    };
+    #[cfg(feature = "rayon")]
+    return texts.par_iter().map_init(Vec::new, parse).collect();
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut scratch = vec![];
+        texts.iter().map(|text| parse(&mut scratch, text)).collect()
+    }
+}
+
+/// Parses a batch of JSON texts and appends their jsonbb encoding to one shared buffer.
+///
+/// Returns the range of each value in the buffer, in the same order as the texts. Failed texts
+/// leave nothing in the buffer. Each value can be read with [`ValueRef::from_bytes`] from its
+/// range.
+///
+/// With the `rayon` feature, the texts are parsed in parallel into a buffer per thread, which
+/// are then concatenated.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::ValueRef;
+///
+/// let texts: &[&[u8]] = &[br#"{"a": 1}"#, b"[", b"\"b\""];
+/// let mut buffer = vec![];
+/// let ranges = jsonbb::parse_batch_into(texts, &mut buffer);
+/// let value = ValueRef::from_bytes(&buffer[ranges[0].as_ref().unwrap().clone()]);
+/// assert_eq!(value.to_string(), r#"{"a":1}"#);
+/// assert!(ranges[1].is_err());
+/// let value = ValueRef::from_bytes(&buffer[ranges[2].as_ref().unwrap().clone()]);
+/// assert_eq!(value.as_str(), Some("b"));
+/// ```
+///
+/// [`ValueRef::from_bytes`]: crate::ValueRef::from_bytes
+pub fn parse_batch_into(texts: &[&[u8]], buffer: &mut Vec<u8>) -> Vec<Result<Range<usize>, Error>> {
+    #[cfg(feature = "rayon")]
+    {
+        let chunk_size = texts.len().div_ceil(4 * rayon::current_num_threads());
+        let chunks: Vec<_> = texts
+            .par_chunks(chunk_size.max(1))
+            .map(|texts| {
+                let mut buffer = vec![];
+                let ranges: Vec<_> = texts
+                    .iter()
+                    .map(|text| parse_into(text, &mut buffer))
+                    .collect();
+                (buffer, ranges)
+            })
+            .collect();
+        let mut results = Vec::with_capacity(texts.len());
+        buffer.reserve(chunks.iter().map(|(b, _)| b.len()).sum());
+        for (chunk, ranges) in chunks {
+            // values are encoded relative to their start, so they can be moved as is
+            let base = buffer.len();
+            buffer.extend_from_slice(&chunk);
+            results.extend(
+                ranges
+                    .into_iter()
+                    .map(|r| r.map(|r| r.start + base..r.end + base)),
+            );
+        }
+        results
+    }
+    #[cfg(not(feature = "rayon"))]
+    texts.iter().map(|text| parse_into(text, buffer)).collect()
+}
+
+/// Parses the JSON text to the end of the buffer and returns the range of the value.
+///
+/// The buffer is unchanged on error.
+fn parse_into(text: &[u8], buffer: &mut Vec<u8>) -> Result<Range<usize>, Error> {
+    let start = buffer.len();
+    let mut builder = Builder::<&mut Vec<u8>>::appending(buffer);
+    match parser::parse(text, &mut builder, &ParseOptions::default()) {
+        Ok(()) => {
+            builder.finish();
+            Ok(start..buffer.len())
+        }
+        Err(e) => {
+            buffer.truncate(start);
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ValueRef;
+
+    fn texts() -> Vec<Vec<u8>> {
+        (0..1000)
+            .map(|i| match i % 7 {
+                0 => format!(r#"{{"id": {i}, "tags": ["a", "b"], "ok": true}}"#),
+                1 => format!("[{i}, {}]", i as f64 / 4.0),
+                2 => format!(r#""row {i}""#),
+                3 => format!(r#"{{"id": {i}"#), // invalid
+                4 => "null".into(),
+                5 => format!(r#"{{"nested": {{"id": {i}, "list": [[], {{}}]}}}}"#),
+                _ => format!("{i}"),
+            })
+            .map(String::into_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn parse_batch() {
+        let texts = texts();
+        let texts: Vec<&[u8]> = texts.iter().map(|t| t.as_slice()).collect();
+        let values = super::parse_batch(&texts);
+        assert_eq!(values.len(), texts.len());
+        for (text, value) in texts.iter().zip(values) {
+            match Value::from_text(text) {
+                Ok(expected) => assert_eq!(value.unwrap(), expected),
+                Err(e) => assert_eq!(value.unwrap_err().to_string(), e.to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_batch_into() {
+        let texts = texts();
+        let texts: Vec<&[u8]> = texts.iter().map(|t| t.as_slice()).collect();
+        let mut buffer = b"prefix".to_vec();
+        let ranges = super::parse_batch_into(&texts, &mut buffer);
+        assert_eq!(ranges.len(), texts.len());
+        let mut end = 6;
+        for (text, range) in texts.iter().zip(ranges) {
+            match Value::from_text(text) {
+                Ok(expected) => {
+                    let range = range.unwrap();
+                    // the values are contiguous in order
+                    assert_eq!(range.start, end);
+                    end = range.end;
+                    assert_eq!(ValueRef::from_bytes(&buffer[range]), expected.as_ref());
+                }
+                Err(e) => assert_eq!(range.unwrap_err().to_string(), e.to_string()),
+            }
+        }
+        assert_eq!(end, buffer.len());
+        assert!(super::parse_batch_into(&[], &mut buffer).is_empty());
+    }
+}
@@ -26,12 +26,71 @@ use std::fmt::{self, Debug, Display};
 pub struct Builder<W = Vec<u8>> {
     /// The buffer to write to.
     buffer: W,
+    /// The position in the buffer that the value is encoded relative to.
+    start: usize,
     /// A stack of entries.
     ///
     /// Smallvec is used to avoid heap allocation for single value.
     pointers: SmallVec<[Entry; 1]>,
     /// A stack of (position, number of pointers) pairs when the array/object starts.
     container_starts: Vec<(usize, usize)>,
+    /// The order of keys in objects.
+    key_order: KeyOrder,
+    /// Whether to keep the insertion order of keys in objects.
+    preserve_order: bool,
+    /// How to handle duplicate keys in objects.
+    duplicate_key_policy: DuplicateKeyPolicy,
+    /// How to handle infinite and NaN numbers.
+    non_finite_policy: NonFinitePolicy,
+}
+
+/// The order in which object keys are stored.
+///
+/// The order is recorded in each object, so objects built with different orders can be read
+/// and compared with each other.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum KeyOrder {
+    /// Keys are ordered bytewise.
+    #[default]
+    Lexicographic,
+    /// Keys are ordered by length first, then bytewise. This is the same as PostgreSQL's `jsonb`.
+    LengthFirst,
+}
+
+impl KeyOrder {
+    /// Compares two keys in this order.
+    pub(crate) fn cmp(self, a: &str, b: &str) -> std::cmp::Ordering {
+        match self {
+            KeyOrder::Lexicographic => a.cmp(b),
+            KeyOrder::LengthFirst => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+        }
+    }
+}
+
+/// How to handle duplicate keys in an object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeyPolicy {
+    /// The last value is kept.
+    #[default]
+    LastWins,
+    /// The first value is kept.
+    FirstWins,
+    /// Duplicate keys are rejected with [`Error::DuplicateKey`].
+    Error,
+    /// All values of a duplicate key are collected into an array in insertion order.
+    Collect,
+}
+
+/// How to handle infinite and NaN numbers, which can not be represented in JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum NonFinitePolicy {
+    /// Non-finite numbers are rejected with [`Error::NonFinite`].
+    #[default]
+    Error,
+    /// Non-finite numbers are stored as null.
+    Null,
+    /// Non-finite numbers are stored as strings `"NaN"`, `"Infinity"` or `"-Infinity"`.
+    String,
 }
 
 /// A checkpoint of the builder state.
@@ -62,8 +121,13 @@ impl<W: Clone> Clone for Builder<W> {
     fn clone(&self) -> Self {
         Builder {
             buffer: self.buffer.clone(),
+            start: self.start,
             pointers: self.pointers.clone(),
             container_starts: self.container_starts.clone(),
+            key_order: self.key_order,
+            preserve_order: self.preserve_order,
+            duplicate_key_policy: self.duplicate_key_policy,
+            non_finite_policy: self.non_finite_policy,
         }
     }
 }
@@ -78,23 +142,160 @@ impl Builder<Vec<u8>> {
     pub fn with_capacity(capacity: usize) -> Self {
         Builder {
             buffer: Vec::with_capacity(capacity),
+            start: 0,
             pointers: SmallVec::new(),
             container_starts: vec![],
+            key_order: KeyOrder::default(),
+            preserve_order: false,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            non_finite_policy: NonFinitePolicy::default(),
         }
     }
 }
 
 impl<'a> Builder<&'a mut Vec<u8>> {
     /// Creates a new [`Builder`].
+    ///
+    /// The value is encoded relative to the start of the buffer. Use [`appending`] to encode it
+    /// relative to the current end.
+    ///
+    /// [`appending`]: #method.appending
     pub fn new(buffer: &'a mut Vec<u8>) -> Self {
+        Self::with_start(buffer, 0)
+    }
+
+    /// Creates a new [`Builder`] that appends the value to the buffer.
+    ///
+    /// The value is encoded relative to the current end of the buffer, so that it can be read
+    /// from the appended bytes alone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Builder, ValueRef};
+    ///
+    /// let mut buffer = vec![];
+    /// let mut ranges = vec![];
+    /// for s in ["a", "b"] {
+    ///     let start = buffer.len();
+    ///     let mut builder = Builder::<&mut Vec<u8>>::appending(&mut buffer);
+    ///     builder.add_string(s);
+    ///     builder.finish();
+    ///     ranges.push(start..buffer.len());
+    /// }
+    /// assert_eq!(ValueRef::from_bytes(&buffer[ranges[1].clone()]).as_str(), Some("b"));
+    /// ```
+    pub fn appending(buffer: &'a mut Vec<u8>) -> Self {
+        let start = buffer.len();
+        Self::with_start(buffer, start)
+    }
+
+    fn with_start(buffer: &'a mut Vec<u8>, start: usize) -> Self {
         Builder {
             buffer,
+            start,
             pointers: SmallVec::new(),
             container_starts: vec![],
+            key_order: KeyOrder::default(),
+            preserve_order: false,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            non_finite_policy: NonFinitePolicy::default(),
         }
     }
 }
 
+impl<W> Builder<W> {
+    /// Sets the order of keys in objects built afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Builder, KeyOrder};
+    /// use serde::de::DeserializeSeed;
+    ///
+    /// let mut builder = Builder::<Vec<u8>>::new().with_key_order(KeyOrder::LengthFirst);
+    /// let mut deserializer = serde_json::Deserializer::from_str(r#"{"aa": 1, "b": 2}"#);
+    /// builder.deserialize(&mut deserializer).unwrap();
+    /// let value = builder.finish();
+    /// assert_eq!(value.to_string(), r#"{"b":2,"aa":1}"#);
+    /// ```
+    pub fn with_key_order(mut self, order: KeyOrder) -> Self {
+        self.key_order = order;
+        self
+    }
+
+    /// Sets whether objects built afterwards keep the insertion order of keys.
+    ///
+    /// If enabled, the original order is stored as a permutation of the sorted entries.
+    /// Iterating and printing the object follows the insertion order, while lookups are still
+    /// binary searches. For duplicate keys, the position of the first occurrence is kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Builder;
+    ///
+    /// let mut builder = Builder::<Vec<u8>>::new().with_preserve_order(true);
+    /// builder.begin_object();
+    /// builder.add_string("b");
+    /// builder.add_u64(1);
+    /// builder.add_string("a");
+    /// builder.add_u64(2);
+    /// builder.end_object();
+    /// let value = builder.finish();
+    /// assert_eq!(value.to_string(), r#"{"b":1,"a":2}"#);
+    /// assert_eq!(value.get("a").unwrap().as_u64(), Some(2));
+    /// ```
+    pub fn with_preserve_order(mut self, enable: bool) -> Self {
+        self.preserve_order = enable;
+        self
+    }
+
+    /// Sets how duplicate keys are handled in objects built afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Builder, DuplicateKeyPolicy};
+    ///
+    /// let mut builder = Builder::<Vec<u8>>::new().with_duplicate_key_policy(DuplicateKeyPolicy::Collect);
+    /// builder.begin_object();
+    /// builder.add_string("a");
+    /// builder.add_u64(1);
+    /// builder.add_string("b");
+    /// builder.add_u64(2);
+    /// builder.add_string("a");
+    /// builder.add_u64(3);
+    /// builder.end_object();
+    /// assert_eq!(builder.finish().to_string(), r#"{"a":[1,3],"b":2}"#);
+    /// ```
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_key_policy = policy;
+        self
+    }
+
+    /// Sets how infinite and NaN numbers are handled by [`add_f64`] afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Builder, NonFinitePolicy};
+    ///
+    /// let mut builder = Builder::<Vec<u8>>::new().with_non_finite_policy(NonFinitePolicy::String);
+    /// builder.begin_array();
+    /// builder.add_f64(f64::NAN);
+    /// builder.add_f64(f64::NEG_INFINITY);
+    /// builder.end_array();
+    /// assert_eq!(builder.finish().to_string(), r#"["NaN","-Infinity"]"#);
+    /// ```
+    ///
+    /// [`add_f64`]: #method.add_f64
+    pub fn with_non_finite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite_policy = policy;
+        self
+    }
+}
+
 impl<W: AsMut<Vec<u8>>> Builder<W> {
     /// Adds a null value to the builder.
     pub fn add_null(&mut self) {
@@ -142,16 +343,30 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     }
 
     /// Adds an f64 value to the builder.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is infinite or NaN and the policy is [`NonFinitePolicy::Error`].
+    #[inline]
     pub fn add_f64(&mut self, v: f64) {
-        assert!(
-            !v.is_nan() && !v.is_infinite(),
-            "Infinite or NaN values are not JSON numbers"
-        );
+        if let Err(e) = self.try_add_f64(v) {
+            panic!("{e}");
+        }
+    }
+
+    /// Adds an f64 value to the builder, returning an error if the value is infinite or NaN
+    /// and the policy is [`NonFinitePolicy::Error`].
+    #[inline]
+    pub fn try_add_f64(&mut self, v: f64) -> Result<(), Error> {
+        if !v.is_finite() {
+            return self.add_non_finite(v);
+        }
         let offset = self.offset();
         self.pointers.push(Entry::number(offset));
         let buffer = self.buffer.as_mut();
         buffer.push(NUMBER_F64);
         buffer.put_f64_ne(v);
+        Ok(())
     }
 
     /// Adds a string value to the builder.
@@ -163,6 +378,99 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         buffer.put_slice(v.as_bytes());
     }
 
+    #[cold]
+    fn add_non_finite(&mut self, v: f64) -> Result<(), Error> {
+        match self.non_finite_policy {
+            NonFinitePolicy::Error => return Err(Error::NonFinite { value: v }),
+            NonFinitePolicy::Null => self.add_null(),
+            NonFinitePolicy::String if v.is_nan() => self.add_string("NaN"),
+            NonFinitePolicy::String if v > 0.0 => self.add_string("Infinity"),
+            NonFinitePolicy::String => self.add_string("-Infinity"),
+        }
+        Ok(())
+    }
+
+    /// Returns the number of open containers and pending values, which locates the next value.
+    pub(crate) fn position(&self) -> (usize, usize) {
+        (self.container_starts.len(), self.pointers.len())
+    }
+
+    /// Returns the position of the open container at `level`, counted from 1 for the outermost.
+    pub(crate) fn container_position(&self, level: usize) -> (usize, usize) {
+        (level - 1, self.container_starts[level - 1].1)
+    }
+
+    /// Returns the index of the value at the position in its container.
+    pub(crate) fn index_at(&self, (level, index): (usize, usize)) -> usize {
+        index
+            - level
+                .checked_sub(1)
+                .map_or(0, |i| self.container_starts[i].1)
+    }
+
+    /// Returns the string pushed right before the value at the position, i.e. its key if the
+    /// value is in an object.
+    pub(crate) fn key_at(&mut self, (level, index): (usize, usize)) -> Option<String> {
+        let entry = self.pointers[index.checked_sub(1)?];
+        if !entry.is_string() {
+            return None;
+        }
+        let start = level
+            .checked_sub(1)
+            .map_or(self.start, |i| self.container_starts[i].0);
+        let data = &self.buffer.as_mut()[start..];
+        ValueRef::from_slice(data, entry)
+            .as_str()
+            .map(str::to_owned)
+    }
+
+    /// Replaces the innermost open object, which holds only the text of a
+    /// `serde_json::value::RawValue`, with that text as raw JSON.
+    pub(crate) fn end_raw_value(&mut self) -> Result<(), Error> {
+        let (start, npointer) = self
+            .container_starts
+            .pop()
+            .ok_or_else(|| Error::builder("no object to end"))?;
+        if self.pointers.len() != npointer + 1 || !self.pointers[npointer].is_string() {
+            return Err(Error::builder("invalid raw value"));
+        }
+        let entry = self.pointers.pop().unwrap();
+        let buffer = self.buffer.as_mut();
+        let text = ValueRef::from_slice(&buffer[start..], entry)
+            .as_str()
+            .unwrap()
+            .to_owned();
+        buffer.truncate(start);
+        self.add_raw_json(&text)
+    }
+
+    /// Returns the length of the value if the pending entries are written.
+    pub(crate) fn encoded_len(&mut self) -> usize {
+        self.buffer.as_mut().len() + self.pointers.len() * 4
+    }
+
+    /// Adds a string value whose bytes are written by `f` to the builder.
+    ///
+    /// `f` must only write valid UTF-8. If it returns an error, the builder is left in an
+    /// unspecified state.
+    pub(crate) fn add_string_with<E>(
+        &mut self,
+        f: impl FnOnce(&mut Vec<u8>) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let offset = self.offset();
+        self.pointers.push(Entry::string(offset));
+
+        let buffer = self.buffer.as_mut();
+        let offset = buffer.len();
+        buffer.put_u32_ne(0); // placeholder for length
+        f(buffer)?;
+
+        // update length
+        let len = buffer.len() - offset - 4;
+        (&mut buffer[offset..]).put_u32_ne(len.try_into().expect("string too long"));
+        Ok(())
+    }
+
     /// Adds a string value that displays the given value to the builder.
     pub fn display(&mut self, v: impl Display) {
         use std::io::Write;
@@ -207,6 +515,25 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
         self.pointers.push(Entry::array(offset));
     }
 
+    /// Adds all elements of an array to the current array, copying their data at once.
+    pub(crate) fn add_array_elements(&mut self, array: ArrayRef<'_>) {
+        let data = array.as_slice();
+        let entries = data.len() - 8 - 4 * array.len();
+        let base = self.offset();
+        self.buffer.as_mut().extend_from_slice(&data[..entries]);
+        for chunk in data[entries..data.len() - 8].chunks_exact(4) {
+            let mut entry = Entry::from(chunk);
+            // null and booleans have no data
+            if !matches!(
+                entry.tag(),
+                Entry::NULL_TAG | Entry::FALSE_TAG | Entry::TRUE_TAG
+            ) {
+                entry.set_offset(entry.offset() + base);
+            }
+            self.pointers.push(entry);
+        }
+    }
+
     /// Begins an object.
     ///
     /// The caller then needs to push the keys and values in the following order:
@@ -215,11 +542,13 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     /// ```
     /// where each key must be a string.
     ///
-    /// Keys are allowed to be duplicated, but the last value will be used.
+    /// Keys are allowed to be duplicated. By default the last value will be used,
+    /// see [`with_duplicate_key_policy`] for other options.
     ///
     /// Finally [`end_object`] must be called to finish the object.
     ///
     /// [`end_object`]: #method.end_object
+    /// [`with_duplicate_key_policy`]: #method.with_duplicate_key_policy
     pub fn begin_object(&mut self) {
         let buffer = self.buffer.as_mut();
         self.container_starts
@@ -230,18 +559,73 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
     ///
     /// # Panics
     ///
-    /// Panics if:
+    /// Panics if [`try_end_object`] returns an error.
+    ///
+    /// [`try_end_object`]: #method.try_end_object
+    pub fn end_object(&mut self) {
+        if let Err(e) = self.try_end_object() {
+            panic!("{e}");
+        }
+    }
+
+    /// Ends an object with the key order and insertion order setting of another object.
+    pub(crate) fn end_object_like(&mut self, object: ObjectRef<'_>) {
+        let key_order = std::mem::replace(&mut self.key_order, object.key_order());
+        let preserve_order = std::mem::replace(&mut self.preserve_order, object.preserves_order());
+        self.end_object();
+        self.key_order = key_order;
+        self.preserve_order = preserve_order;
+    }
+
+    /// Ends an object whose keys were added unique and sorted in the key order, without sorting
+    /// them again.
+    pub(crate) fn end_sorted_object(&mut self, key_order: KeyOrder) {
+        let buffer = self.buffer.as_mut();
+        let (start, npointer) = self.container_starts.pop().unwrap();
+        let len = (self.pointers.len() - npointer) / 2;
+        buffer.reserve(8 * len + 4 + 4);
+        for entry in self.pointers.drain(npointer..) {
+            buffer.put_slice(entry.as_bytes());
+        }
+        let mut n = len as u32;
+        if key_order == KeyOrder::LengthFirst {
+            n |= OBJECT_LENGTH_FIRST;
+        }
+        buffer.put_u32_ne(n);
+        buffer.put_u32_ne((buffer.len() - start + 4) as u32);
+
+        let offset = self.offset();
+        self.pointers.push(Entry::object(offset));
+    }
+
+    /// Ends an object, returning an error if:
+    /// - there is no object to end.
     /// - there is an odd number of entries pushed since the paired [`begin_object`].
     /// - any key is not a string.
+    /// - there are duplicate keys and the policy is [`DuplicateKeyPolicy::Error`].
+    ///
+    /// On error, the object is left unfinished with its pending entries unchanged. Use
+    /// [`rollback_to`] to discard it.
     ///
     /// [`begin_object`]: #method.begin_object
-    pub fn end_object(&mut self) {
+    /// [`rollback_to`]: #method.rollback_to
+    pub fn try_end_object(&mut self) -> Result<(), Error> {
+        let Some(&(_, npointer)) = self.container_starts.last() else {
+            return Err(Error::builder("no object to end"));
+        };
+        let pending = &self.pointers[npointer..];
+        if !pending.len().is_multiple_of(2) {
+            return Err(Error::builder("expected even number of entries"));
+        }
+        if pending.iter().step_by(2).any(|k| !k.is_string()) {
+            return Err(Error::builder("key must be string"));
+        }
+        if self.duplicate_key_policy == DuplicateKeyPolicy::Collect {
+            self.collect_duplicate_keys();
+        }
+
         let buffer = self.buffer.as_mut();
-        let (start, npointer) = self.container_starts.pop().unwrap();
-        assert!(
-            (self.pointers.len() - npointer).is_multiple_of(2),
-            "expected even number of entries"
-        );
+        let &(start, npointer) = self.container_starts.last().unwrap();
         let len = (self.pointers.len() - npointer) / 2;
 
         // sort entries by key
@@ -255,9 +639,6 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
                 len,
             )
         };
-        for (k, _) in entries.iter() {
-            assert!(k.is_string(), "key must be string");
-        }
         let entry_to_str = |entry: Entry| {
             // Performance tip: this closure is in hot path, so we use `unsafe` to avoid bound check.
             // SAFETY: the string is pushed by us, so it's valid UTF-8 and the range is valid.
@@ -267,7 +648,26 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
                 std::str::from_utf8_unchecked(buffer.get_unchecked(offset + 4..offset + 4 + len))
             }
         };
-        entries.sort_by_key(|(k, _)| entry_to_str(*k));
+        // the pending entries to restore on error
+        let original =
+            (self.duplicate_key_policy == DuplicateKeyPolicy::Error).then(|| entries.to_vec());
+        // the insertion position of each entry, only if preserving order
+        let mut positions = vec![];
+        if self.preserve_order {
+            positions = (0..len).collect::<Vec<_>>();
+            positions.sort_by(|&i, &j| {
+                let order = self.key_order;
+                order.cmp(entry_to_str(entries[i].0), entry_to_str(entries[j].0))
+            });
+            let sorted = positions.iter().map(|&i| entries[i]).collect::<Vec<_>>();
+            entries.copy_from_slice(&sorted);
+        } else {
+            match self.key_order {
+                KeyOrder::Lexicographic => entries.sort_by_key(|(k, _)| entry_to_str(*k)),
+                order => entries
+                    .sort_by(|(k1, _), (k2, _)| order.cmp(entry_to_str(*k1), entry_to_str(*k2))),
+            }
+        }
 
         // deduplicate keys
         let mut prev_key = None;
@@ -277,11 +677,25 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
             if prev_key != Some(key) {
                 prev_key = Some(key);
                 entries[unique_len] = entries[i];
+                if !positions.is_empty() {
+                    positions[unique_len] = positions[i];
+                }
                 unique_len += 1;
             } else {
-                entries[unique_len - 1] = entries[i];
+                match self.duplicate_key_policy {
+                    DuplicateKeyPolicy::LastWins | DuplicateKeyPolicy::Collect => {
+                        entries[unique_len - 1] = entries[i]
+                    }
+                    DuplicateKeyPolicy::FirstWins => {}
+                    DuplicateKeyPolicy::Error => {
+                        let key = key.to_owned();
+                        entries.copy_from_slice(original.as_deref().unwrap());
+                        return Err(Error::DuplicateKey { key });
+                    }
+                }
             }
         }
+        self.container_starts.pop();
 
         // remove data if there are duplicates
         if unique_len != len {
@@ -302,6 +716,8 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
                     v.offset() + 4 + (&data[v.offset()..]).get_u32_ne() as usize
                 } else if v.is_array() || v.is_object() {
                     v.offset()
+                } else if v.is_raw_json() {
+                    v.offset() + raw_json_size(&data[v.offset()..])
                 } else {
                     // null, false, true: no data for value
                     begin + 4 + (&data[begin..]).get_u32_ne() as usize
@@ -328,16 +744,154 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
             buffer.put_slice(kentry.as_bytes());
             buffer.put_slice(ventry.as_bytes());
         }
-        buffer.put_u32_ne(unique_len as u32);
+        let mut n = unique_len as u32;
+        if self.key_order == KeyOrder::LengthFirst {
+            n |= OBJECT_LENGTH_FIRST;
+        }
+        if self.preserve_order {
+            // write the permutation from insertion order to sorted order
+            let mut permutation = (0..unique_len as u32).collect::<Vec<_>>();
+            permutation.sort_unstable_by_key(|&i| positions[i as usize]);
+            buffer.reserve(4 * unique_len);
+            for i in permutation {
+                buffer.put_u32_ne(i);
+            }
+            n |= OBJECT_PRESERVE_ORDER;
+        }
+        buffer.put_u32_ne(n);
         buffer.put_u32_ne((buffer.len() - start + 4) as u32);
 
         let offset = self.offset();
         self.pointers.truncate(npointer);
         self.pointers.push(Entry::object(offset));
+        Ok(())
+    }
+
+    /// Rebuilds the current object by collecting the values of each duplicate key into an array.
+    ///
+    /// Keys are kept at the position of their first occurrence.
+    fn collect_duplicate_keys(&mut self) {
+        let &(start, npointer) = self.container_starts.last().unwrap();
+        let entries = self.pointers[npointer..]
+            .chunks_exact(2)
+            .map(|pair| (pair[0], pair[1]))
+            .collect::<Vec<_>>();
+        let data = &self.buffer.as_mut()[start..];
+        let key = |entry: Entry| {
+            ValueRef::from_slice(data, entry)
+                .as_str()
+                .expect("key must be string")
+        };
+
+        // group entries by key, then order groups by their first occurrence
+        let mut indices = (0..entries.len()).collect::<Vec<_>>();
+        indices.sort_by_key(|&i| key(entries[i].0));
+        let mut groups: Vec<&[usize]> = indices
+            .chunk_by(|&i, &j| key(entries[i].0) == key(entries[j].0))
+            .collect();
+        if groups.len() == entries.len() {
+            return;
+        }
+        groups.sort_unstable_by_key(|group| group[0]);
+
+        let data = data.to_vec();
+        self.buffer.as_mut().truncate(start);
+        self.pointers.truncate(npointer);
+        for group in groups {
+            let (kentry, ventry) = entries[group[0]];
+            self.add_value(ValueRef::from_slice(&data, kentry));
+            if group.len() == 1 {
+                self.copy_value(ValueRef::from_slice(&data, ventry));
+                continue;
+            }
+            self.begin_array();
+            for &i in group {
+                self.copy_value(ValueRef::from_slice(&data, entries[i].1));
+            }
+            self.end_array();
+        }
+    }
+
+    /// Adds a value like [`add_value`](Self::add_value), but keeps raw JSON as is.
+    fn copy_value(&mut self, value: ValueRef<'_>) {
+        let ValueRef::RawJson(raw) = value else {
+            return self.add_value(value);
+        };
+        let offset = self.offset();
+        self.pointers.push(Entry::raw_json(offset));
+        self.buffer.as_mut().put_slice(raw.as_slice());
+    }
+
+    /// Adds JSON text to the builder, to be printed verbatim.
+    ///
+    /// The text is stored as is, without surrounding whitespace, along with its value parsed
+    /// with the settings of this builder. It is printed verbatim by `Display`, by
+    /// [`ValueRef::to_writer`] with default options and by `serde_json`. Other output options
+    /// and PostgreSQL text formatting print the value. All other methods of [`ValueRef`] see the
+    /// value. [`add_value`](Self::add_value) parses the text into a regular value.
+    ///
+    /// Returns an error if the text is not valid JSON or has duplicate keys and the policy is
+    /// [`DuplicateKeyPolicy::Error`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// builder.begin_object();
+    /// builder.add_string("raw");
+    /// builder.add_raw_json(r#" {"b": 1,  "a": [true]} "#).unwrap();
+    /// builder.end_object();
+    /// let value = builder.finish();
+    /// assert_eq!(value.to_string(), r#"{"raw":{"b": 1,  "a": [true]}}"#);
+    ///
+    /// let raw = value.get("raw").unwrap();
+    /// assert!(raw.is_raw_json() && raw.is_object());
+    /// assert_eq!(raw.as_raw_json().unwrap().parse().to_string(), r#"{"a":[true],"b":1}"#);
+    ///
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// assert!(builder.add_raw_json(r#"{"a": }"#).is_err());
+    /// ```
+    pub fn add_raw_json(&mut self, json: &str) -> Result<(), Error> {
+        let json = json.trim_matches([' ', '\n', '\r', '\t']);
+        let mut builder = Builder::<Vec<u8>>::with_capacity(json.len())
+            .with_key_order(self.key_order)
+            .with_preserve_order(self.preserve_order)
+            .with_duplicate_key_policy(self.duplicate_key_policy)
+            .with_non_finite_policy(self.non_finite_policy);
+        parser::parse_str(json, &mut builder)?;
+        let value = builder.finish();
+        let offset = self.offset();
+        self.pointers.push(Entry::raw_json(offset));
+        let buffer = self.buffer.as_mut();
+        buffer.put_u32_ne(json.len().try_into().expect("string too long"));
+        buffer.put_slice(json.as_bytes());
+        buffer.put_u32_ne(value.as_bytes().len().try_into().expect("value too long"));
+        buffer.put_slice(value.as_bytes());
+        Ok(())
     }
 
     /// Adds a JSON value to the builder.
+    ///
+    /// Raw JSON text is parsed into a regular value, but raw JSON nested in arrays and objects is
+    /// copied as is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`try_add_value`] returns an error.
+    ///
+    /// [`try_add_value`]: #method.try_add_value
     pub fn add_value(&mut self, value: ValueRef<'_>) {
+        if let Err(e) = self.try_add_value(value) {
+            panic!("{e}");
+        }
+    }
+
+    /// Adds a JSON value to the builder, returning an error if the value is raw JSON that:
+    /// - is not valid JSON text.
+    /// - has duplicate keys and the policy is [`DuplicateKeyPolicy::Error`].
+    ///
+    /// On error, nothing is added.
+    pub fn try_add_value(&mut self, value: ValueRef<'_>) -> Result<(), Error> {
         match value {
             ValueRef::Null => self.add_null(),
             ValueRef::Bool(b) => self.add_bool(b),
@@ -365,7 +919,19 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
                 let offset = self.offset();
                 self.pointers.push(Entry::object(offset));
             }
+            ValueRef::RawJson(r) => {
+                let buffer_length = self.buffer.as_mut().len();
+                let pointer_length = self.pointers.len();
+                let container_starts_length = self.container_starts.len();
+                if let Err(e) = parser::parse_str(r.as_str(), self) {
+                    self.buffer.as_mut().truncate(buffer_length);
+                    self.pointers.truncate(pointer_length);
+                    self.container_starts.truncate(container_starts_length);
+                    return Err(e);
+                }
+            }
         }
+        Ok(())
     }
 
     /// Finishes building.
@@ -380,7 +946,7 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
 
     /// Get the current offset from the array/object start.
     fn offset(&mut self) -> usize {
-        self.buffer.as_mut().len() - self.container_starts.last().map_or(0, |&(o, _)| o)
+        self.buffer.as_mut().len() - self.container_starts.last().map_or(self.start, |&(o, _)| o)
     }
 
     /// Pops the last value.
@@ -391,7 +957,7 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
             return;
         }
         let buffer = self.buffer.as_mut();
-        let new_len = entry.offset() + self.container_starts.last().map_or(0, |&(o, _)| o);
+        let new_len = entry.offset() + self.container_starts.last().map_or(self.start, |&(o, _)| o);
         buffer.truncate(new_len);
         if entry.is_array() || entry.is_object() {
             let len = (&buffer[new_len - 4..]).get_u32_ne() as usize;
@@ -473,7 +1039,127 @@ impl Builder<&mut Vec<u8>> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Builder, Value};
+    use crate::{Builder, Error, NonFinitePolicy, Value, ValueRef};
+
+    #[test]
+    fn raw_json() {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        for json in [
+            "null",
+            " true",
+            "-1.5e2 ",
+            r#""\u0041""#,
+            "[1, {}]",
+            "{\"b\": 2,\n\"a\": 1}",
+        ] {
+            builder.add_raw_json(json).unwrap();
+        }
+        builder.add_raw_json("12345678901234567890").unwrap();
+        builder.add_raw_json("[]").unwrap();
+        builder.pop();
+        builder.end_array();
+        let value = builder.finish();
+        assert_eq!(
+            value.to_string(),
+            "[null,true,-1.5e2,\"\\u0041\",[1, {}],{\"b\": 2,\n\"a\": 1},12345678901234567890]"
+        );
+        assert_eq!(
+            ValueRef::try_from_bytes(value.as_bytes()).unwrap(),
+            value.as_ref()
+        );
+
+        let array = value.as_array().unwrap();
+        assert!(array.iter().all(|v| v.is_raw_json()));
+        assert!(array.get(0).unwrap().is_null());
+        assert_eq!(array.get(0).unwrap().as_null(), Some(()));
+        assert_eq!(array.get(1).unwrap().as_bool(), Some(true));
+        assert_eq!(array.get(2).unwrap().as_f64(), Some(-150.0));
+        assert!(array.get(3).unwrap().is_string());
+        assert_eq!(array.get(3).unwrap().as_str(), Some("A"));
+        assert!(array.get(4).unwrap().is_array());
+        assert_eq!(
+            array.get(4).unwrap().pointer("/1").unwrap().to_string(),
+            "{}"
+        );
+        assert!(array.get(5).unwrap().is_object());
+        assert_eq!(array.get(5).unwrap().get("a").unwrap().as_u64(), Some(1));
+        assert_eq!(array.get(5).unwrap().as_object().unwrap().len(), 2);
+        assert_eq!(array.get(6).unwrap().as_u64(), Some(12345678901234567890));
+
+        // materialized by `add_value`
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        for v in array.iter() {
+            builder.add_value(v);
+        }
+        builder.end_array();
+        let parsed = builder.finish();
+        let expected: Value = r#"[null,true,-150.0,"A",[1,{}],{"a":1,"b":2},12345678901234567890]"#
+            .parse()
+            .unwrap();
+        assert_eq!(parsed, expected);
+        assert!(parsed.as_array().unwrap().iter().all(|v| !v.is_raw_json()));
+
+        // serialized verbatim
+        assert_eq!(serde_json::to_string(&value).unwrap(), value.to_string());
+        assert_eq!(
+            serde_json::Value::from(value.as_ref()),
+            serde_json::Value::from(expected.as_ref())
+        );
+        // and kept as raw JSON when serialized into a builder
+        let copied = crate::to_value(&value).unwrap();
+        assert_eq!(copied.to_string(), value.to_string());
+        assert!(copied.as_array().unwrap().iter().all(|v| v.is_raw_json()));
+        let raw = serde_json::value::RawValue::from_string("[1 ,2]".into()).unwrap();
+        let copied = crate::to_value(std::collections::BTreeMap::from([("a", raw)])).unwrap();
+        assert_eq!(copied.to_string(), r#"{"a":[1 ,2]}"#);
+
+        // compared and hashed by value, in PostgreSQL's type order
+        let hasher = std::hash::RandomState::new();
+        let hash = |v: ValueRef<'_>| std::hash::BuildHasher::hash_one(&hasher, v);
+        for (raw, parsed) in array.iter().zip(expected.as_array().unwrap().iter()) {
+            assert_eq!(raw, parsed);
+            assert_eq!(raw.cmp(&parsed), std::cmp::Ordering::Equal);
+            assert_eq!(hash(raw), hash(parsed));
+        }
+        assert!(array.get(5).unwrap() > expected.pointer("/4").unwrap());
+        assert!(array.get(0).unwrap() < expected.pointer("/3").unwrap());
+        assert!(array.get(4).unwrap() > array.get(6).unwrap());
+
+        assert!(!array.get(4).unwrap().is_u64());
+        assert!(!array.get(5).unwrap().is_f64());
+        assert!(array.get(6).unwrap().is_u64() && !array.get(6).unwrap().is_i64());
+
+        for json in ["", " ", "[1,]", "{} {}", "tru", "\"\\x\""] {
+            let mut builder = Builder::<Vec<u8>>::new();
+            assert!(builder.add_raw_json(json).is_err(), "{json}");
+        }
+
+        // `try_add_value` reports duplicate keys in raw JSON and adds nothing
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.add_raw_json(r#"{"a": [1], "a": 2}"#).unwrap();
+        let duplicate = builder.finish();
+        let mut builder =
+            Builder::<Vec<u8>>::new().with_duplicate_key_policy(crate::DuplicateKeyPolicy::Error);
+        builder.begin_array();
+        assert_eq!(
+            builder
+                .try_add_value(duplicate.as_ref())
+                .unwrap_err()
+                .to_string(),
+            "duplicate key `a` at line 1 column 18"
+        );
+        builder.add_null();
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[null]");
+
+        // invalid raw JSON is rejected by validation
+        let mut bytes = value.as_bytes().to_vec();
+        let pos = bytes.windows(4).position(|w| w == b"true").unwrap();
+        bytes[pos] = b'x';
+        assert!(ValueRef::try_from_bytes(&bytes).is_err());
+    }
 
     #[test]
     fn unique_key() {
@@ -487,6 +1173,245 @@ mod tests {
         );
     }
 
+    #[test]
+    fn key_order() {
+        use crate::KeyOrder;
+        use serde::de::DeserializeSeed;
+        use std::hash::BuildHasher;
+
+        let json = r#"{"bb":1,"a":{"ccc":2,"dd":3},"c":4,"a":{"ccc":5,"dd":6},"aaa":null}"#;
+        let mut builder = Builder::<Vec<u8>>::new().with_key_order(KeyOrder::LengthFirst);
+        builder
+            .deserialize(&mut serde_json::Deserializer::from_str(json))
+            .unwrap();
+        let value = builder.finish();
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":{"dd":6,"ccc":5},"c":4,"bb":1,"aaa":null}"#
+        );
+        let object = value.as_object().unwrap();
+        assert_eq!(object.key_order(), KeyOrder::LengthFirst);
+        assert_eq!(object.len(), 4);
+        assert_eq!(object.get("bb").unwrap().as_u64(), Some(1));
+        assert_eq!(object.get("aaa").unwrap().as_null(), Some(()));
+        assert!(object.contains_key("c"));
+        assert!(!object.contains_key("b"));
+
+        // compare with lexicographic order
+        let lex: Value = json.parse().unwrap();
+        assert_eq!(
+            lex.as_object().unwrap().key_order(),
+            KeyOrder::Lexicographic
+        );
+        assert_eq!(value, lex);
+        assert_eq!(value.cmp(&lex), std::cmp::Ordering::Equal);
+        let state = std::collections::hash_map::RandomState::new();
+        assert_eq!(state.hash_one(&value), state.hash_one(&lex));
+        assert_eq!(value.to_pg_text(), lex.to_pg_text());
+
+        // objects are ordered in their key order, and lexicographically if the orders differ
+        let objects = [
+            r#"{"b":1,"aa":1}"#,
+            r#"{"b":1,"aa":2}"#,
+            r#"{"b":0,"ab":1}"#,
+        ];
+        let mut values = vec![];
+        for json in objects {
+            for key_order in [KeyOrder::Lexicographic, KeyOrder::LengthFirst] {
+                let mut builder = Builder::<Vec<u8>>::new().with_key_order(key_order);
+                builder
+                    .deserialize(&mut serde_json::Deserializer::from_str(json))
+                    .unwrap();
+                values.push(builder.finish());
+            }
+        }
+        let lex = |v: &Value| v.to_string().parse::<Value>().unwrap();
+        for a in &values {
+            for b in &values {
+                let (ka, kb) = (a.as_object().unwrap(), b.as_object().unwrap());
+                if ka.key_order() != KeyOrder::LengthFirst
+                    || kb.key_order() != KeyOrder::LengthFirst
+                {
+                    assert_eq!(a.cmp(b), lex(a).cmp(&lex(b)), "{a} {b}");
+                }
+            }
+        }
+        // as PostgreSQL: '{"b":0,"ab":1}' < '{"b":1,"aa":1}' < '{"b":1,"aa":2}'
+        assert!(values[5] < values[1] && values[1] < values[3] && values[5] < values[3]);
+        assert!(lex(&values[5]) > lex(&values[3]));
+
+        // the order is kept when copied into another builder
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        builder.add_value(value.as_ref());
+        builder.end_array();
+        assert_eq!(
+            builder.finish().to_string(),
+            r#"[{"a":{"dd":6,"ccc":5},"c":4,"bb":1,"aaa":null}]"#
+        );
+    }
+
+    #[test]
+    fn preserve_order() {
+        use crate::KeyOrder;
+        use serde::de::DeserializeSeed;
+
+        let json = r#"{"c":1,"a":{"z":1,"y":[]},"bb":null,"c":2,"d":"x"}"#;
+        for order in [KeyOrder::Lexicographic, KeyOrder::LengthFirst] {
+            let mut builder = Builder::<Vec<u8>>::new()
+                .with_key_order(order)
+                .with_preserve_order(true);
+            builder
+                .deserialize(&mut serde_json::Deserializer::from_str(json))
+                .unwrap();
+            let value = builder.finish();
+            assert_eq!(
+                value.to_string(),
+                r#"{"c":2,"a":{"z":1,"y":[]},"bb":null,"d":"x"}"#
+            );
+            let object = value.as_object().unwrap();
+            assert!(object.preserves_order());
+            assert_eq!(object.key_order(), order);
+            assert_eq!(object.len(), 4);
+            assert_eq!(object.keys().collect::<Vec<_>>(), ["c", "a", "bb", "d"]);
+            for (k, v) in object.iter() {
+                assert_eq!(object.get(k), Some(v));
+            }
+            assert!(!object.contains_key("b"));
+
+            // insertion order does not affect equality
+            let sorted: Value = json.parse().unwrap();
+            assert_eq!(value, sorted);
+            assert_eq!(value.cmp(&sorted), std::cmp::Ordering::Equal);
+            assert_eq!(
+                value.to_pg_text(),
+                r#"{"a": {"y": [], "z": 1}, "c": 2, "d": "x", "bb": null}"#
+            );
+        }
+    }
+
+    #[test]
+    fn duplicate_key_policy() {
+        use crate::DuplicateKeyPolicy;
+
+        let json = br#"{"a":1,"b":2,"c":{"x":null,"x":[0]},"a":{},"b":"b","a":[3]}"#;
+        let parse = |policy| Value::from_text_with_policy(json, policy);
+        assert_eq!(
+            parse(DuplicateKeyPolicy::LastWins).unwrap().to_string(),
+            r#"{"a":[3],"b":"b","c":{"x":[0]}}"#
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::FirstWins).unwrap().to_string(),
+            r#"{"a":1,"b":2,"c":{"x":null}}"#
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::Collect).unwrap().to_string(),
+            r#"{"a":[1,{},[3]],"b":[2,"b"],"c":{"x":[null,[0]]}}"#
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::Error).unwrap_err().to_string(),
+            "duplicate key `x` at line 1 column 35"
+        );
+
+        // collect with insertion order
+        let mut builder = Builder::<Vec<u8>>::new()
+            .with_duplicate_key_policy(DuplicateKeyPolicy::Collect)
+            .with_preserve_order(true);
+        builder.begin_object();
+        for (k, v) in [("b", 1), ("a", 2), ("b", 3)] {
+            builder.add_string(k);
+            builder.add_u64(v);
+        }
+        builder.end_object();
+        assert_eq!(builder.finish().to_string(), r#"{"b":[1,3],"a":2}"#);
+
+        // the object is left unfinished on error
+        let mut builder =
+            Builder::<Vec<u8>>::new().with_duplicate_key_policy(DuplicateKeyPolicy::Error);
+        builder.begin_array();
+        let checkpoint = builder.checkpoint();
+        builder.begin_object();
+        builder.add_string("a");
+        builder.add_null();
+        builder.add_string("a");
+        builder.add_null();
+        let err = builder.try_end_object().unwrap_err();
+        assert!(matches!(err, Error::DuplicateKey { key } if key == "a"));
+        builder.rollback_to(&checkpoint);
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[]");
+    }
+
+    #[test]
+    fn duplicate_raw_json_keys() {
+        use crate::DuplicateKeyPolicy;
+
+        let build = |policy| {
+            let mut builder = Builder::<Vec<u8>>::new().with_duplicate_key_policy(policy);
+            builder.begin_object();
+            for (k, v) in [
+                ("b", "[1, 2]"),
+                ("a", r#"{"x": 1}"#),
+                ("b", "3"),
+                ("c", "4"),
+            ] {
+                builder.add_string(k);
+                builder.add_raw_json(v).unwrap();
+            }
+            builder.try_end_object().map(|()| builder.finish())
+        };
+        for (policy, expected) in [
+            (
+                DuplicateKeyPolicy::LastWins,
+                r#"{"a":{"x": 1},"b":3,"c":4}"#,
+            ),
+            (
+                DuplicateKeyPolicy::FirstWins,
+                r#"{"a":{"x": 1},"b":[1, 2],"c":4}"#,
+            ),
+            (
+                DuplicateKeyPolicy::Collect,
+                r#"{"a":{"x": 1},"b":[[1, 2],3],"c":4}"#,
+            ),
+        ] {
+            let value = build(policy).unwrap();
+            assert_eq!(value.to_string(), expected, "{policy:?}");
+            assert_eq!(
+                ValueRef::try_from_bytes(value.as_bytes()).unwrap(),
+                value.as_ref()
+            );
+            let raw = value.get("a").unwrap();
+            assert!(raw.is_raw_json(), "{policy:?}");
+        }
+        assert!(matches!(
+            build(DuplicateKeyPolicy::Error),
+            Err(Error::DuplicateKey { key }) if key == "b"
+        ));
+    }
+
+    #[test]
+    fn mut_vec_offsets() {
+        let build = |builder: &mut Builder<&mut Vec<u8>>| {
+            builder.begin_array();
+            builder.add_string("a");
+            builder.end_array();
+        };
+
+        // `new` encodes relative to the start of the buffer
+        let mut buffer = b"prefix".to_vec();
+        let mut builder = Builder::<&mut Vec<u8>>::new(&mut buffer);
+        build(&mut builder);
+        builder.finish();
+        assert_eq!(ValueRef::from_bytes(&buffer).to_string(), r#"["a"]"#);
+
+        // `appending` encodes relative to the end of the buffer
+        let mut buffer = b"prefix".to_vec();
+        let mut builder = Builder::<&mut Vec<u8>>::appending(&mut buffer);
+        build(&mut builder);
+        builder.finish();
+        assert_eq!(ValueRef::from_bytes(&buffer[6..]).to_string(), r#"["a"]"#);
+    }
+
     #[test]
     fn pop() {
         let mut builder = Builder::<Vec<u8>>::new();
@@ -524,6 +1449,65 @@ mod tests {
         assert_eq!(value.to_string(), "[1,4]");
     }
 
+    #[test]
+    fn end_object_misuse() {
+        let mut builder = Builder::<Vec<u8>>::new();
+        let err = builder.try_end_object().unwrap_err();
+        assert_eq!(err.to_string(), "invalid use of builder: no object to end");
+
+        builder.begin_object();
+        builder.add_string("a");
+        let err = builder.try_end_object().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid use of builder: expected even number of entries"
+        );
+        builder.pop();
+        builder.add_u64(1);
+        builder.add_u64(2);
+        assert!(matches!(
+            builder.try_end_object(),
+            Err(Error::Builder { message }) if message == "key must be string"
+        ));
+
+        // the pending entries are unchanged after a duplicate key error
+        let mut builder =
+            Builder::<Vec<u8>>::new().with_duplicate_key_policy(crate::DuplicateKeyPolicy::Error);
+        builder.begin_object();
+        for (k, v) in [("c", 1), ("a", 2), ("c", 3), ("b", 4)] {
+            builder.add_string(k);
+            builder.add_u64(v);
+        }
+        let err = builder.try_end_object().unwrap_err();
+        assert!(matches!(err, Error::DuplicateKey { key } if key == "c"));
+        builder.pop();
+        builder.pop();
+        builder.pop();
+        builder.pop();
+        builder.end_object();
+        assert_eq!(builder.finish().to_string(), r#"{"a":2,"c":1}"#);
+    }
+
+    #[test]
+    fn non_finite_policy() {
+        let mut builder = Builder::<Vec<u8>>::new();
+        let err = builder.try_add_f64(f64::INFINITY).unwrap_err();
+        assert!(matches!(err, Error::NonFinite { value } if value == f64::INFINITY));
+        builder.add_f64(1.5);
+        assert_eq!(builder.finish().to_string(), "1.5");
+
+        let mut builder = Builder::<Vec<u8>>::new().with_non_finite_policy(NonFinitePolicy::Null);
+        builder.begin_array();
+        builder.add_f64(f64::NAN);
+        builder.add_f64(f64::INFINITY);
+        builder.end_array();
+        assert_eq!(builder.finish().to_string(), "[null,null]");
+
+        // the serializer returns an error instead of panicking
+        let mut builder = Builder::<Vec<u8>>::new();
+        assert!(serde::Serialize::serialize(&[1.0, f64::NAN], &mut builder).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn rollback_invalid() {
@@ -29,6 +29,7 @@ impl Entry {
     pub const TRUE_TAG: u32 = 4;
     pub const ARRAY_TAG: u32 = 5;
     pub const OBJECT_TAG: u32 = 6;
+    pub const RAW_JSON_TAG: u32 = 7;
 
     pub const fn tag(self) -> u32 {
         u32::from_ne_bytes(self.0) >> 29
@@ -78,6 +79,11 @@ impl Entry {
         Self::from_u32((Self::OBJECT_TAG << 29) | (offset as u32))
     }
 
+    pub const fn raw_json(offset: usize) -> Self {
+        assert!(offset <= Self::LEN_MASK as usize, "offset too large");
+        Self::from_u32((Self::RAW_JSON_TAG << 29) | (offset as u32))
+    }
+
     pub const fn is_number(self) -> bool {
         self.tag() == Self::NUMBER_TAG
     }
@@ -94,6 +100,10 @@ impl Entry {
         self.tag() == Self::OBJECT_TAG
     }
 
+    pub const fn is_raw_json(self) -> bool {
+        self.tag() == Self::RAW_JSON_TAG
+    }
+
     pub fn set_offset(&mut self, offset: usize) {
         assert!(offset <= Self::LEN_MASK as usize, "offset too large");
         self.0 = ((self.tag() << 29) | (offset as u32)).to_ne_bytes();
@@ -0,0 +1,240 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::{self, Display};
+use std::io;
+
+/// The error type for all fallible operations in this crate.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The JSON text is invalid or violates the parse options.
+    Parse {
+        /// The description of the error.
+        message: String,
+        /// The 1-based line number at which the error was detected.
+        line: usize,
+        /// The 1-based column number at which the error was detected, counted in bytes.
+        column: usize,
+    },
+    /// A Rust value could not be serialized into a jsonbb value.
+    Serialize {
+        /// The description of the error.
+        message: String,
+        /// The JSON pointer to the offending field, empty for the root value.
+        path: String,
+    },
+    /// An object contains duplicate keys and the policy is [`DuplicateKeyPolicy::Error`].
+    ///
+    /// [`DuplicateKeyPolicy::Error`]: crate::DuplicateKeyPolicy::Error
+    DuplicateKey {
+        /// The duplicate key.
+        key: String,
+    },
+    /// A number is infinite or NaN and the policy is [`NonFinitePolicy::Error`].
+    ///
+    /// [`NonFinitePolicy::Error`]: crate::NonFinitePolicy::Error
+    NonFinite {
+        /// The non-finite number.
+        value: f64,
+    },
+    /// The [`Builder`](crate::Builder) is used incorrectly, e.g. ending an object with a
+    /// non-string key.
+    Builder {
+        /// The description of the error.
+        message: String,
+    },
+    /// The bytes are not a valid jsonbb encoding.
+    Decode {
+        /// The description of the error.
+        message: String,
+    },
+    /// An error occurred while evaluating a JSON path expression.
+    JsonPath {
+        /// The description of the error.
+        message: String,
+    },
+    /// An error occurred while evaluating a jq filter.
+    Jq {
+        /// The description of the error.
+        message: String,
+    },
+    /// A `jsonb` operator was applied to a value it does not support, e.g. deleting a key from a
+    /// scalar.
+    Operator {
+        /// The description of the error.
+        message: String,
+    },
+    /// An I/O error occurred while writing JSON text.
+    Io(io::Error),
+}
+
+impl Error {
+    /// Creates a parse error at the byte position of the JSON text.
+    #[cold]
+    pub(crate) fn parse(message: impl Into<String>, json: &[u8], pos: usize) -> Self {
+        let consumed = &json[..pos.min(json.len())];
+        let line = consumed.iter().filter(|&&c| c == b'\n').count() + 1;
+        let line_start = consumed
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1);
+        Error::Parse {
+            message: message.into(),
+            line,
+            column: consumed.len() - line_start + 1,
+        }
+    }
+
+    /// Converts the location of a parse error in a text to the location in the whole input,
+    /// given the line and column where the text starts.
+    pub(crate) fn relocate(self, (start_line, start_column): (usize, usize)) -> Self {
+        match self {
+            Error::Parse {
+                message,
+                line,
+                column,
+            } => Error::Parse {
+                message,
+                line: start_line + line - 1,
+                column: if line == 1 {
+                    start_column + column - 1
+                } else {
+                    column
+                },
+            },
+            e => e,
+        }
+    }
+
+    /// Creates a serialization error at the root value.
+    #[cold]
+    pub(crate) fn serialize(message: impl Display) -> Self {
+        Error::Serialize {
+            message: message.to_string(),
+            path: String::new(),
+        }
+    }
+
+    /// Creates a builder misuse error.
+    #[cold]
+    pub(crate) fn builder(message: &str) -> Self {
+        Error::Builder {
+            message: message.into(),
+        }
+    }
+
+    /// Creates a decode error.
+    #[cold]
+    pub(crate) fn decode(message: &str) -> Self {
+        Error::Decode {
+            message: message.into(),
+        }
+    }
+
+    /// Prepends a key or index to the path of a serialization error.
+    ///
+    /// Other errors are converted to serialization errors first.
+    #[cold]
+    pub(crate) fn in_field(self, segment: impl Display) -> Self {
+        let (message, path) = match self {
+            Error::Serialize { message, path } => (message, path),
+            e => (e.to_string(), String::new()),
+        };
+        let segment = segment.to_string().replace('~', "~0").replace('/', "~1");
+        Error::Serialize {
+            message,
+            path: format!("/{segment}{path}"),
+        }
+    }
+}
+
+/// Returns the 1-based line and column after the bytes, given the line and column where they
+/// start.
+pub(crate) fn advance((line, column): (usize, usize), bytes: &[u8]) -> (usize, usize) {
+    match bytes.iter().rposition(|&c| c == b'\n') {
+        Some(i) => (
+            line + bytes.iter().filter(|&&c| c == b'\n').count(),
+            bytes.len() - i,
+        ),
+        None => (line, column + bytes.len()),
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse {
+                message,
+                line,
+                column,
+            } => write!(f, "{message} at line {line} column {column}"),
+            Error::Serialize { message, path } if path.is_empty() => write!(f, "{message}"),
+            Error::Serialize { message, path } => write!(f, "{message} at {path}"),
+            Error::DuplicateKey { key } => write!(f, "duplicate key `{key}`"),
+            Error::NonFinite { value } => {
+                write!(f, "Infinite or NaN values are not JSON numbers: {value}")
+            }
+            Error::Builder { message } => write!(f, "invalid use of builder: {message}"),
+            Error::Decode { message } => write!(f, "invalid jsonbb encoding: {message}"),
+            Error::JsonPath { message } => write!(f, "{message}"),
+            Error::Jq { message } => write!(f, "{message}"),
+            Error::Operator { message } => write!(f, "{message}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::serialize(msg)
+    }
+}
+
+#[cfg(feature = "simd-json")]
+impl Error {
+    /// Converts an error of `simd-json` while parsing the JSON text.
+    pub(crate) fn from_simd_json(e: simd_json::Error, json: &[u8]) -> Self {
+        Error::parse(format!("{:?}", e.error()), json, e.index())
+    }
+}
+
+#[cfg(feature = "sonic-rs")]
+impl From<sonic_rs::Error> for Error {
+    fn from(e: sonic_rs::Error) -> Self {
+        let (line, column) = (e.line(), e.column());
+        let message = e.to_string();
+        let suffix = format!(" at line {line} column {column}");
+        Error::Parse {
+            message: message.strip_suffix(&suffix).unwrap_or(&message).into(),
+            line,
+            column,
+        }
+    }
+}
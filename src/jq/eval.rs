@@ -0,0 +1,818 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evaluator of jq filters.
+
+use std::cmp::Ordering;
+
+use super::parser::{ArithOp, Builtin, CompareOp, Expr};
+use crate::jsonpath::JsonItem;
+use crate::{Builder, Error, NumberRef, ObjectRef, Value, ValueRef};
+
+type Items<'a> = Vec<JsonItem<'a>>;
+
+/// Variables bound by `as` and `reduce`, innermost first.
+pub(super) struct Env<'e, 'a> {
+    name: &'e str,
+    value: JsonItem<'a>,
+    parent: Option<&'e Env<'e, 'a>>,
+}
+
+impl<'a> Env<'_, 'a> {
+    fn get(&self, name: &str) -> &JsonItem<'a> {
+        if self.name == name {
+            return &self.value;
+        }
+        // variables are checked by the parser
+        self.parent.expect("undefined variable").get(name)
+    }
+}
+
+#[cold]
+fn error(message: String) -> Error {
+    Error::Jq { message }
+}
+
+/// Describes a value in error messages, e.g. `number (1)`.
+fn describe(value: ValueRef<'_>) -> String {
+    format!("{} ({value})", type_name(value))
+}
+
+fn type_name(value: ValueRef<'_>) -> &'static str {
+    match value {
+        ValueRef::Null => "null",
+        ValueRef::Bool(_) => "boolean",
+        ValueRef::Number(_) => "number",
+        ValueRef::String(_) => "string",
+        ValueRef::Array(_) => "array",
+        ValueRef::Object(_) => "object",
+        ValueRef::RawJson(raw) => type_name(raw.value()),
+    }
+}
+
+/// Returns false for `null` and `false`, and true for all other values.
+fn is_truthy(value: ValueRef<'_>) -> bool {
+    !matches!(value, ValueRef::Null | ValueRef::Bool(false))
+}
+
+/// Calls `f` with the value of the item and pushes the values it emits as items.
+///
+/// Values inside an owned item are copied, while values inside a borrowed item are borrowed.
+fn with_children<'a, F>(item: &JsonItem<'a>, items: &mut Items<'a>, f: F) -> Result<(), Error>
+where
+    F: for<'b> FnOnce(ValueRef<'b>, &mut dyn FnMut(ValueRef<'b>)) -> Result<(), Error>,
+{
+    match item {
+        JsonItem::Borrowed(value) => f(*value, &mut |v| items.push(JsonItem::from_ref(v))),
+        JsonItem::Owned(value) => f(value.as_ref(), &mut |v| {
+            items.push(JsonItem::from_ref(v).into_owned().into())
+        }),
+    }
+}
+
+/// Evaluates the filter with the input, pushing its outputs.
+pub(super) fn eval<'a>(
+    expr: &Expr,
+    input: &JsonItem<'a>,
+    env: Option<&Env<'_, 'a>>,
+    output: &mut Items<'a>,
+) -> Result<(), Error> {
+    match expr {
+        Expr::Identity => output.push(input.clone()),
+        Expr::Recurse => recurse(input, output)?,
+        Expr::Literal(value) => output.push(value.clone().into()),
+        Expr::Variable(name) => output.push(env.expect("undefined variable").get(name).clone()),
+        Expr::Index(base, key) => {
+            for key in eval_all(key, input, env)? {
+                for base in eval_all(base, input, env)? {
+                    index(&base, key.as_ref(), output)?;
+                }
+            }
+        }
+        Expr::Slice(base, from, to) => {
+            let bound = |e: &Option<Box<Expr>>| match e {
+                Some(e) => Ok(eval_all(e, input, env)?.into_iter().map(Some).collect()),
+                None => Ok::<_, Error>(vec![None]),
+            };
+            for to in bound(to)? {
+                for from in bound(from)? {
+                    for base in eval_all(base, input, env)? {
+                        let from = from.as_ref().map(JsonItem::as_ref);
+                        let to = to.as_ref().map(JsonItem::as_ref);
+                        output.push(slice(base.as_ref(), from, to)?.into());
+                    }
+                }
+            }
+        }
+        Expr::Iterate(base) => {
+            for base in eval_all(base, input, env)? {
+                iterate(&base, output)?;
+            }
+        }
+        Expr::Try(body) => {
+            // outputs before an error are kept
+            let _ = eval(body, input, env, output);
+        }
+        Expr::Pipe(left, right) => {
+            for item in eval_all(left, input, env)? {
+                eval(right, &item, env, output)?;
+            }
+        }
+        Expr::Bind(source, name, body) => {
+            for value in eval_all(source, input, env)? {
+                let env = Env {
+                    name,
+                    value,
+                    parent: env,
+                };
+                eval(body, input, Some(&env), output)?;
+            }
+        }
+        Expr::Comma(left, right) => {
+            eval(left, input, env, output)?;
+            eval(right, input, env, output)?;
+        }
+        Expr::Neg(operand) => {
+            for item in eval_all(operand, input, env)? {
+                let zero = Value::from(0);
+                output.push(
+                    arith(ArithOp::Sub, zero.as_ref(), item.as_ref()).map_err(|_| {
+                        error(format!("{} cannot be negated", describe(item.as_ref())))
+                    })?,
+                );
+            }
+        }
+        Expr::Arith(op, left, right) => {
+            for r in eval_all(right, input, env)? {
+                for l in eval_all(left, input, env)? {
+                    output.push(arith(*op, l.as_ref(), r.as_ref())?);
+                }
+            }
+        }
+        Expr::Compare(op, left, right) => {
+            for r in eval_all(right, input, env)? {
+                for l in eval_all(left, input, env)? {
+                    let ordering = compare(l.as_ref(), r.as_ref());
+                    let result = match op {
+                        CompareOp::Eq => ordering.is_eq(),
+                        CompareOp::Ne => ordering.is_ne(),
+                        CompareOp::Lt => ordering.is_lt(),
+                        CompareOp::Le => ordering.is_le(),
+                        CompareOp::Gt => ordering.is_gt(),
+                        CompareOp::Ge => ordering.is_ge(),
+                    };
+                    output.push(Value::from(result).into());
+                }
+            }
+        }
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            let is_and = matches!(expr, Expr::And(..));
+            for l in eval_all(left, input, env)? {
+                // short circuit: `false and _` and `true or _`
+                if is_truthy(l.as_ref()) != is_and {
+                    output.push(Value::from(!is_and).into());
+                    continue;
+                }
+                for r in eval_all(right, input, env)? {
+                    output.push(Value::from(is_truthy(r.as_ref())).into());
+                }
+            }
+        }
+        Expr::Alternative(left, right) => {
+            let mut items = vec![];
+            // errors on the left are ignored
+            let _ = eval(left, input, env, &mut items);
+            let len = output.len();
+            output.extend(items.into_iter().filter(|i| is_truthy(i.as_ref())));
+            if output.len() == len {
+                eval(right, input, env, output)?;
+            }
+        }
+        Expr::Array(body) => {
+            let items = match body {
+                Some(body) => eval_all(body, input, env)?,
+                None => vec![],
+            };
+            output.push(Value::array(items.iter().map(JsonItem::as_ref)).into());
+        }
+        Expr::Object(entries) => {
+            // the cartesian product of all keys and values
+            let mut objects: Vec<Vec<(JsonItem<'a>, JsonItem<'a>)>> = vec![vec![]];
+            for (key, value) in entries {
+                let keys = eval_all(key, input, env)?;
+                let values = eval_all(value, input, env)?;
+                let mut next = vec![];
+                for object in &objects {
+                    for key in &keys {
+                        if !key.as_ref().is_string() {
+                            return Err(error(format!(
+                                "Cannot use {} as object key",
+                                describe(key.as_ref())
+                            )));
+                        }
+                        for value in &values {
+                            let mut object = object.clone();
+                            object.push((key.clone(), value.clone()));
+                            next.push(object);
+                        }
+                    }
+                }
+                objects = next;
+            }
+            for object in objects {
+                let entries = object
+                    .iter()
+                    .map(|(k, v)| (k.as_ref().as_str().unwrap(), v.as_ref()));
+                output.push(build_object(entries).into());
+            }
+        }
+        Expr::If(branches, otherwise) => if_then(branches, otherwise, input, env, output)?,
+        Expr::Reduce {
+            source,
+            name,
+            init,
+            update,
+        } => {
+            let values = eval_all(source, input, env)?;
+            for init in eval_all(init, input, env)? {
+                let mut acc = init;
+                for value in &values {
+                    let env = Env {
+                        name,
+                        value: value.clone(),
+                        parent: env,
+                    };
+                    // the last output is the new state, or null if there is none
+                    acc = eval_all(update, &acc, Some(&env))?
+                        .pop()
+                        .unwrap_or_else(|| Value::null().into());
+                }
+                output.push(acc);
+            }
+        }
+        Expr::Call(builtin, args) => call(*builtin, args, input, env, output)?,
+    }
+    Ok(())
+}
+
+fn eval_all<'a>(
+    expr: &Expr,
+    input: &JsonItem<'a>,
+    env: Option<&Env<'_, 'a>>,
+) -> Result<Items<'a>, Error> {
+    let mut output = vec![];
+    eval(expr, input, env, &mut output)?;
+    Ok(output)
+}
+
+fn if_then<'a>(
+    branches: &[(Expr, Expr)],
+    otherwise: &Expr,
+    input: &JsonItem<'a>,
+    env: Option<&Env<'_, 'a>>,
+    output: &mut Items<'a>,
+) -> Result<(), Error> {
+    let Some(((cond, then), rest)) = branches.split_first() else {
+        return eval(otherwise, input, env, output);
+    };
+    for cond in eval_all(cond, input, env)? {
+        if is_truthy(cond.as_ref()) {
+            eval(then, input, env, output)?;
+        } else {
+            if_then(rest, otherwise, input, env, output)?;
+        }
+    }
+    Ok(())
+}
+
+/// `..`: the input and all its descendants in pre-order.
+fn recurse<'a>(item: &JsonItem<'a>, output: &mut Items<'a>) -> Result<(), Error> {
+    output.push(item.clone());
+    if matches!(item.as_ref(), ValueRef::Array(_) | ValueRef::Object(_)) {
+        let mut children = vec![];
+        iterate(item, &mut children)?;
+        for child in &children {
+            recurse(child, output)?;
+        }
+    }
+    Ok(())
+}
+
+/// `.[]`
+fn iterate<'a>(item: &JsonItem<'a>, output: &mut Items<'a>) -> Result<(), Error> {
+    with_children(item, output, |value, push| {
+        match value {
+            ValueRef::Array(array) => array.iter().for_each(push),
+            ValueRef::Object(object) => object.values().for_each(push),
+            _ => return Err(error(format!("Cannot iterate over {}", describe(value)))),
+        }
+        Ok(())
+    })
+}
+
+/// `.[key]`
+fn index<'a>(item: &JsonItem<'a>, key: ValueRef<'_>, output: &mut Items<'a>) -> Result<(), Error> {
+    let mut null = false;
+    with_children(item, output, |value, push| {
+        let child = match (value, key) {
+            (ValueRef::Null, ValueRef::String(_) | ValueRef::Number(_)) => None,
+            (ValueRef::Object(object), ValueRef::String(key)) => object.get(key.as_str()),
+            (ValueRef::Array(array), ValueRef::Number(index)) => {
+                let index = index.as_f64().unwrap().floor();
+                let index = if index < 0.0 {
+                    index + array.len() as f64
+                } else {
+                    index
+                };
+                (index >= 0.0).then(|| array.get(index as usize)).flatten()
+            }
+            (_, ValueRef::String(key)) => {
+                return Err(error(format!(
+                    "Cannot index {} with string \"{}\"",
+                    type_name(value),
+                    key.as_str()
+                )))
+            }
+            _ => {
+                return Err(error(format!(
+                    "Cannot index {} with {}",
+                    type_name(value),
+                    type_name(key)
+                )))
+            }
+        };
+        match child {
+            Some(child) => push(child),
+            None => null = true,
+        }
+        Ok(())
+    })?;
+    if null {
+        output.push(Value::null().into());
+    }
+    Ok(())
+}
+
+/// `.[from:to]`
+fn slice(
+    value: ValueRef<'_>,
+    from: Option<ValueRef<'_>>,
+    to: Option<ValueRef<'_>>,
+) -> Result<Value, Error> {
+    let len = match value {
+        ValueRef::Null => return Ok(Value::null()),
+        ValueRef::Array(array) => array.len(),
+        ValueRef::String(s) => s.as_str().chars().count(),
+        _ => {
+            return Err(error(format!(
+                "Cannot index {} with object",
+                type_name(value)
+            )))
+        }
+    };
+    let bound = |v: Option<ValueRef<'_>>, default: usize, round: fn(f64) -> f64| {
+        let index = match v {
+            None | Some(ValueRef::Null) => return Ok(default),
+            Some(ValueRef::Number(n)) => round(n.as_f64().unwrap()),
+            Some(_) => {
+                return Err(error(
+                    "Start and end indices of an array slice must be numbers".into(),
+                ))
+            }
+        };
+        let index = if index < 0.0 {
+            index + len as f64
+        } else {
+            index
+        };
+        Ok(index.clamp(0.0, len as f64) as usize)
+    };
+    let start = bound(from, 0, f64::floor)?;
+    let end = bound(to, len, f64::ceil)?.max(start);
+    Ok(match value {
+        ValueRef::Array(array) => Value::array(array.iter().skip(start).take(end - start)),
+        ValueRef::String(s) => {
+            let s: String = s.as_str().chars().skip(start).take(end - start).collect();
+            Value::from(s.as_str())
+        }
+        _ => unreachable!(),
+    })
+}
+
+/// Converts the result of arithmetic to a value, using an integer if it is exact.
+fn number(x: f64) -> Result<JsonItem<'static>, Error> {
+    if !x.is_finite() {
+        return Err(error("number is not finite".into()));
+    }
+    if x.fract() == 0.0 && x.abs() < (1u64 << 53) as f64 {
+        return Ok(Value::from(x as i64).into());
+    }
+    Ok(Value::from(x).into())
+}
+
+fn arith<'a, 'v>(op: ArithOp, l: ValueRef<'v>, r: ValueRef<'v>) -> Result<JsonItem<'a>, Error> {
+    let fail = |verb: &str| {
+        error(format!(
+            "{} and {} cannot be {verb}",
+            describe(l),
+            describe(r)
+        ))
+    };
+    match (op, l, r) {
+        (ArithOp::Add, ValueRef::Null, v) | (ArithOp::Add, v, ValueRef::Null) => {
+            Ok(JsonItem::from_ref(v).into_owned().into())
+        }
+        (_, ValueRef::Number(a), ValueRef::Number(b)) => {
+            arith_number(op, a, b).ok_or_else(|| {
+                fail(match op {
+                    ArithOp::Div => "divided because the divisor is zero",
+                    ArithOp::Rem => "divided (remainder) because the divisor is zero",
+                    _ => "computed",
+                })
+            })?
+        }
+        (ArithOp::Add, ValueRef::String(a), ValueRef::String(b)) => {
+            Ok(Value::from(format!("{}{}", a.as_str(), b.as_str()).as_str()).into())
+        }
+        (ArithOp::Add, ValueRef::Array(a), ValueRef::Array(b)) => {
+            Ok(Value::array(a.iter().chain(b.iter())).into())
+        }
+        (ArithOp::Add, ValueRef::Object(a), ValueRef::Object(b)) => {
+            // keys on the right win
+            Ok(build_object(a.iter().chain(b.iter())).into())
+        }
+        (ArithOp::Sub, ValueRef::Array(a), ValueRef::Array(b)) => Ok(Value::array(
+            a.iter()
+                .filter(|x| b.iter().all(|y| compare(*x, y).is_ne())),
+        )
+        .into()),
+        (ArithOp::Mul, ValueRef::String(s), ValueRef::Number(n))
+        | (ArithOp::Mul, ValueRef::Number(n), ValueRef::String(s)) => {
+            let n = n.as_f64().unwrap();
+            if n <= 0.0 {
+                return Ok(Value::null().into());
+            }
+            Ok(Value::from(s.as_str().repeat((n.ceil() as usize).max(1)).as_str()).into())
+        }
+        (ArithOp::Mul, ValueRef::Object(a), ValueRef::Object(b)) => Ok(deep_merge(a, b).into()),
+        (ArithOp::Div, ValueRef::String(a), ValueRef::String(b)) => {
+            let (a, b) = (a.as_str(), b.as_str());
+            let parts: Vec<Value> = if a.is_empty() {
+                vec![]
+            } else if b.is_empty() {
+                a.chars()
+                    .map(|c| Value::from(c.to_string().as_str()))
+                    .collect()
+            } else {
+                a.split(b).map(Value::from).collect()
+            };
+            Ok(Value::array(parts.iter().map(Value::as_ref)).into())
+        }
+        (ArithOp::Add, ..) => Err(fail("added")),
+        (ArithOp::Sub, ..) => Err(fail("subtracted")),
+        (ArithOp::Mul, ..) => Err(fail("multiplied")),
+        (ArithOp::Div | ArithOp::Rem, ..) => Err(fail("divided")),
+    }
+}
+
+/// Computes with two numbers, or returns `None` on division by zero.
+fn arith_number(
+    op: ArithOp,
+    a: NumberRef<'_>,
+    b: NumberRef<'_>,
+) -> Option<Result<JsonItem<'static>, Error>> {
+    // exact integer arithmetic where possible
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64()) {
+        let exact = match op {
+            ArithOp::Add => x.checked_add(y),
+            ArithOp::Sub => x.checked_sub(y),
+            ArithOp::Mul => x.checked_mul(y),
+            ArithOp::Div if y == 0 => return None,
+            ArithOp::Div => x
+                .checked_rem(y)
+                .filter(|r| *r == 0)
+                .and_then(|_| x.checked_div(y)),
+            ArithOp::Rem if y == 0 => return None,
+            ArithOp::Rem => Some(x.wrapping_rem(y)),
+        };
+        if let Some(z) = exact {
+            return Some(Ok(Value::from(z).into()));
+        }
+    }
+    let (x, y) = (a.as_f64().unwrap(), b.as_f64().unwrap());
+    Some(number(match op {
+        ArithOp::Add => x + y,
+        ArithOp::Sub => x - y,
+        ArithOp::Mul => x * y,
+        ArithOp::Div if y == 0.0 => return None,
+        ArithOp::Div => x / y,
+        ArithOp::Rem => {
+            // the operands are truncated to integers
+            let (x, y) = (x as i64, y as i64);
+            if y == 0 {
+                return None;
+            }
+            x.wrapping_rem(y) as f64
+        }
+    }))
+}
+
+/// Builds an object that keeps the insertion order of its keys, as jq does.
+///
+/// A repeated key keeps its first position and takes its last value.
+fn build_object<'a>(entries: impl IntoIterator<Item = (&'a str, ValueRef<'a>)>) -> Value {
+    let mut builder = Builder::<Vec<u8>>::new().with_preserve_order(true);
+    builder.begin_object();
+    for (key, value) in entries {
+        builder.add_string(key);
+        builder.add_value(value);
+    }
+    builder.end_object();
+    builder.finish()
+}
+
+/// `a * b` for objects: merges recursively, with values on the right winning.
+fn deep_merge(a: ObjectRef<'_>, b: ObjectRef<'_>) -> Value {
+    let mut entries = vec![];
+    for (key, value) in a.iter() {
+        let merged = match (value, b.get(key)) {
+            (ValueRef::Object(x), Some(ValueRef::Object(y))) => deep_merge(x, y),
+            (_, Some(v)) => v.to_owned(),
+            (v, None) => v.to_owned(),
+        };
+        entries.push((key, merged));
+    }
+    for (key, value) in b.iter() {
+        if !a.contains_key(key) {
+            entries.push((key, value.to_owned()));
+        }
+    }
+    build_object(entries.iter().map(|(k, v)| (*k, v.as_ref())))
+}
+
+/// Compares two values in jq's order: null < false < true < numbers < strings < arrays < objects.
+///
+/// Arrays are compared element by element, and objects by their sorted keys and then by the
+/// values of each key.
+fn compare(a: ValueRef<'_>, b: ValueRef<'_>) -> Ordering {
+    fn rank(v: ValueRef<'_>) -> u8 {
+        match v {
+            ValueRef::Null => 0,
+            ValueRef::Bool(false) => 1,
+            ValueRef::Bool(true) => 2,
+            ValueRef::Number(_) => 3,
+            ValueRef::String(_) => 4,
+            ValueRef::Array(_) => 5,
+            ValueRef::Object(_) => 6,
+            ValueRef::RawJson(_) => unreachable!(),
+        }
+    }
+    match (a, b) {
+        (ValueRef::RawJson(a), b) => compare(a.value(), b),
+        (a, ValueRef::RawJson(b)) => compare(a, b.value()),
+        (ValueRef::Number(a), ValueRef::Number(b)) => a.cmp(&b),
+        (ValueRef::String(a), ValueRef::String(b)) => a.as_str().cmp(b.as_str()),
+        (ValueRef::Array(a), ValueRef::Array(b)) => {
+            let mut b = b.iter();
+            for x in a.iter() {
+                let Some(y) = b.next() else {
+                    return Ordering::Greater;
+                };
+                let ordering = compare(x, y);
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+            match b.next() {
+                Some(_) => Ordering::Less,
+                None => Ordering::Equal,
+            }
+        }
+        (ValueRef::Object(a), ValueRef::Object(b)) => {
+            let (ka, kb) = (sorted_keys(a), sorted_keys(b));
+            ka.cmp(&kb).then_with(|| {
+                ka.iter()
+                    .map(|k| compare(a.get(k).unwrap(), b.get(k).unwrap()))
+                    .find(|o| o.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+        }
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn sorted_keys(object: ObjectRef<'_>) -> Vec<&str> {
+    let mut keys: Vec<_> = object.keys().collect();
+    keys.sort_unstable();
+    keys
+}
+
+/// Calls a builtin function.
+fn call<'a>(
+    builtin: Builtin,
+    args: &[Expr],
+    input: &JsonItem<'a>,
+    env: Option<&Env<'_, 'a>>,
+    output: &mut Items<'a>,
+) -> Result<(), Error> {
+    let value = input.as_ref();
+    let item: JsonItem<'a> = match builtin {
+        Builtin::Empty => return Ok(()),
+        Builtin::Error => {
+            let messages = match args.first() {
+                Some(arg) => eval_all(arg, input, env)?,
+                None => vec![input.clone()],
+            };
+            let Some(message) = messages.first() else {
+                return Ok(());
+            };
+            return Err(error(match message.as_ref() {
+                ValueRef::String(s) => s.as_str().into(),
+                v => format!("(not a string): {v}"),
+            }));
+        }
+        Builtin::Not => Value::from(!is_truthy(value)).into(),
+        Builtin::Length => match value {
+            ValueRef::Null => Value::from(0).into(),
+            ValueRef::Number(n) => number(n.as_f64().unwrap().abs())?,
+            ValueRef::String(s) => Value::from(s.as_str().chars().count()).into(),
+            ValueRef::Array(array) => Value::from(array.len()).into(),
+            ValueRef::Object(object) => Value::from(object.len()).into(),
+            _ => return Err(error(format!("{} has no length", describe(value)))),
+        },
+        Builtin::Keys | Builtin::KeysUnsorted => match value {
+            ValueRef::Object(object) => {
+                let keys = match builtin {
+                    Builtin::Keys => sorted_keys(object),
+                    _ => object.keys().collect(),
+                };
+                let keys: Vec<_> = keys.into_iter().map(Value::from).collect();
+                Value::array(keys.iter().map(Value::as_ref)).into()
+            }
+            ValueRef::Array(array) => {
+                let indices: Vec<_> = (0..array.len()).map(Value::from).collect();
+                Value::array(indices.iter().map(Value::as_ref)).into()
+            }
+            _ => return Err(error(format!("{} has no keys", describe(value)))),
+        },
+        Builtin::Has => {
+            for key in eval_all(&args[0], input, env)? {
+                let has = match (value, key.as_ref()) {
+                    (ValueRef::Object(object), ValueRef::String(key)) => {
+                        object.contains_key(key.as_str())
+                    }
+                    (ValueRef::Array(array), ValueRef::Number(index)) => {
+                        let index = index.as_f64().unwrap();
+                        index >= 0.0 && index < array.len() as f64
+                    }
+                    (value, key) => {
+                        return Err(error(format!(
+                            "Cannot check whether {} has a {} key",
+                            type_name(value),
+                            type_name(key)
+                        )))
+                    }
+                };
+                output.push(Value::from(has).into());
+            }
+            return Ok(());
+        }
+        Builtin::Select => {
+            for cond in eval_all(&args[0], input, env)? {
+                if is_truthy(cond.as_ref()) {
+                    output.push(input.clone());
+                }
+            }
+            return Ok(());
+        }
+        Builtin::Map => {
+            let mut items = vec![];
+            for element in children(input)? {
+                eval(&args[0], &element, env, &mut items)?;
+            }
+            Value::array(items.iter().map(JsonItem::as_ref)).into()
+        }
+        Builtin::ToEntries => to_entries(value)?.into(),
+        Builtin::FromEntries => from_entries(value)?.into(),
+        Builtin::WithEntries => {
+            let entries = JsonItem::Owned(to_entries(value)?);
+            let mut items = vec![];
+            for entry in children(&entries)? {
+                eval(&args[0], &entry, env, &mut items)?;
+            }
+            let entries = Value::array(items.iter().map(JsonItem::as_ref));
+            from_entries(entries.as_ref())?.into()
+        }
+        Builtin::Add => {
+            let mut acc: JsonItem<'a> = Value::null().into();
+            for element in children(input)? {
+                acc = arith(ArithOp::Add, acc.as_ref(), element.as_ref())?;
+            }
+            acc
+        }
+        Builtin::Type => Value::from(type_name(value)).into(),
+        Builtin::ToString => match value {
+            ValueRef::String(_) => input.clone(),
+            _ => Value::from(value.to_string().as_str()).into(),
+        },
+        Builtin::ToNumber => match value {
+            ValueRef::Number(_) => input.clone(),
+            ValueRef::String(s) => match Value::from_text(s.as_str().trim().as_bytes()) {
+                Ok(n) if n.as_ref().is_number() => n.into(),
+                _ => return Err(error(format!("Cannot parse '{}' as a number", s.as_str()))),
+            },
+            _ => {
+                return Err(error(format!(
+                    "{} cannot be parsed as a number",
+                    describe(value)
+                )))
+            }
+        },
+        Builtin::Sort => match value {
+            ValueRef::Array(array) => {
+                let mut elements: Vec<_> = array.iter().collect();
+                elements.sort_by(|a, b| compare(*a, *b));
+                Value::array(elements).into()
+            }
+            _ => {
+                return Err(error(format!(
+                    "{} cannot be sorted, as it is not an array",
+                    describe(value)
+                )))
+            }
+        },
+    };
+    output.push(item);
+    Ok(())
+}
+
+/// Returns the elements of an array or the values of an object.
+fn children<'a>(item: &JsonItem<'a>) -> Result<Items<'a>, Error> {
+    let mut items = vec![];
+    iterate(item, &mut items)?;
+    Ok(items)
+}
+
+/// `to_entries`: `{"a": 1}` to `[{"key": "a", "value": 1}]`, and `[1]` to
+/// `[{"key": 0, "value": 1}]`.
+fn to_entries(value: ValueRef<'_>) -> Result<Value, Error> {
+    let entry = |key: Value, value| build_object([("key", key.as_ref()), ("value", value)]);
+    let entries: Vec<Value> = match value {
+        ValueRef::Object(object) => object.iter().map(|(k, v)| entry(k.into(), v)).collect(),
+        ValueRef::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| entry(i.into(), v))
+            .collect(),
+        _ => return Err(error(format!("{} has no keys", describe(value)))),
+    };
+    Ok(Value::array(entries.iter().map(Value::as_ref)))
+}
+
+/// `from_entries`: the inverse of `to_entries`, defined in jq 1.6 as
+/// `map({(.key // .Key // .name // .Name): (if has("value") then .value else .Value end)}) | add | .//={}`.
+fn from_entries(value: ValueRef<'_>) -> Result<Value, Error> {
+    let ValueRef::Array(array) = value else {
+        return Err(error(format!("Cannot iterate over {}", describe(value))));
+    };
+    let mut entries = vec![];
+    for entry in array.iter() {
+        let object = match entry {
+            ValueRef::Object(object) => Some(object),
+            ValueRef::Null => None,
+            _ => {
+                return Err(error(format!(
+                    "Cannot index {} with string \"key\"",
+                    type_name(entry)
+                )))
+            }
+        };
+        let field = |name| object.and_then(|o| o.get(name)).unwrap_or(ValueRef::Null);
+        // `a // b` is `b` unless `a` is truthy
+        let key = ["key", "Key", "name"]
+            .into_iter()
+            .map(field)
+            .find(|v| is_truthy(*v))
+            .unwrap_or_else(|| field("Name"));
+        let ValueRef::String(key) = key else {
+            return Err(error(format!("Cannot use {} as object key", describe(key))));
+        };
+        let value = match object {
+            Some(object) if object.contains_key("value") => field("value"),
+            _ => field("Value"),
+        };
+        entries.push((key.as_str(), value));
+    }
+    Ok(build_object(entries))
+}
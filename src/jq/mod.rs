@@ -0,0 +1,586 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A subset of the [jq](https://jqlang.github.io/jq/manual/) language.
+//!
+//! A [`JqFilter`] is compiled once and run over [`ValueRef`] inputs. Values selected from the
+//! input are borrowed, and outputs can be written to a shared buffer with [`JqFilter::run_into`].
+//!
+//! The supported syntax includes:
+//!
+//! - paths `.`, `..`, `.key`, `."key"`, `.[k]`, `.[]`, `.[from:to]` and `?`
+//! - pipes `|`, comma `,`, alternative `//` and `... as $name | ...`
+//! - literals, array construction `[...]` and object construction `{a, "b": .c, (.d): 1, $x}`
+//! - arithmetic `+`, `-`, `*`, `/`, `%` and comparison `==`, `!=`, `<`, `<=`, `>`, `>=`
+//! - `and`, `or`, `if ... then ... elif ... else ... end` and `reduce ... as $x (init; update)`
+//! - the functions `empty`, `error`, `not`, `length`, `keys`, `keys_unsorted`, `has`, `select`,
+//!   `map`, `to_entries`, `from_entries`, `with_entries`, `add`, `type`, `tostring`, `tonumber`
+//!   and `sort`
+//!
+//! Function definitions, string interpolation, `try`/`catch`, assignment operators, `foreach`
+//! and `label` are not supported. Numbers are computed as 64-bit integers where exact and as
+//! floats otherwise.
+//!
+//! Builtins and error messages follow jq 1.6, e.g. `from_entries` takes the key from `key`,
+//! `Key`, `name` or `Name` and the value from `value` or `Value`. Objects constructed by a filter
+//! keep the insertion order of their keys, as in jq. The order of input objects is whatever
+//! [`ObjectRef::iter`](crate::ObjectRef::iter) returns: `keys_unsorted`, `to_entries` and `+`
+//! see their insertion order only if they were built with
+//! [`Builder::with_preserve_order`], and otherwise their key order. To print outputs as jq does,
+//! write them with [`FloatFormat::Jq`](crate::FloatFormat::Jq).
+//!
+//! # Example
+//!
+//! ```
+//! use jsonbb::jq::JqFilter;
+//! use jsonbb::Value;
+//!
+//! let value: Value = r#"{"items": [{"id": 1, "qty": 0}, {"id": 2, "qty": 3, "note": "x"}]}"#
+//!     .parse()
+//!     .unwrap();
+//! let filter = JqFilter::new(".items[] | select(.qty > 0) | {id, qty}").unwrap();
+//! let outputs = filter.run(value.as_ref()).unwrap();
+//! assert_eq!(outputs.len(), 1);
+//! assert_eq!(outputs[0].to_string(), r#"{"id":2,"qty":3}"#);
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use self::parser::Expr;
+use crate::jsonpath::JsonItem;
+use crate::{Builder, Error, ValueRef};
+
+mod eval;
+mod parser;
+
+/// A compiled jq filter.
+///
+/// See the [module documentation](self) for the supported syntax.
+#[derive(Debug, Clone)]
+pub struct JqFilter {
+    text: String,
+    expr: Expr,
+}
+
+impl JqFilter {
+    /// Compiles a filter.
+    ///
+    /// Returns [`Error::Parse`] if the filter is invalid or uses an unsupported feature.
+    pub fn new(filter: &str) -> Result<Self, Error> {
+        Ok(JqFilter {
+            text: filter.into(),
+            expr: parser::parse(filter)?,
+        })
+    }
+
+    /// Runs the filter with the input and returns all outputs.
+    ///
+    /// Returns [`Error::Jq`] if the filter raises an error, e.g. when indexing a number.
+    pub fn run<'a>(&self, input: ValueRef<'a>) -> Result<Vec<JsonItem<'a>>, Error> {
+        let mut outputs = vec![];
+        eval::eval(&self.expr, &JsonItem::from_ref(input), None, &mut outputs)?;
+        Ok(outputs)
+    }
+
+    /// Runs the filter with the input and appends the jsonbb encoding of the outputs to the
+    /// buffer.
+    ///
+    /// Returns the range of each output in the buffer. The buffer is unchanged on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::jq::JqFilter;
+    /// use jsonbb::{Value, ValueRef};
+    ///
+    /// let value: Value = "[1, 2, 3]".parse().unwrap();
+    /// let filter = JqFilter::new(".[] * 10").unwrap();
+    /// let mut buffer = vec![];
+    /// let ranges = filter.run_into(value.as_ref(), &mut buffer).unwrap();
+    /// let outputs: Vec<_> = ranges.into_iter().map(|r| ValueRef::from_bytes(&buffer[r]).to_string()).collect();
+    /// assert_eq!(outputs, ["10", "20", "30"]);
+    /// ```
+    pub fn run_into(
+        &self,
+        input: ValueRef<'_>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<Vec<Range<usize>>, Error> {
+        let outputs = self.run(input)?;
+        let mut ranges = Vec::with_capacity(outputs.len());
+        for output in outputs {
+            let start = buffer.len();
+            let mut builder = Builder::<&mut Vec<u8>>::appending(buffer);
+            builder.add_value(output.as_ref());
+            builder.finish();
+            ranges.push(start..buffer.len());
+        }
+        Ok(ranges)
+    }
+}
+
+impl FromStr for JqFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for JqFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+    use crate::Value;
+
+    /// Runs the filter and returns the outputs as JSON texts.
+    #[track_caller]
+    fn run(filter: &str, input: &str) -> Vec<String> {
+        let input: Value = input.parse().unwrap();
+        let filter = JqFilter::new(filter).unwrap();
+        let outputs = filter.run(input.as_ref()).unwrap();
+        outputs.iter().map(|o| o.to_string()).collect()
+    }
+
+    #[track_caller]
+    fn run_err(filter: &str, input: &str) -> String {
+        let input: Value = input.parse().unwrap();
+        let filter = JqFilter::new(filter).unwrap();
+        filter.run(input.as_ref()).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn paths() {
+        let input = r#"{"a": {"b": [1, 2, 3]}, "c d": null}"#;
+        assert_eq!(run(".", "1"), ["1"]);
+        assert_eq!(run(".a.b", input), ["[1,2,3]"]);
+        assert_eq!(run(r#"."c d""#, input), ["null"]);
+        assert_eq!(run(".a.x", input), ["null"]);
+        assert_eq!(run(".a.b[0], .a.b[-1], .a.b[5]", input), ["1", "3", "null"]);
+        assert_eq!(run(r#".["a"]["b"][1]"#, input), ["2"]);
+        assert_eq!(run(".a.b[]", input), ["1", "2", "3"]);
+        assert_eq!(run(".a.b[1:]", input), ["[2,3]"]);
+        assert_eq!(run(".a.b[:-1]", input), ["[1,2]"]);
+        assert_eq!(run(".[2:4]", r#""abcdef""#), [r#""cd""#]);
+        assert_eq!(run(".x.y", "null"), ["null"]);
+        assert_eq!(run("[..]", "[[1]]"), ["[[[1]],[1],1]"]);
+        assert_eq!(run(".a?", "1"), Vec::<String>::new());
+        assert_eq!(run("[.[]?]", "[1, 2]"), ["[1,2]"]);
+        assert_eq!(run_err(".a", "1"), r#"Cannot index number with string "a""#);
+        assert_eq!(run_err(".[]", "true"), "Cannot iterate over boolean (true)");
+    }
+
+    #[test]
+    fn pipes() {
+        let input = r#"{"items": [{"id": 1, "qty": 0}, {"id": 2, "qty": 3}]}"#;
+        assert_eq!(run(".items[] | .id", input), ["1", "2"]);
+        assert_eq!(run(".items[0] | .id, .qty", input), ["1", "0"]);
+        assert_eq!(run(".a // 1, (.b, false) // 2", r#"{"b": 3}"#), ["1", "3"]);
+        assert_eq!(run("(.a, .b) // 5", r#"{"a": null, "b": false}"#), ["5"]);
+        assert_eq!(run(".[] as $x | $x * 2", "[1, 2]"), ["2", "4"]);
+        assert_eq!(run(". as $x | .a | $x.b + .", r#"{"a": 1, "b": 2}"#), ["3"]);
+        assert_eq!(run("1 + . as $x | $x", "2"), ["3"]);
+    }
+
+    #[test]
+    fn construction() {
+        let input = r#"{"id": 1, "qty": 2, "tags": ["a", "b"]}"#;
+        assert_eq!(run("{id, qty}", input), [r#"{"id":1,"qty":2}"#]);
+        assert_eq!(
+            run(r#"{"x": .id, (.tags[0]): .qty}"#, input),
+            [r#"{"x":1,"a":2}"#]
+        );
+        assert_eq!(
+            run("{tag: .tags[]}", input),
+            [r#"{"tag":"a"}"#, r#"{"tag":"b"}"#]
+        );
+        assert_eq!(run(". as $v | {$v} | .v.id", input), ["1"]);
+        assert_eq!(run("{a: 1 | . + 1}", "null"), [r#"{"a":2}"#]);
+        assert_eq!(run("[.tags[], .id]", input), [r#"["a","b",1]"#]);
+        assert_eq!(run("[]", input), ["[]"]);
+        assert_eq!(run("{}", input), ["{}"]);
+        assert_eq!(
+            run(r#"[1, "a", null, true, false, -1.5]"#, "null"),
+            [r#"[1,"a",null,true,false,-1.5]"#]
+        );
+        assert_eq!(
+            run_err("{(.id): 1}", input),
+            "Cannot use number (1) as object key"
+        );
+    }
+
+    #[test]
+    fn builtins() {
+        let input = r#"{"b": 2, "a": [1, "x"]}"#;
+        assert_eq!(run("keys", input), [r#"["a","b"]"#]);
+        assert_eq!(run(".a | keys", input), ["[0,1]"]);
+        assert_eq!(run("has(\"a\"), has(\"c\")", input), ["true", "false"]);
+        assert_eq!(run(".a | has(1), has(2)", input), ["true", "false"]);
+        assert_eq!(
+            run("[.[] | length]", r#"[null, -5, "héllo", [1, 2], {"a": 1}]"#),
+            ["[0,5,5,2,1]"]
+        );
+        assert_eq!(run("map(. * 2)", "[1, 2]"), ["[2,4]"]);
+        assert_eq!(run("map(.)", r#"{"a": 1}"#), ["[1]"]);
+        assert_eq!(run(".[] | select(. > 1)", "[1, 2, 3]"), ["2", "3"]);
+        assert_eq!(
+            run("to_entries", r#"{"a": 1}"#),
+            [r#"[{"key":"a","value":1}]"#]
+        );
+        assert_eq!(
+            run(
+                "from_entries",
+                r#"[{"key": "b", "value": 1}, {"Key": "a", "Value": 2}, {"name": "c", "v": 3}]"#
+            ),
+            [r#"{"b":1,"a":2,"c":null}"#]
+        );
+        assert_eq!(
+            run("with_entries({key: .value, value: .key})", r#"{"a": "x"}"#),
+            [r#"{"x":"a"}"#]
+        );
+        assert_eq!(run("add", "[1, 2, 3]"), ["6"]);
+        assert_eq!(run("add", r#"["a", "b"]"#), [r#""ab""#]);
+        assert_eq!(run("add", "[]"), ["null"]);
+        assert_eq!(
+            run("[.[] | type]", r#"[null, true, 1, "a", [], {}]"#),
+            [r#"["null","boolean","number","string","array","object"]"#]
+        );
+        assert_eq!(
+            run("[.[] | tostring]", r#"[1, "a", [1]]"#),
+            [r#"["1","a","[1]"]"#]
+        );
+        assert_eq!(run(r#""12" | tonumber"#, "null"), ["12"]);
+        assert_eq!(
+            run(
+                "sort",
+                r#"[{"a": 1}, [2], "b", 3, true, false, null, [1], "a", 2.5]"#
+            ),
+            [r#"[null,false,true,2.5,3,"a","b",[1],[2],{"a":1}]"#]
+        );
+        assert_eq!(
+            run("[.[] | not]", "[null, false, 0]"),
+            ["[true,true,false]"]
+        );
+        assert_eq!(run("[1, empty, 2]", "null"), ["[1,2]"]);
+        assert_eq!(run_err(r#"error("boom")"#, "null"), "boom");
+        assert_eq!(run_err("error", "{}"), "(not a string): {}");
+        assert_eq!(run_err("keys", "1"), "number (1) has no keys");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let input = r#"{"a": 7, "b": 2}"#;
+        assert_eq!(
+            run(".a + .b, .a - .b, .a * .b, .a / .b, .a % .b", input),
+            ["9", "5", "14", "3.5", "1"]
+        );
+        assert_eq!(run("1 + 2 * 3 - 4 / 2", "null"), ["5"]);
+        assert_eq!(run("-., -(1 + 2)", "5"), ["-5", "-3"]);
+        assert_eq!(run("(1, 2) + (10, 20)", "null"), ["11", "12", "21", "22"]);
+        assert_eq!(run("9007199254740993 + 0", "null"), ["9007199254740993"]);
+        assert_eq!(run(". + 1", "null"), ["1"]);
+        assert_eq!(
+            run(r#""ab" + "cd", "a,b" / ",", "ab" * 2"#, "null"),
+            [r#""abcd""#, r#"["a","b"]"#, r#""abab""#]
+        );
+        assert_eq!(run("[1, 2, 1, 3] - [1]", "null"), ["[2,3]"]);
+        assert_eq!(run("[1] + [2]", "null"), ["[1,2]"]);
+        assert_eq!(
+            run(r#"{"a": 1, "b": {"c": 1}} + {"b": {"d": 2}}"#, "null"),
+            [r#"{"a":1,"b":{"d":2}}"#]
+        );
+        assert_eq!(
+            run(r#"{"a": 1, "b": {"c": 1}} * {"b": {"d": 2}}"#, "null"),
+            [r#"{"a":1,"b":{"c":1,"d":2}}"#]
+        );
+        assert_eq!(
+            run_err("1 / 0", "null"),
+            "number (1) and number (0) cannot be divided because the divisor is zero"
+        );
+        assert_eq!(
+            run_err(r#"{} + 1"#, "null"),
+            "object ({}) and number (1) cannot be added"
+        );
+        assert_eq!(
+            run_err(r#"-"a""#, "null"),
+            r#"string ("a") cannot be negated"#
+        );
+    }
+
+    #[test]
+    fn comparison() {
+        assert_eq!(
+            run(
+                "1 == 1.0, 1 != 2, 1 < 2, 2 <= 1, \"a\" > 1, [] >= {}",
+                "null"
+            ),
+            ["true", "true", "true", "false", "true", "false"]
+        );
+        assert_eq!(run(r#"{"a": [1, 2]} == {"a": [1, 2.0]}"#, "null"), ["true"]);
+        assert_eq!(
+            run("null < false, false < true, true < 0", "null"),
+            ["true", "true", "true"]
+        );
+        assert_eq!(
+            run(".a and .b, .a or .b", r#"{"a": true, "b": null}"#),
+            ["false", "true"]
+        );
+        assert_eq!(
+            run("false and error, true or error", "null"),
+            ["false", "true"]
+        );
+    }
+
+    #[test]
+    fn conditionals() {
+        let filter = r#"if . > 1 then "big" elif . == 1 then "one" else "small" end"#;
+        assert_eq!(run(filter, "2"), [r#""big""#]);
+        assert_eq!(run(filter, "1"), [r#""one""#]);
+        assert_eq!(run(filter, "0"), [r#""small""#]);
+        assert_eq!(run("if . then 1 end", "false"), ["false"]);
+        assert_eq!(
+            run("[.[] | if . then 1 else 2 end]", "[true, null]"),
+            ["[1,2]"]
+        );
+    }
+
+    #[test]
+    fn reduce() {
+        assert_eq!(run("reduce .[] as $x (0; . + $x)", "[1, 2, 3]"), ["6"]);
+        assert_eq!(
+            run("reduce .[] as $x ({}; . + {($x): 1})", r#"["a", "b"]"#),
+            [r#"{"a":1,"b":1}"#]
+        );
+        assert_eq!(
+            run("reduce .[] as $x (0, 10; . + $x)", "[1, 2]"),
+            ["3", "13"]
+        );
+        assert_eq!(run("reduce .[] as $x (0; empty)", "[1]"), ["null"]);
+        assert_eq!(
+            run(". as $x | reduce .[] as $y (0; . + $y * $x[0])", "[2, 3]"),
+            ["10"]
+        );
+    }
+
+    #[test]
+    fn syntax() {
+        for filter in [
+            "",
+            ".a |",
+            "{a: }",
+            "[1, 2",
+            ".[1:]]",
+            "$x",
+            "reduce . as $x (0; $y)",
+            "foo",
+            "length(1)",
+            "if . then 1",
+            r#""\(1)""#,
+            ".a = 1",
+            "def f: 1; f",
+            "1 == 2 == 3",
+        ] {
+            let err = JqFilter::new(filter).unwrap_err();
+            assert!(matches!(err, Error::Parse { .. }), "{filter}: {err}");
+        }
+        assert_eq!(
+            JqFilter::new("foo(1)").unwrap_err().to_string(),
+            "foo/1 is not defined at line 1 column 1"
+        );
+        let filter: JqFilter = ".a # comment\n| .b".parse().unwrap();
+        assert_eq!(filter.to_string(), ".a # comment\n| .b");
+        assert_eq!(run(".a # comment\n| .b", r#"{"a": {"b": 1}}"#), ["1"]);
+    }
+
+    #[test]
+    fn run_into() {
+        let input: Value = r#"{"a": [1, {"b": 2}]}"#.parse().unwrap();
+        let mut buffer = b"xx".to_vec();
+        let filter = JqFilter::new(".a[]").unwrap();
+        let ranges = filter.run_into(input.as_ref(), &mut buffer).unwrap();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].start, 2);
+        assert_eq!(
+            ValueRef::from_bytes(&buffer[ranges[0].clone()]).as_u64(),
+            Some(1)
+        );
+        let object = ValueRef::from_bytes(&buffer[ranges[1].clone()]);
+        assert_eq!(object.to_string(), r#"{"b":2}"#);
+
+        let len = buffer.len();
+        let filter = JqFilter::new(".a[], error(\"x\")").unwrap();
+        assert!(filter.run_into(input.as_ref(), &mut buffer).is_err());
+        assert_eq!(buffer.len(), len);
+    }
+
+    /// Outputs of jq 1.6 for `jq -c <filter>`, with errors as `error: <message>`.
+    const JQ_CASES: &[(&str, &str, &str)] = &[
+        // insertion order
+        ("{z: 1, a: 2, z: 3}", "null", r#"{"z":3,"a":2}"#),
+        ("keys_unsorted, keys", r#"{"b": 1, "a": 2}"#, r#"["b","a"] ["a","b"]"#),
+        (
+            "to_entries",
+            r#"{"b": 1, "a": 2}"#,
+            r#"[{"key":"b","value":1},{"key":"a","value":2}]"#,
+        ),
+        ("to_entries", "[5]", r#"[{"key":0,"value":5}]"#),
+        (". + {c: 1, b: 5}", r#"{"b": 1, "a": 2}"#, r#"{"b":5,"a":2,"c":1}"#),
+        (
+            ". * {a: {y: 1, x: 2}}",
+            r#"{"b": 1, "a": {"x": 0}}"#,
+            r#"{"b":1,"a":{"x":2,"y":1}}"#,
+        ),
+        (
+            "with_entries(select(.value > 1))",
+            r#"{"c": 3, "a": 1, "b": 2}"#,
+            r#"{"c":3,"b":2}"#,
+        ),
+        // `from_entries` key and value aliases
+        (
+            "from_entries",
+            r#"[{"key": "b", "value": 1}, {"Key": "a", "Value": 2}, {"name": "c", "v": 3},
+                {"key": null, "Name": "d", "value": false},
+                {"key": false, "name": "e", "value": null, "Value": 4}, {"k": "f", "key": "b"}]"#,
+            r#"{"b":null,"a":2,"c":null,"d":false,"e":null}"#,
+        ),
+        ("from_entries", "[]", "{}"),
+        ("from_entries", "[null]", "error: Cannot use null (null) as object key"),
+        ("from_entries", r#"[{"k": "a", "v": 1}]"#, "error: Cannot use null (null) as object key"),
+        ("from_entries", r#"[{"key": 1}]"#, "error: Cannot use number (1) as object key"),
+        ("from_entries", "[1]", r#"error: Cannot index number with string "key""#),
+        ("from_entries", "1", "error: Cannot iterate over number (1)"),
+        // numbers
+        ("9223372036854775807 + 1", "null", "9223372036854776000"),
+        ("100000000000000000000", "null", "1e+20"),
+        ("1e17, 1e16, 3.0, 0.0001, 0.00001, -1e-7", "null", "1e+17 1e+16 3 0.0001 1e-05 -1e-07"),
+        ("1 / 3, 0.1 + 0.2, .[] / 2", "[1, 3]", "0.3333333333333333 0.30000000000000004 0.5 1.5"),
+        // errors
+        (".a", "1", r#"error: Cannot index number with string "a""#),
+        (".a.b", r#"{"a": [1]}"#, r#"error: Cannot index array with string "b""#),
+        (".[0]", r#"{"a": 1}"#, "error: Cannot index object with number"),
+        (".[]", "1", "error: Cannot iterate over number (1)"),
+        ("keys", "1", "error: number (1) has no keys"),
+        ("to_entries", "1", "error: number (1) has no keys"),
+        ("length", "true", "error: boolean (true) has no length"),
+        (r#"has("a")"#, "[1]", "error: Cannot check whether array has a string key"),
+        ("has(0)", "{}", "error: Cannot check whether object has a number key"),
+        ("sort", "{}", "error: object ({}) cannot be sorted, as it is not an array"),
+        (r#"1 + "a""#, "null", r#"error: number (1) and string ("a") cannot be added"#),
+        ("{} - 1", "null", "error: object ({}) and number (1) cannot be subtracted"),
+        (r#""a" * {}"#, "null", r#"error: string ("a") and object ({}) cannot be multiplied"#),
+        (
+            "1 as $x | $x / 0",
+            "null",
+            "error: number (1) and number (0) cannot be divided because the divisor is zero",
+        ),
+        (
+            "1 as $x | $x % 0",
+            "null",
+            "error: number (1) and number (0) cannot be divided (remainder) because the divisor is zero",
+        ),
+        ("1 as $k | {($k): 2}", "null", "error: Cannot use number (1) as object key"),
+        (r#"error("x")"#, "null", "error: x"),
+        (r#"error({"a": 1})"#, "null", r#"error: (not a string): {"a":1}"#),
+    ];
+
+    /// Runs the filter over an input that keeps the key order of its text, and prints the
+    /// outputs as `jq -c` does.
+    fn run_like_jq(filter: &str, input: &str) -> String {
+        let mut builder = crate::Builder::<Vec<u8>>::new().with_preserve_order(true);
+        builder
+            .deserialize(&mut serde_json::Deserializer::from_str(input))
+            .unwrap();
+        let input = builder.finish();
+        let outputs = match JqFilter::new(filter).unwrap().run(input.as_ref()) {
+            Ok(outputs) => outputs,
+            Err(e) => return format!("error: {e}"),
+        };
+        let options = crate::WriteOptions::new().float_format(crate::FloatFormat::Jq);
+        let outputs: Vec<_> = outputs
+            .iter()
+            .map(|o| {
+                let mut buf = vec![];
+                o.as_ref().to_writer(&mut buf, &options).unwrap();
+                String::from_utf8(buf).unwrap()
+            })
+            .collect();
+        outputs.join(" ")
+    }
+
+    #[test]
+    fn jq_compat() {
+        for (filter, input, expected) in JQ_CASES {
+            assert_eq!(run_like_jq(filter, input), *expected, "{filter}");
+        }
+    }
+
+    /// Checks the expected outputs against a local jq 1.6 if there is one.
+    #[test]
+    fn jq_compat_reference() {
+        let version = Command::new("jq").arg("--version").output();
+        if !version.is_ok_and(|v| v.stdout.starts_with(b"jq-1.6")) {
+            return;
+        }
+        for (filter, input, expected) in JQ_CASES {
+            let mut jq = Command::new("jq")
+                .args(["-c", filter])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let mut stdin = jq.stdin.take().unwrap();
+            stdin.write_all(input.as_bytes()).unwrap();
+            drop(stdin);
+            let output = jq.wait_with_output().unwrap();
+            let stdout = String::from_utf8(output.stdout).unwrap();
+            let stderr = String::from_utf8(output.stderr).unwrap();
+            let actual = match stderr.trim_end().strip_prefix("jq: error (at <stdin>:") {
+                Some(error) => {
+                    let (_, message) = error.split_once(')').unwrap();
+                    let message = message.strip_prefix(": ").unwrap_or(message.trim_start());
+                    format!("error: {message}")
+                }
+                None => stdout.split_whitespace().collect::<Vec<_>>().join(" "),
+            };
+            assert_eq!(actual, *expected, "{filter}");
+        }
+    }
+
+    #[test]
+    fn raw_json() {
+        let mut builder = crate::Builder::<Vec<u8>>::new();
+        builder.begin_object();
+        builder.add_string("a");
+        builder.add_raw_json(r#"{"b": [1, 2]}"#).unwrap();
+        builder.end_object();
+        let value = builder.finish();
+
+        let filter = JqFilter::new(".a.b[1], (.a | type), (.a == {\"b\": [1, 2]})").unwrap();
+        let outputs = filter.run(value.as_ref()).unwrap();
+        let outputs: Vec<_> = outputs.iter().map(|o| o.to_string()).collect();
+        assert_eq!(outputs, ["2", r#""object""#, "true"]);
+    }
+}
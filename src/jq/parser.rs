@@ -0,0 +1,728 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of jq filters.
+
+use std::fmt;
+
+use crate::{Error, Value};
+
+/// A filter expression.
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    /// `.`
+    Identity,
+    /// `..`
+    Recurse,
+    Literal(Value),
+    Variable(String),
+    /// `t[k]`, `t.k` or `t."k"`.
+    Index(Box<Expr>, Box<Expr>),
+    /// `t[from:to]`
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    /// `t[]`
+    Iterate(Box<Expr>),
+    /// `t?`
+    Try(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    /// `source as $name | body`
+    Bind(Box<Expr>, String, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Arith(ArithOp, Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    /// `a // b`
+    Alternative(Box<Expr>, Box<Expr>),
+    /// `[e]`, or `[]` if `None`.
+    Array(Option<Box<Expr>>),
+    /// `{k: v, ...}`
+    Object(Vec<(Expr, Expr)>),
+    /// `if c then a elif c then a else b end`, where a missing `else` is `.`.
+    If(Vec<(Expr, Expr)>, Box<Expr>),
+    /// `reduce source as $name (init; update)`
+    Reduce {
+        source: Box<Expr>,
+        name: String,
+        init: Box<Expr>,
+        update: Box<Expr>,
+    },
+    Call(Builtin, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A builtin function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Builtin {
+    Empty,
+    Error,
+    Not,
+    Length,
+    Keys,
+    KeysUnsorted,
+    Has,
+    Select,
+    Map,
+    ToEntries,
+    FromEntries,
+    WithEntries,
+    Add,
+    Type,
+    ToString,
+    ToNumber,
+    Sort,
+}
+
+impl Builtin {
+    /// Looks up a function by its name and number of arguments.
+    fn lookup(name: &str, arity: usize) -> Option<Self> {
+        Some(match (name, arity) {
+            ("empty", 0) => Builtin::Empty,
+            ("error", 0 | 1) => Builtin::Error,
+            ("not", 0) => Builtin::Not,
+            ("length", 0) => Builtin::Length,
+            ("keys", 0) => Builtin::Keys,
+            ("keys_unsorted", 0) => Builtin::KeysUnsorted,
+            ("has", 1) => Builtin::Has,
+            ("select", 1) => Builtin::Select,
+            ("map", 1) => Builtin::Map,
+            ("to_entries", 0) => Builtin::ToEntries,
+            ("from_entries", 0) => Builtin::FromEntries,
+            ("with_entries", 1) => Builtin::WithEntries,
+            ("add", 0) => Builtin::Add,
+            ("type", 0) => Builtin::Type,
+            ("tostring", 0) => Builtin::ToString,
+            ("tonumber", 0) => Builtin::ToNumber,
+            ("sort", 0) => Builtin::Sort,
+            _ => return None,
+        })
+    }
+}
+
+/// Parses a filter.
+pub(super) fn parse(text: &str) -> Result<Expr, Error> {
+    let mut parser = Parser {
+        text,
+        tokens: tokenize(text)?,
+        index: 0,
+        variables: vec![],
+    };
+    let expr = parser.parse_pipe()?;
+    if *parser.peek() != Token::Eof {
+        return Err(parser.unexpected());
+    }
+    Ok(expr)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(&'static str),
+    /// An identifier or keyword.
+    Ident(String),
+    /// `.name`
+    Field(String),
+    String(String),
+    Number(Value),
+    Variable(String),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Punct(p) => write!(f, "`{p}`"),
+            Token::Ident(s) => write!(f, "`{s}`"),
+            Token::Field(s) => write!(f, "`.{s}`"),
+            Token::String(s) => write!(f, "string {s:?}"),
+            Token::Number(n) => write!(f, "number {n}"),
+            Token::Variable(s) => write!(f, "variable `${s}`"),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Punctuations, longest first.
+const PUNCTS: &[&str] = &[
+    "..", "!=", "==", "<=", ">=", "//", "|", ",", ".", "[", "]", "(", ")", "{", "}", ":", ";", "?",
+    "+", "-", "*", "/", "%", "<", ">",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let bytes = text.as_bytes();
+    let error = |message: &str, pos| Error::parse(message, bytes, pos);
+    let is_ident_start = |c: u8| c.is_ascii_alphabetic() || c == b'_';
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_';
+    let mut tokens = vec![];
+    let mut pos = 0;
+    loop {
+        loop {
+            while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+                pos += 1;
+            }
+            // comments run to the end of the line
+            if bytes.get(pos) != Some(&b'#') {
+                break;
+            }
+            pos += bytes[pos..].iter().take_while(|&&c| c != b'\n').count();
+        }
+        let start = pos;
+        let Some(&c) = bytes.get(pos) else {
+            tokens.push((Token::Eof, pos));
+            return Ok(tokens);
+        };
+        let token = match c {
+            b'"' => {
+                pos += 1;
+                loop {
+                    match bytes.get(pos) {
+                        None => return Err(error("unterminated string literal", start)),
+                        Some(b'"') => break,
+                        Some(b'\\') if bytes.get(pos + 1) == Some(&b'(') => {
+                            return Err(error("string interpolation is not supported", pos))
+                        }
+                        Some(b'\\') => pos += 2,
+                        Some(_) => pos += 1,
+                    }
+                }
+                pos += 1;
+                // the literal is a JSON string
+                match Value::from_text(&bytes[start..pos]) {
+                    Ok(s) => Token::String(s.as_str().unwrap().into()),
+                    Err(_) => return Err(error("invalid string literal", start)),
+                }
+            }
+            b'$' if bytes.get(pos + 1).is_some_and(|&c| is_ident_start(c)) => {
+                pos += 1 + bytes[pos + 1..]
+                    .iter()
+                    .take_while(|&&c| is_ident(c))
+                    .count();
+                Token::Variable(text[start + 1..pos].into())
+            }
+            b'.' if bytes.get(pos + 1).is_some_and(|&c| is_ident_start(c)) => {
+                pos += 1 + bytes[pos + 1..]
+                    .iter()
+                    .take_while(|&&c| is_ident(c))
+                    .count();
+                Token::Field(text[start + 1..pos].into())
+            }
+            b'0'..=b'9' => {
+                pos += bytes[pos..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                if bytes.get(pos) == Some(&b'.')
+                    && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit)
+                {
+                    pos += 1;
+                    pos += bytes[pos..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count();
+                }
+                if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+                    pos += 1;
+                    if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+                        pos += 1;
+                    }
+                    pos += bytes[pos..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count();
+                }
+                if bytes.get(pos).is_some_and(|&c| is_ident(c)) {
+                    return Err(error("trailing junk after numeric literal", pos));
+                }
+                match Value::from_text(&bytes[start..pos]) {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => return Err(error("invalid numeric literal", start)),
+                }
+            }
+            c if is_ident_start(c) => {
+                pos += bytes[pos..].iter().take_while(|&&c| is_ident(c)).count();
+                Token::Ident(text[start..pos].into())
+            }
+            _ => match PUNCTS
+                .iter()
+                .find(|p| bytes[pos..].starts_with(p.as_bytes()))
+            {
+                Some(p) => {
+                    pos += p.len();
+                    Token::Punct(p)
+                }
+                None => {
+                    let c = text[pos..].chars().next().unwrap();
+                    return Err(error(&format!("unexpected character `{c}`"), pos));
+                }
+            },
+        };
+        tokens.push((token, start));
+    }
+}
+
+/// Keywords that can not be function names.
+const KEYWORDS: &[&str] = &[
+    "and", "or", "if", "then", "elif", "else", "end", "as", "reduce", "def", "try", "catch",
+    "label", "import", "include", "foreach", "__loc__",
+];
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    /// Variables in scope.
+    variables: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index.min(self.tokens.len() - 1)].0
+    }
+
+    /// Returns the current token and advances, also past the end of input.
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.index += 1;
+        token
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Token::Punct(p) if *p == punct);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Token::Ident(s) if s == keyword);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{punct}`, found {}", self.peek())))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{keyword}`, found {}", self.peek())))
+        }
+    }
+
+    /// Parses `$name` after `as`.
+    fn expect_variable(&mut self) -> Result<String, Error> {
+        match self.next() {
+            Token::Variable(name) => Ok(name),
+            _ => {
+                self.index -= 1;
+                Err(self.error(format!("expected a variable, found {}", self.peek())))
+            }
+        }
+    }
+
+    /// Returns an error at the current token.
+    #[cold]
+    fn error(&self, message: String) -> Error {
+        Error::parse(
+            message,
+            self.text.as_bytes(),
+            self.tokens[self.index.min(self.tokens.len() - 1)].1,
+        )
+    }
+
+    #[cold]
+    fn unexpected(&self) -> Error {
+        self.error(format!("unexpected {}", self.peek()))
+    }
+
+    /// Parses `a | b`, which is right-associative.
+    fn parse_pipe(&mut self) -> Result<Expr, Error> {
+        let left = self.parse_comma()?;
+        if self.eat_punct("|") {
+            let right = self.parse_pipe()?;
+            return Ok(Expr::Pipe(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn parse_comma(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_alternative()?;
+        while self.eat_punct(",") {
+            let right = self.parse_alternative()?;
+            expr = Expr::Comma(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// Parses `a // b`, which is right-associative.
+    fn parse_alternative(&mut self) -> Result<Expr, Error> {
+        let left = self.parse_or()?;
+        if self.eat_punct("//") {
+            let right = self.parse_alternative()?;
+            return Ok(Expr::Alternative(Box::new(left), Box::new(right)));
+        }
+        Ok(left)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_comparison()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_comparison()?;
+            expr = Expr::And(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// Parses a comparison, which is non-associative.
+    fn parse_comparison(&mut self) -> Result<Expr, Error> {
+        let left = self.parse_additive()?;
+        let op = match self.peek() {
+            Token::Punct("==") => CompareOp::Eq,
+            Token::Punct("!=") => CompareOp::Ne,
+            Token::Punct("<") => CompareOp::Lt,
+            Token::Punct("<=") => CompareOp::Le,
+            Token::Punct(">") => CompareOp::Gt,
+            Token::Punct(">=") => CompareOp::Ge,
+            _ => return Ok(left),
+        };
+        self.index += 1;
+        let right = self.parse_additive()?;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Token::Punct("+") => ArithOp::Add,
+                Token::Punct("-") => ArithOp::Sub,
+                _ => return Ok(expr),
+            };
+            self.index += 1;
+            let right = self.parse_multiplicative()?;
+            expr = Expr::Arith(op, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Punct("*") => ArithOp::Mul,
+                Token::Punct("/") => ArithOp::Div,
+                Token::Punct("%") => ArithOp::Rem,
+                _ => return Ok(expr),
+            };
+            self.index += 1;
+            let right = self.parse_unary()?;
+            expr = Expr::Arith(op, Box::new(expr), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        if self.eat_punct("-") {
+            return Ok(Expr::Neg(Box::new(self.parse_postfix(true)?)));
+        }
+        self.parse_postfix(true)
+    }
+
+    /// Parses a term followed by suffixes, and `as $name | body` if `bind` is true.
+    fn parse_postfix(&mut self, bind: bool) -> Result<Expr, Error> {
+        let mut expr = self.parse_term()?;
+        loop {
+            expr = match self.peek() {
+                Token::Field(name) => {
+                    let key = Expr::Literal(Value::from(name.as_str()));
+                    self.index += 1;
+                    Expr::Index(Box::new(expr), Box::new(key))
+                }
+                Token::Punct(".") if matches!(self.tokens[self.index + 1].0, Token::String(_)) => {
+                    self.index += 1;
+                    let Token::String(name) = self.next() else {
+                        unreachable!()
+                    };
+                    let key = Expr::Literal(Value::from(name.as_str()));
+                    Expr::Index(Box::new(expr), Box::new(key))
+                }
+                Token::Punct(".") if self.tokens[self.index + 1].0 == Token::Punct("[") => {
+                    self.index += 1;
+                    self.parse_bracket_suffix(expr)?
+                }
+                Token::Punct("[") => self.parse_bracket_suffix(expr)?,
+                Token::Punct("?") => {
+                    self.index += 1;
+                    Expr::Try(Box::new(expr))
+                }
+                _ => break,
+            };
+        }
+        if bind && self.eat_keyword("as") {
+            let name = self.expect_variable()?;
+            self.expect_punct("|")?;
+            self.variables.push(name.clone());
+            let body = self.parse_pipe();
+            self.variables.pop();
+            return Ok(Expr::Bind(Box::new(expr), name, Box::new(body?)));
+        }
+        Ok(expr)
+    }
+
+    /// Parses `[]`, `[k]` or `[from:to]` after `expr`.
+    fn parse_bracket_suffix(&mut self, expr: Expr) -> Result<Expr, Error> {
+        self.expect_punct("[")?;
+        if self.eat_punct("]") {
+            return Ok(Expr::Iterate(Box::new(expr)));
+        }
+        let from = match self.peek() {
+            Token::Punct(":") => None,
+            _ => Some(Box::new(self.parse_pipe()?)),
+        };
+        if !self.eat_punct(":") {
+            self.expect_punct("]")?;
+            return Ok(Expr::Index(Box::new(expr), from.unwrap()));
+        }
+        let to = match self.peek() {
+            Token::Punct("]") if from.is_some() => None,
+            Token::Punct("]") => return Err(self.unexpected()),
+            _ => Some(Box::new(self.parse_pipe()?)),
+        };
+        self.expect_punct("]")?;
+        Ok(Expr::Slice(Box::new(expr), from, to))
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, Error> {
+        match self.next() {
+            Token::Punct(".") => {
+                if matches!(self.peek(), Token::String(_)) {
+                    // `."key"` is a suffix of `.`
+                    self.index -= 1;
+                }
+                Ok(Expr::Identity)
+            }
+            Token::Punct("..") => Ok(Expr::Recurse),
+            Token::Field(_) => {
+                // `.key` is a suffix of `.`
+                self.index -= 1;
+                Ok(Expr::Identity)
+            }
+            Token::Number(n) => Ok(Expr::Literal(n)),
+            Token::String(s) => Ok(Expr::Literal(Value::from(s.as_str()))),
+            Token::Variable(name) => {
+                if !self.variables.contains(&name) {
+                    self.index -= 1;
+                    return Err(self.error(format!("${name} is not defined")));
+                }
+                Ok(Expr::Variable(name))
+            }
+            Token::Punct("(") => {
+                let expr = self.parse_pipe()?;
+                self.expect_punct(")")?;
+                Ok(expr)
+            }
+            Token::Punct("[") => {
+                if self.eat_punct("]") {
+                    return Ok(Expr::Array(None));
+                }
+                let expr = self.parse_pipe()?;
+                self.expect_punct("]")?;
+                Ok(Expr::Array(Some(Box::new(expr))))
+            }
+            Token::Punct("{") => self.parse_object(),
+            Token::Ident(name) => match name.as_str() {
+                "null" => Ok(Expr::Literal(Value::null())),
+                "true" => Ok(Expr::Literal(Value::from(true))),
+                "false" => Ok(Expr::Literal(Value::from(false))),
+                "if" => self.parse_if(),
+                "reduce" => self.parse_reduce(),
+                _ if KEYWORDS.contains(&name.as_str()) => {
+                    self.index -= 1;
+                    Err(self.error(format!("`{name}` is not supported here")))
+                }
+                _ => self.parse_call(name),
+            },
+            _ => {
+                self.index -= 1;
+                Err(self.unexpected())
+            }
+        }
+    }
+
+    /// Parses a function call after its name.
+    fn parse_call(&mut self, name: String) -> Result<Expr, Error> {
+        let start = self.index - 1;
+        let mut args = vec![];
+        if self.eat_punct("(") {
+            loop {
+                args.push(self.parse_pipe()?);
+                if !self.eat_punct(";") {
+                    break;
+                }
+            }
+            self.expect_punct(")")?;
+        }
+        match Builtin::lookup(&name, args.len()) {
+            Some(builtin) => Ok(Expr::Call(builtin, args)),
+            None => {
+                self.index = start;
+                Err(self.error(format!("{name}/{} is not defined", args.len())))
+            }
+        }
+    }
+
+    /// Parses `{...}` after `{`.
+    fn parse_object(&mut self) -> Result<Expr, Error> {
+        let mut entries = vec![];
+        if self.eat_punct("}") {
+            return Ok(Expr::Object(entries));
+        }
+        loop {
+            let (key, shorthand) = match self.next() {
+                Token::Ident(name) => {
+                    let value = Expr::Index(
+                        Box::new(Expr::Identity),
+                        Box::new(Expr::Literal(Value::from(name.as_str()))),
+                    );
+                    (Expr::Literal(Value::from(name.as_str())), Some(value))
+                }
+                Token::String(name) => {
+                    let value = Expr::Index(
+                        Box::new(Expr::Identity),
+                        Box::new(Expr::Literal(Value::from(name.as_str()))),
+                    );
+                    (Expr::Literal(Value::from(name.as_str())), Some(value))
+                }
+                Token::Variable(name) => {
+                    if !self.variables.contains(&name) {
+                        self.index -= 1;
+                        return Err(self.error(format!("${name} is not defined")));
+                    }
+                    let key = Expr::Literal(Value::from(name.as_str()));
+                    (key, Some(Expr::Variable(name)))
+                }
+                Token::Punct("(") => {
+                    let key = self.parse_pipe()?;
+                    self.expect_punct(")")?;
+                    (key, None)
+                }
+                _ => {
+                    self.index -= 1;
+                    return Err(
+                        self.error(format!("expected an object key, found {}", self.peek()))
+                    );
+                }
+            };
+            let value = if self.eat_punct(":") {
+                self.parse_object_value()?
+            } else {
+                match shorthand {
+                    Some(value) => value,
+                    None => return Err(self.error(format!("expected `:`, found {}", self.peek()))),
+                }
+            };
+            entries.push((key, value));
+            if self.eat_punct("}") {
+                return Ok(Expr::Object(entries));
+            }
+            self.expect_punct(",")?;
+        }
+    }
+
+    /// Parses the value of an object entry, which is a pipe without commas.
+    fn parse_object_value(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.parse_alternative()?;
+        while self.eat_punct("|") {
+            let right = self.parse_alternative()?;
+            expr = Expr::Pipe(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    /// Parses `if ... end` after `if`.
+    fn parse_if(&mut self) -> Result<Expr, Error> {
+        let mut branches = vec![];
+        loop {
+            let cond = self.parse_pipe()?;
+            self.expect_keyword("then")?;
+            let then = self.parse_pipe()?;
+            branches.push((cond, then));
+            if !self.eat_keyword("elif") {
+                break;
+            }
+        }
+        let otherwise = if self.eat_keyword("else") {
+            self.parse_pipe()?
+        } else {
+            Expr::Identity
+        };
+        self.expect_keyword("end")?;
+        Ok(Expr::If(branches, Box::new(otherwise)))
+    }
+
+    /// Parses `reduce source as $name (init; update)` after `reduce`.
+    fn parse_reduce(&mut self) -> Result<Expr, Error> {
+        let source = self.parse_postfix(false)?;
+        self.expect_keyword("as")?;
+        let name = self.expect_variable()?;
+        self.expect_punct("(")?;
+        let init = self.parse_pipe()?;
+        self.expect_punct(";")?;
+        self.variables.push(name.clone());
+        let update = self.parse_pipe();
+        self.variables.pop();
+        let update = update?;
+        self.expect_punct(")")?;
+        Ok(Expr::Reduce {
+            source: Box::new(source),
+            name,
+            init: Box::new(init),
+            update: Box::new(update),
+        })
+    }
+}
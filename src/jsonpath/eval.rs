@@ -0,0 +1,729 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evaluation of a compiled path expression over [`ValueRef`].
+
+use std::cmp::Ordering;
+
+use super::parser::{Accessor, BinaryOp, CompareOp, Expr, Method, UnaryOp};
+use super::JsonItem;
+use crate::{Error, Value, ValueRef};
+
+type Items<'a> = Vec<JsonItem<'a>>;
+
+/// The result of a predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Truth {
+    True,
+    False,
+    Unknown,
+}
+
+impl From<bool> for Truth {
+    fn from(b: bool) -> Self {
+        if b {
+            Truth::True
+        } else {
+            Truth::False
+        }
+    }
+}
+
+impl Truth {
+    pub fn to_item<'a>(self) -> JsonItem<'a> {
+        JsonItem::Owned(match self {
+            Truth::True => Value::from(true),
+            Truth::False => Value::from(false),
+            Truth::Unknown => Value::from(()),
+        })
+    }
+}
+
+/// A number in an arithmetic expression.
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn from_value(value: ValueRef<'_>) -> Option<Self> {
+        let n = value.as_number()?;
+        Some(match n.as_i64() {
+            Some(i) => Number::Int(i),
+            None => Number::Float(n.as_f64()?),
+        })
+    }
+
+    fn to_f64(self) -> f64 {
+        match self {
+            Number::Int(i) => i as f64,
+            Number::Float(f) => f,
+        }
+    }
+
+    /// Converts an integral float to an integer if it fits.
+    fn normalize(f: f64) -> Self {
+        if f.fract() == 0.0 && f >= i64::MIN as f64 && f < i64::MAX as f64 {
+            Number::Int(f as i64)
+        } else {
+            Number::Float(f)
+        }
+    }
+
+    fn to_item<'a>(self) -> Result<JsonItem<'a>, Error> {
+        match self {
+            Number::Int(i) => Ok(JsonItem::Owned(Value::from(i))),
+            Number::Float(f) if f.is_finite() => Ok(JsonItem::Owned(Value::from(f))),
+            Number::Float(_) => Err(error("numeric value out of range")),
+        }
+    }
+
+    fn compare(self, other: Self) -> Ordering {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => a.cmp(&b),
+            (a, b) => a.to_f64().total_cmp(&b.to_f64()),
+        }
+    }
+
+    fn binary(self, op: BinaryOp, other: Self) -> Result<Self, Error> {
+        if let (Number::Int(a), Number::Int(b)) = (self, other) {
+            let result = match op {
+                BinaryOp::Add => a.checked_add(b),
+                BinaryOp::Sub => a.checked_sub(b),
+                BinaryOp::Mul => a.checked_mul(b),
+                BinaryOp::Div | BinaryOp::Rem if b == 0 => return Err(error("division by zero")),
+                // the quotient is a float if the division is inexact
+                BinaryOp::Div if a % b != 0 => None,
+                BinaryOp::Div => a.checked_div(b),
+                BinaryOp::Rem => Some(a.wrapping_rem(b)),
+            };
+            if let Some(result) = result {
+                return Ok(Number::Int(result));
+            }
+        }
+        let (a, b) = (self.to_f64(), other.to_f64());
+        let result = match op {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div | BinaryOp::Rem if b == 0.0 => return Err(error("division by zero")),
+            BinaryOp::Div => a / b,
+            BinaryOp::Rem => a % b,
+        };
+        Ok(Number::Float(result))
+    }
+}
+
+#[cold]
+fn error(message: impl Into<String>) -> Error {
+    Error::JsonPath {
+        message: message.into(),
+    }
+}
+
+/// Calls `f` with the value of the item and pushes the values it emits as items.
+///
+/// Values inside an owned item are copied, while values inside a borrowed item are borrowed.
+fn with_children<'a, F>(item: &JsonItem<'a>, items: &mut Items<'a>, f: F) -> Result<(), Error>
+where
+    F: for<'b> FnOnce(ValueRef<'b>, &mut dyn FnMut(ValueRef<'b>)) -> Result<(), Error>,
+{
+    match item {
+        JsonItem::Borrowed(value) => f(*value, &mut |v| items.push(JsonItem::from_ref(v))),
+        JsonItem::Owned(value) => f(value.as_ref(), &mut |v| {
+            items.push(JsonItem::Owned(v.to_owned()))
+        }),
+    }
+}
+
+/// The state of an evaluation.
+pub(super) struct Evaluator<'a> {
+    pub root: ValueRef<'a>,
+    pub vars: Option<ValueRef<'a>>,
+    pub strict: bool,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Evaluates the expression to a sequence of items.
+    ///
+    /// `current` is the value of `@`, and `last` is the value of `last`.
+    pub fn eval(
+        &self,
+        expr: &Expr,
+        current: &JsonItem<'a>,
+        last: Option<i64>,
+    ) -> Result<Items<'a>, Error> {
+        Ok(match expr {
+            Expr::Root => vec![JsonItem::from_ref(self.root)],
+            Expr::Current => vec![current.clone()],
+            Expr::Last => vec![Number::Int(last.expect("`last` outside of subscripts")).to_item()?],
+            Expr::Variable(name) => vec![self.variable(name)?],
+            Expr::Literal(value) => vec![JsonItem::Owned(value.clone())],
+            Expr::Accessors(base, accessors) => {
+                let mut items = self.eval(base, current, last)?;
+                for accessor in accessors {
+                    items = self.access(accessor, items, current, last)?;
+                }
+                items
+            }
+            Expr::Unary(op, expr) => {
+                let items = self.unwrap(self.eval(expr, current, last)?)?;
+                items
+                    .into_iter()
+                    .map(|item| {
+                        let sign = match op {
+                            UnaryOp::Plus => "+",
+                            UnaryOp::Minus => "-",
+                        };
+                        let Some(n) = Number::from_value(item.as_ref()) else {
+                            return Err(error(format!(
+                                "operand of unary jsonpath operator {sign} is not a numeric value"
+                            )));
+                        };
+                        match (op, n) {
+                            (UnaryOp::Plus, _) => Ok(item),
+                            (UnaryOp::Minus, Number::Int(i)) if i != i64::MIN => {
+                                Number::Int(-i).to_item()
+                            }
+                            (UnaryOp::Minus, n) => Number::Float(-n.to_f64()).to_item(),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            Expr::Binary(op, left, right) => {
+                let operand = |expr, side| {
+                    let items = self.unwrap(self.eval(expr, current, last)?)?;
+                    match items.as_slice() {
+                        [item] => Number::from_value(item.as_ref()),
+                        _ => None,
+                    }
+                    .ok_or_else(|| {
+                        error(format!(
+                            "{side} operand of jsonpath operator {op} is not a single numeric value"
+                        ))
+                    })
+                };
+                let l = operand(left, "left")?;
+                let r = operand(right, "right")?;
+                vec![l.binary(*op, r)?.to_item()?]
+            }
+            _ => vec![self.predicate(expr, current, last).to_item()],
+        })
+    }
+
+    fn variable(&self, name: &str) -> Result<JsonItem<'a>, Error> {
+        self.vars
+            .and_then(|vars| vars.as_object()?.get(name))
+            .map(JsonItem::from_ref)
+            .ok_or_else(|| error(format!("could not find jsonpath variable \"{name}\"")))
+    }
+
+    /// Replaces arrays in the sequence by their elements in lax mode.
+    fn unwrap(&self, items: Items<'a>) -> Result<Items<'a>, Error> {
+        if self.strict || !items.iter().any(|item| item.as_ref().is_array()) {
+            return Ok(items);
+        }
+        let mut unwrapped = Vec::with_capacity(items.len());
+        for item in items {
+            if item.as_ref().is_array() {
+                with_children(&item, &mut unwrapped, |value, emit| {
+                    value.as_array().unwrap().iter().for_each(emit);
+                    Ok(())
+                })?;
+            } else {
+                unwrapped.push(item);
+            }
+        }
+        Ok(unwrapped)
+    }
+
+    /// Applies an accessor to every item of the sequence.
+    fn access(
+        &self,
+        accessor: &Accessor,
+        items: Items<'a>,
+        current: &JsonItem<'a>,
+        last: Option<i64>,
+    ) -> Result<Items<'a>, Error> {
+        let strict = self.strict;
+        let mut output = Vec::new();
+        match accessor {
+            Accessor::Member(key) => {
+                for item in self.unwrap(items)? {
+                    with_children(&item, &mut output, |value, emit| {
+                        match value.as_object() {
+                            Some(object) => match object.get(key) {
+                                Some(v) => emit(v),
+                                None if strict => {
+                                    return Err(error(format!(
+                                        "JSON object does not contain key \"{key}\""
+                                    )))
+                                }
+                                None => {}
+                            },
+                            None if strict => {
+                                return Err(error(
+                                    "jsonpath member accessor can only be applied to an object",
+                                ))
+                            }
+                            None => {}
+                        }
+                        Ok(())
+                    })?;
+                }
+            }
+            Accessor::MemberWildcard => {
+                for item in self.unwrap(items)? {
+                    with_children(&item, &mut output, |value, emit| {
+                        match value.as_object() {
+                            Some(object) => object.values().for_each(emit),
+                            None if strict => {
+                                return Err(error(
+                                    "jsonpath wildcard member accessor can only be applied to an object",
+                                ))
+                            }
+                            None => {}
+                        }
+                        Ok(())
+                    })?;
+                }
+            }
+            Accessor::ElementWildcard => {
+                for item in items {
+                    if item.as_ref().is_array() {
+                        with_children(&item, &mut output, |value, emit| {
+                            value.as_array().unwrap().iter().for_each(emit);
+                            Ok(())
+                        })?;
+                    } else if strict {
+                        return Err(error(
+                            "jsonpath wildcard array accessor can only be applied to an array",
+                        ));
+                    } else {
+                        output.push(item);
+                    }
+                }
+            }
+            Accessor::Elements(subscripts) => {
+                for item in items {
+                    let size = match item.as_ref().as_array() {
+                        Some(array) => array.len(),
+                        // a non-array is treated as an array of itself in lax mode
+                        None if !strict => 1,
+                        None => {
+                            return Err(error(
+                                "jsonpath array accessor can only be applied to an array",
+                            ))
+                        }
+                    };
+                    let last = Some(size as i64 - 1);
+                    for (from, to) in subscripts {
+                        let from = self.subscript(from, current, last)?;
+                        let to = match to {
+                            Some(to) => self.subscript(to, current, last)?,
+                            None => from,
+                        };
+                        let (from, to) = if strict {
+                            if from < 0 || from > to || to >= size as i64 {
+                                return Err(error("jsonpath array subscript is out of bounds"));
+                            }
+                            (from, to)
+                        } else {
+                            (from.max(0), to.min(size as i64 - 1))
+                        };
+                        if from > to {
+                            continue;
+                        }
+                        let range = from as usize..=to as usize;
+                        if item.as_ref().is_array() {
+                            with_children(&item, &mut output, |value, emit| {
+                                let array = value.as_array().unwrap();
+                                range.for_each(|i| emit(array.get(i).unwrap()));
+                                Ok(())
+                            })?;
+                        } else {
+                            output.push(item.clone());
+                        }
+                    }
+                }
+            }
+            Accessor::Descendants { min, max } => {
+                fn visit<'b>(
+                    value: ValueRef<'b>,
+                    level: u32,
+                    (min, max): (u32, u32),
+                    emit: &mut dyn FnMut(ValueRef<'b>),
+                ) {
+                    if level >= min {
+                        emit(value);
+                    }
+                    if level >= max {
+                        return;
+                    }
+                    match value {
+                        ValueRef::Array(array) => {
+                            for v in array.iter() {
+                                visit(v, level + 1, (min, max), emit);
+                            }
+                        }
+                        ValueRef::Object(object) => {
+                            for v in object.values() {
+                                visit(v, level + 1, (min, max), emit);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                for item in items {
+                    with_children(&item, &mut output, |value, emit| {
+                        visit(value, 0, (*min, *max), emit);
+                        Ok(())
+                    })?;
+                }
+            }
+            Accessor::Filter(predicate) => {
+                for item in self.unwrap(items)? {
+                    if self.predicate(predicate, &item, last) == Truth::True {
+                        output.push(item);
+                    }
+                }
+            }
+            Accessor::Method(method) => {
+                let items = match method {
+                    Method::Type | Method::Size => items,
+                    _ => self.unwrap(items)?,
+                };
+                for item in items {
+                    self.method(*method, item, &mut output)?;
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Evaluates an array subscript to an integer.
+    fn subscript(
+        &self,
+        expr: &Expr,
+        current: &JsonItem<'a>,
+        last: Option<i64>,
+    ) -> Result<i64, Error> {
+        let items = self.eval(expr, current, last)?;
+        match items.as_slice() {
+            [item] => match Number::from_value(item.as_ref()) {
+                Some(Number::Int(i)) => Some(i),
+                Some(Number::Float(f)) if f.abs() < i32::MAX as f64 => Some(f.trunc() as i64),
+                _ => None,
+            },
+            _ => None,
+        }
+        .ok_or_else(|| error("jsonpath array subscript is not a single numeric value"))
+    }
+
+    /// Applies an item method and pushes the results.
+    fn method(
+        &self,
+        method: Method,
+        item: JsonItem<'a>,
+        output: &mut Items<'a>,
+    ) -> Result<(), Error> {
+        let value = item.as_ref();
+        let invalid = |expected: &str| {
+            error(format!(
+                "jsonpath item method {method} can only be applied to {expected}"
+            ))
+        };
+        let string = |s: &str| JsonItem::Owned(Value::from(s));
+        let result = match method {
+            Method::Type => string(match value {
+                ValueRef::Null => "null",
+                ValueRef::Bool(_) => "boolean",
+                ValueRef::Number(_) => "number",
+                ValueRef::String(_) => "string",
+                ValueRef::Array(_) => "array",
+                ValueRef::Object(_) => "object",
+                ValueRef::RawJson(_) => unreachable!("raw JSON is parsed into items"),
+            }),
+            Method::Size => match value.as_array() {
+                Some(array) => Number::Int(array.len() as i64).to_item()?,
+                None if !self.strict => Number::Int(1).to_item()?,
+                None => return Err(invalid("an array")),
+            },
+            Method::Double => {
+                let f = match value {
+                    ValueRef::Number(n) => n.as_f64().unwrap(),
+                    ValueRef::String(s) => s.as_str().trim().parse::<f64>().map_err(|_| {
+                        error(format!(
+                            "argument \"{}\" of jsonpath item method {method} is invalid for type double precision",
+                            s.as_str()
+                        ))
+                    })?,
+                    _ => return Err(invalid("a string or numeric value")),
+                };
+                if !f.is_finite() {
+                    return Err(error(format!(
+                        "NaN or Infinity is not allowed for jsonpath item method {method}"
+                    )));
+                }
+                Number::Float(f).to_item()?
+            }
+            Method::Ceiling | Method::Floor | Method::Abs => {
+                let n = Number::from_value(value).ok_or_else(|| invalid("a numeric value"))?;
+                match (method, n) {
+                    (Method::Ceiling | Method::Floor, Number::Int(_)) => item,
+                    (Method::Abs, Number::Int(i)) if i >= 0 => item,
+                    (Method::Abs, Number::Int(i)) if i != i64::MIN => Number::Int(-i).to_item()?,
+                    (Method::Ceiling, n) => Number::normalize(n.to_f64().ceil()).to_item()?,
+                    (Method::Floor, n) => Number::normalize(n.to_f64().floor()).to_item()?,
+                    (_, n) => Number::Float(n.to_f64().abs()).to_item()?,
+                }
+            }
+            Method::KeyValue => {
+                let object = value.as_object().ok_or_else(|| invalid("an object"))?;
+                let id = Value::from(self.object_id(&item));
+                // each pair is a separate item
+                for (key, value) in object.iter() {
+                    let key = Value::from(key);
+                    let pair = [("id", id.as_ref()), ("key", key.as_ref()), ("value", value)];
+                    output.push(JsonItem::Owned(Value::object(pair)));
+                }
+                return Ok(());
+            }
+            Method::BigInt | Method::Integer => {
+                let (min, max, ty) = match method {
+                    Method::BigInt => (i64::MIN, i64::MAX, "bigint"),
+                    _ => (i32::MIN as i64, i32::MAX as i64, "integer"),
+                };
+                let n = match value {
+                    ValueRef::Number(n) => Number::from_value(ValueRef::Number(n)),
+                    ValueRef::String(s) => s.as_str().trim().parse::<i64>().ok().map(Number::Int),
+                    _ => return Err(invalid("a string or numeric value")),
+                };
+                let i = match n {
+                    Some(Number::Int(i)) => Some(i),
+                    Some(Number::Float(f)) if f.abs() < 9.2e18 => Some(f.round() as i64),
+                    _ => None,
+                }
+                .filter(|i| (min..=max).contains(i))
+                .ok_or_else(|| {
+                    error(format!(
+                        "argument \"{value}\" of jsonpath item method {method} is invalid for type {ty}"
+                    ))
+                })?;
+                Number::Int(i).to_item()?
+            }
+            Method::Number => match value {
+                ValueRef::Number(_) => item,
+                ValueRef::String(s) => {
+                    let parsed = Value::from_text(s.as_str().trim().as_bytes())
+                        .ok()
+                        .filter(|v| v.as_ref().is_number());
+                    match parsed {
+                        // an integral numeric such as `1e2` is stored as an integer to be
+                        // printed as PostgreSQL does
+                        Some(v) => match v.as_ref().as_f64() {
+                            Some(f) if f.fract() == 0.0 && f.abs() < 9.2e18 => {
+                                JsonItem::Owned(Value::from(f as i64))
+                            }
+                            _ => JsonItem::Owned(v),
+                        },
+                        None => {
+                            return Err(error(format!(
+                                "argument \"{}\" of jsonpath item method {method} is invalid for type numeric",
+                                s.as_str()
+                            )))
+                        }
+                    }
+                }
+                _ => return Err(invalid("a string or numeric value")),
+            },
+            Method::Boolean => {
+                let b = match value {
+                    ValueRef::Bool(b) => Some(b),
+                    ValueRef::Number(n) => n.as_i64().map(|i| i != 0),
+                    ValueRef::String(s) => parse_bool(s.as_str()),
+                    _ => return Err(invalid("a boolean, string, or numeric value")),
+                };
+                let b = b.ok_or_else(|| {
+                    error(format!(
+                        "argument \"{value}\" of jsonpath item method {method} is invalid for type boolean"
+                    ))
+                })?;
+                JsonItem::Owned(Value::from(b))
+            }
+            Method::String => match value {
+                ValueRef::String(_) => item,
+                ValueRef::Number(_) => string(&value.to_pg_text()),
+                ValueRef::Bool(b) => string(if b { "true" } else { "false" }),
+                _ => return Err(invalid("a boolean, string, or numeric value")),
+            },
+        };
+        output.push(result);
+        Ok(())
+    }
+
+    /// Returns a unique identifier of an object, used by `.keyvalue()`.
+    ///
+    /// The identifier is the distance in bytes from the end of the object to the end of the input
+    /// value, so the root object has identifier 0. Objects in the variables have identifiers
+    /// starting from 10000000000, and computed objects have identifier 0.
+    fn object_id(&self, item: &JsonItem<'a>) -> i64 {
+        let JsonItem::Borrowed(value) = item else {
+            return 0;
+        };
+        let address = value.as_slice().as_ptr_range().end as usize;
+        let offset = |base: ValueRef<'_>| {
+            let range = base.as_slice().as_ptr_range();
+            (range.start as usize..=range.end as usize)
+                .contains(&address)
+                .then(|| (range.end as usize - address) as i64)
+        };
+        offset(self.root)
+            .or_else(|| Some(10_000_000_000 + offset(self.vars?)?))
+            .unwrap_or(0)
+    }
+
+    /// Evaluates a predicate.
+    ///
+    /// Errors in the operands make the result unknown.
+    pub fn predicate(&self, expr: &Expr, current: &JsonItem<'a>, last: Option<i64>) -> Truth {
+        let operand = |expr| {
+            self.eval(expr, current, last)
+                .and_then(|items| self.unwrap(items))
+        };
+        match expr {
+            Expr::And(left, right) => match self.predicate(left, current, last) {
+                Truth::False => Truth::False,
+                l => match self.predicate(right, current, last) {
+                    Truth::True => l,
+                    r => r,
+                },
+            },
+            Expr::Or(left, right) => match self.predicate(left, current, last) {
+                Truth::True => Truth::True,
+                l => match self.predicate(right, current, last) {
+                    Truth::False => l,
+                    r => r,
+                },
+            },
+            Expr::Not(expr) => match self.predicate(expr, current, last) {
+                Truth::True => Truth::False,
+                Truth::False => Truth::True,
+                Truth::Unknown => Truth::Unknown,
+            },
+            Expr::IsUnknown(expr) => (self.predicate(expr, current, last) == Truth::Unknown).into(),
+            Expr::Exists(expr) => match self.eval(expr, current, last) {
+                Ok(items) => (!items.is_empty()).into(),
+                Err(_) => Truth::Unknown,
+            },
+            Expr::Compare(op, left, right) => {
+                let (Ok(left), Ok(right)) = (operand(left), operand(right)) else {
+                    return Truth::Unknown;
+                };
+                self.any(left.iter().flat_map(|l| {
+                    right
+                        .iter()
+                        .map(move |r| compare(*op, l.as_ref(), r.as_ref()))
+                }))
+            }
+            #[cfg(feature = "regex")]
+            Expr::LikeRegex(expr, regex) => {
+                let Ok(items) = operand(expr) else {
+                    return Truth::Unknown;
+                };
+                self.any(items.iter().map(|item| match item.as_ref().as_str() {
+                    Some(s) => regex.is_match(s).into(),
+                    None => Truth::Unknown,
+                }))
+            }
+            Expr::StartsWith(expr, prefix) => {
+                let (Ok(items), Ok(prefix)) = (operand(expr), self.eval(prefix, current, last))
+                else {
+                    return Truth::Unknown;
+                };
+                let [prefix] = prefix.as_slice() else {
+                    return Truth::Unknown;
+                };
+                let Some(prefix) = prefix.as_ref().as_str() else {
+                    return Truth::Unknown;
+                };
+                self.any(items.iter().map(|item| match item.as_ref().as_str() {
+                    Some(s) => s.starts_with(prefix).into(),
+                    None => Truth::Unknown,
+                }))
+            }
+            _ => unreachable!("not a predicate"),
+        }
+    }
+
+    /// Combines the results of a predicate over the pairs of items.
+    ///
+    /// In lax mode the result is true as soon as any pair is true, while in strict mode it is
+    /// unknown as soon as any pair is unknown.
+    fn any(&self, results: impl Iterator<Item = Truth>) -> Truth {
+        let mut found = false;
+        let mut unknown = false;
+        for result in results {
+            match result {
+                Truth::True if !self.strict => return Truth::True,
+                Truth::True => found = true,
+                Truth::Unknown if self.strict => return Truth::Unknown,
+                Truth::Unknown => unknown = true,
+                Truth::False => {}
+            }
+        }
+        if found {
+            Truth::True
+        } else if unknown {
+            Truth::Unknown
+        } else {
+            Truth::False
+        }
+    }
+}
+
+/// Compares two scalar values.
+fn compare(op: CompareOp, left: ValueRef<'_>, right: ValueRef<'_>) -> Truth {
+    let ordering = match (left, right) {
+        (ValueRef::Null, ValueRef::Null) => Ordering::Equal,
+        // null is only equal to null, and not comparable with other values
+        (ValueRef::Null, _) | (_, ValueRef::Null) => return (op == CompareOp::Ne).into(),
+        (ValueRef::Bool(a), ValueRef::Bool(b)) => a.cmp(&b),
+        (ValueRef::Number(_), ValueRef::Number(_)) => Number::from_value(left)
+            .unwrap()
+            .compare(Number::from_value(right).unwrap()),
+        (ValueRef::String(a), ValueRef::String(b)) => a.as_str().cmp(b.as_str()),
+        _ => return Truth::Unknown,
+    };
+    match op {
+        CompareOp::Eq => ordering.is_eq(),
+        CompareOp::Ne => ordering.is_ne(),
+        CompareOp::Lt => ordering.is_lt(),
+        CompareOp::Le => ordering.is_le(),
+        CompareOp::Gt => ordering.is_gt(),
+        CompareOp::Ge => ordering.is_ge(),
+    }
+    .into()
+}
+
+/// Parses a boolean the same way as PostgreSQL.
+fn parse_bool(s: &str) -> Option<bool> {
+    let s = s.trim().to_ascii_lowercase();
+    match s.as_str() {
+        "t" | "true" | "y" | "yes" | "on" | "1" => Some(true),
+        "f" | "false" | "n" | "no" | "of" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,725 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSON path languages.
+//!
+//! Two languages are supported:
+//!
+//! - [`JsonPath`]: JSONPath as standardized by RFC 9535, which selects nodes of a value.
+//! - [`SqlJsonPath`]: the SQL/JSON path language, as implemented by PostgreSQL's `jsonpath` type.
+//!
+//! # SQL/JSON path
+//!
+//! A [`SqlJsonPath`] is compiled once and evaluated directly over [`ValueRef`]s:
+//!
+//! | PostgreSQL                         | jsonbb                       |
+//! |------------------------------------|------------------------------|
+//! | `jsonb_path_query(target, path)`   | [`SqlJsonPath::query`]       |
+//! | `jsonb_path_query_first`           | [`SqlJsonPath::query_first`] |
+//! | `jsonb_path_exists`, `@?`          | [`SqlJsonPath::exists`]      |
+//! | `jsonb_path_match`, `@@`           | [`SqlJsonPath::matches`]     |
+//!
+//! The operators `@?` and `@@` suppress errors, which is `.ok()` on the result.
+//!
+//! The supported syntax includes:
+//!
+//! - `lax` (default) and `strict` modes
+//! - `$`, `@`, `$name` variables and literals
+//! - `.key`, `."key"`, `.*`, `[*]`, `.**`, `.**{2 to last}`
+//! - array subscripts and slices such as `[0, 2 to last]`
+//! - filters `? (...)` with `==`, `!=`, `<>`, `<`, `<=`, `>`, `>=`, `&&`, `||`, `!`,
+//!   `exists (...)`, `like_regex "..." flag "..."`, `starts with` and `(...) is unknown`
+//!   (`like_regex` requires the `regex` feature, which is enabled by default)
+//! - arithmetic `+`, `-`, `*`, `/`, `%`
+//! - item methods `.type()`, `.size()`, `.double()`, `.ceiling()`, `.floor()`, `.abs()`,
+//!   `.keyvalue()`, `.bigint()`, `.integer()`, `.number()`, `.boolean()` and `.string()`
+//!
+//! Date and time methods such as `.datetime()` are not supported. Numbers are computed as 64-bit
+//! integers or floats rather than arbitrary precision decimals, so the scale of a number is not
+//! kept: `.string()` and `.number()` turn `1.50` into `"1.5"` and `1.5`, where PostgreSQL keeps
+//! `1.50`.
+//!
+//! ## Example
+//!
+//! ```
+//! use jsonbb::jsonpath::SqlJsonPath;
+//! use jsonbb::Value;
+//!
+//! let value: Value = r#"{"a": [1, 2, 3, 4]}"#.parse().unwrap();
+//! let vars: Value = r#"{"min": 2}"#.parse().unwrap();
+//!
+//! let path = SqlJsonPath::new("$.a[*] ? (@ > $min)").unwrap();
+//! let items = path.query(value.as_ref(), Some(vars.as_ref())).unwrap();
+//! assert_eq!(items.len(), 2);
+//! assert_eq!(items[0].as_ref().as_u64(), Some(3));
+//!
+//! let path = SqlJsonPath::new("strict $.b").unwrap();
+//! assert!(path.exists(value.as_ref(), None).is_err());
+//!
+//! let path = SqlJsonPath::new("$.a.size() == 4").unwrap();
+//! assert_eq!(path.matches(value.as_ref(), None).unwrap(), Some(true));
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use self::eval::Evaluator;
+use self::parser::Plan;
+use crate::{Error, Value, ValueRef};
+
+pub use self::rfc9535::{JsonPath, NormalizedPath, PathElement};
+
+mod eval;
+mod parser;
+mod rfc9535;
+
+/// A compiled SQL/JSON path expression.
+///
+/// See the [module documentation](self) for the supported syntax.
+#[derive(Debug, Clone)]
+pub struct SqlJsonPath {
+    text: String,
+    plan: Plan,
+}
+
+/// An item of the sequence returned by a path, either a value inside the input or a computed value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonItem<'a> {
+    /// A value inside the input value or variables.
+    Borrowed(ValueRef<'a>),
+    /// A value computed by the path, e.g. by arithmetic or an item method.
+    Owned(Value),
+}
+
+impl<'a> JsonItem<'a> {
+    /// Converts a value to an item, resolving raw JSON nodes to their values.
+    pub(crate) fn from_ref(value: ValueRef<'a>) -> Self {
+        JsonItem::Borrowed(value.resolve())
+    }
+
+    /// Returns a reference to the value.
+    pub fn as_ref(&self) -> ValueRef<'_> {
+        match self {
+            JsonItem::Borrowed(value) => *value,
+            JsonItem::Owned(value) => value.as_ref(),
+        }
+    }
+
+    /// Converts the item into an owned value.
+    pub fn into_owned(self) -> Value {
+        match self {
+            JsonItem::Borrowed(value) => value.to_owned(),
+            JsonItem::Owned(value) => value,
+        }
+    }
+}
+
+impl From<Value> for JsonItem<'_> {
+    fn from(value: Value) -> Self {
+        JsonItem::Owned(value)
+    }
+}
+
+impl fmt::Display for JsonItem<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_ref().fmt(f)
+    }
+}
+
+impl SqlJsonPath {
+    /// Compiles a path expression.
+    ///
+    /// Returns [`Error::Parse`] if the expression is invalid.
+    pub fn new(path: &str) -> Result<Self, Error> {
+        Ok(SqlJsonPath {
+            text: path.into(),
+            plan: parser::parse(path)?,
+        })
+    }
+
+    /// Returns true if the path is in strict mode.
+    pub fn is_strict(&self) -> bool {
+        self.plan.strict
+    }
+
+    /// Returns all items returned by the path for the value, as `jsonb_path_query`.
+    ///
+    /// `vars` must be an object whose members are the values of the variables.
+    ///
+    /// Returns [`Error::JsonPath`] if the evaluation fails, e.g. on a missing key in strict mode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::jsonpath::SqlJsonPath;
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": {"b": 1, "c": "x"}}"#.parse().unwrap();
+    /// let path = SqlJsonPath::new(r#"$.a.keyvalue() ? (@.value.type() == "number").key"#).unwrap();
+    /// let items = path.query(value.as_ref(), None).unwrap();
+    /// assert_eq!(items[0].to_string(), r#""b""#);
+    /// ```
+    pub fn query<'a>(
+        &self,
+        value: ValueRef<'a>,
+        vars: Option<ValueRef<'a>>,
+    ) -> Result<Vec<JsonItem<'a>>, Error> {
+        if vars.is_some_and(|vars| !vars.is_object()) {
+            return Err(Error::JsonPath {
+                message: "\"vars\" argument is not an object".into(),
+            });
+        }
+        for name in &self.plan.variables {
+            if !vars.is_some_and(|vars| vars.as_object().unwrap().contains_key(name)) {
+                return Err(Error::JsonPath {
+                    message: format!("could not find jsonpath variable \"{name}\""),
+                });
+            }
+        }
+        let evaluator = Evaluator {
+            root: value,
+            vars,
+            strict: self.plan.strict,
+        };
+        let root = JsonItem::from_ref(value);
+        if self.plan.expr.is_predicate() {
+            let truth = evaluator.predicate(&self.plan.expr, &root, None);
+            return Ok(vec![truth.to_item()]);
+        }
+        evaluator.eval(&self.plan.expr, &root, None)
+    }
+
+    /// Returns the first item returned by the path for the value, as `jsonb_path_query_first`.
+    pub fn query_first<'a>(
+        &self,
+        value: ValueRef<'a>,
+        vars: Option<ValueRef<'a>>,
+    ) -> Result<Option<JsonItem<'a>>, Error> {
+        Ok(self.query(value, vars)?.into_iter().next())
+    }
+
+    /// Returns true if the path returns any item for the value, as `jsonb_path_exists`.
+    ///
+    /// The operator `@?` is the same with errors turned into SQL null.
+    pub fn exists(&self, value: ValueRef<'_>, vars: Option<ValueRef<'_>>) -> Result<bool, Error> {
+        Ok(!self.query(value, vars)?.is_empty())
+    }
+
+    /// Returns the result of a predicate path for the value, as `jsonb_path_match`.
+    ///
+    /// Returns `None` if the result is unknown. The operator `@@` is the same with errors turned
+    /// into SQL null.
+    ///
+    /// Returns [`Error::JsonPath`] if the path does not return a single boolean or null.
+    pub fn matches(
+        &self,
+        value: ValueRef<'_>,
+        vars: Option<ValueRef<'_>>,
+    ) -> Result<Option<bool>, Error> {
+        let items = self.query(value, vars)?;
+        match items.as_slice() {
+            [item] if item.as_ref().is_boolean() => Ok(item.as_ref().as_bool()),
+            [item] if item.as_ref().is_null() => Ok(None),
+            _ => Err(Error::JsonPath {
+                message: "single boolean result is expected".into(),
+            }),
+        }
+    }
+}
+
+impl FromStr for SqlJsonPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for SqlJsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the items as a JSON array, or the error message.
+    fn query_with(json: &str, path: &str, vars: &str) -> Result<Value, String> {
+        let value: Value = json.parse().unwrap();
+        let vars: Value = vars.parse().unwrap();
+        let path = SqlJsonPath::new(path).map_err(|e| e.to_string())?;
+        let items = path
+            .query(value.as_ref(), Some(vars.as_ref()))
+            .map_err(|e| e.to_string())?;
+        Ok(Value::array(items.iter().map(JsonItem::as_ref)))
+    }
+
+    #[track_caller]
+    fn check(json: &str, path: &str, expected: &str) {
+        check_with(json, path, "{}", expected);
+    }
+
+    #[track_caller]
+    fn check_with(json: &str, path: &str, vars: &str, expected: &str) {
+        let actual = query_with(json, path, vars).unwrap_or_else(|e| panic!("{path}: {e}"));
+        assert_eq!(actual, expected.parse().unwrap(), "{path}: {actual}");
+    }
+
+    #[track_caller]
+    fn check_error(json: &str, path: &str, message: &str) {
+        match query_with(json, path, "{}") {
+            Ok(v) => panic!("{path}: expected error, got {v}"),
+            Err(e) => assert!(e.contains(message), "{path}: {e}"),
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        check(r#"{"a": 12}"#, "$.a", "[12]");
+        check(r#"{"a": 12}"#, "$.b", "[]");
+        check_error(
+            r#"{"a": 12}"#,
+            "strict $.b",
+            r#"JSON object does not contain key "b""#,
+        );
+        check(r#"{"a": {"a b": 12}}"#, r#"$.a."a b""#, "[12]");
+        check(r#"{"a": {"b": 1}, "c": {"b": 2}}"#, "$.*.b", "[1, 2]");
+        check(r#"[{"a": 1}, {"a": 2}, 3]"#, "$.a", "[1, 2]");
+        check(r#"[{"a": 1}, {"a": 2}, 3]"#, "lax $.a", "[1, 2]");
+        check_error(
+            r#"[{"a": 1}]"#,
+            "strict $.a",
+            "can only be applied to an object",
+        );
+        check_error("1", "strict $.*", "can only be applied to an object");
+        check("[1, 2, 3]", "$[*]", "[1, 2, 3]");
+        check("1", "$[*]", "[1]");
+        check_error("1", "strict $[*]", "can only be applied to an array");
+        check("[1, [2, 3]]", "$[*][*]", "[1, 2, 3]");
+    }
+
+    #[test]
+    fn subscripts() {
+        check("[0, 1, 2, 3, 4]", "$[1]", "[1]");
+        check("[0, 1, 2, 3, 4]", "$[last]", "[4]");
+        check("[0, 1, 2, 3, 4]", "$[last - 1]", "[3]");
+        check("[0, 1, 2, 3, 4]", "$[1 to last]", "[1, 2, 3, 4]");
+        check("[0, 1, 2, 3, 4]", "$[0, 2 to 3, last]", "[0, 2, 3, 4]");
+        check("[0, 1, 2, 3, 4]", "$[1.9]", "[1]");
+        check("[0, 1, 2, 3, 4]", "$[3 to 10]", "[3, 4]");
+        check("[0, 1, 2, 3, 4]", "$[-1]", "[]");
+        check("[0, 1, 2, 3, 4]", "$[3 to 1]", "[]");
+        check("[0, 1, 2, 3, 4]", "$[$.size() - 1]", "[4]");
+        check_with("[0, 1, 2, 3, 4]", "$[$i]", r#"{"i": 2}"#, "[2]");
+        check_error("[0, 1, 2]", "strict $[3]", "out of bounds");
+        check_error("[0, 1, 2]", "strict $[1 to 3]", "out of bounds");
+        check_error("[0, 1, 2]", r#"$["a"]"#, "not a single numeric value");
+        check("1", "$[0]", "[1]");
+        check("1", "$[last]", "[1]");
+        check("1", "$[1]", "[]");
+        check_error("1", "strict $[0]", "can only be applied to an array");
+        check("[]", "$[last]", "[]");
+    }
+
+    #[test]
+    fn descendants() {
+        let json = r#"{"a": [1, {"b": 2}], "c": 3}"#;
+        check(
+            json,
+            "$.**",
+            r#"[{"a": [1, {"b": 2}], "c": 3}, [1, {"b": 2}], 1, {"b": 2}, 2, 3]"#,
+        );
+        check(json, "$.**{1}", r#"[[1, {"b": 2}], 3]"#);
+        check(json, "$.**{2 to last}", r#"[1, {"b": 2}, 2]"#);
+        check(json, "$.**{3 to 10}", "[2]");
+        // arrays are unwrapped in lax mode, so the value is found twice
+        check(json, "$.**.b", "[2, 2]");
+        check(json, "strict $.** ? (@.b > 0)", r#"[{"b": 2}]"#);
+    }
+
+    #[test]
+    fn filters() {
+        check("[1, 2, 3, 4]", "$[*] ? (@ > 2)", "[3, 4]");
+        check("[1, 2, 3, 4]", "$ ? (@ > 2)", "[3, 4]");
+        check("[1, 2, 3, 4]", "strict $ ? (@ > 2)", "[]");
+        check(r#"[1, "a", null, 2]"#, "$[*] ? (@ > 1)", "[2]");
+        check("[1, null]", "$[*] ? (@ != null)", "[1]");
+        check("[1, null]", "$[*] ? (@ == null)", "[null]");
+        check(
+            r#"["a", "b", true, false]"#,
+            r#"$[*] ? (@ >= "b" || @ > false)"#,
+            r#"["b", true]"#,
+        );
+        check("[1, 2, 3]", "$[*] ? (@ == 1 || @ == 3)", "[1, 3]");
+        check("[1, 2, 3]", "$[*] ? (@ > 1 && @ < 3)", "[2]");
+        check("[1, 2, 3]", "$[*] ? (!(@ > 1))", "[1]");
+        check(r#"[1, "a"]"#, "$[*] ? ((@ > 0) is unknown)", r#"["a"]"#);
+        check(
+            r#"[{"a": 1}, {"b": 2}]"#,
+            "$[*] ? (exists (@.b))",
+            r#"[{"b": 2}]"#,
+        );
+        check(
+            r#"[{"a": 1}, {"b": 2}]"#,
+            "$[*] ? (!exists (@.b))",
+            r#"[{"a": 1}]"#,
+        );
+        // errors in predicates are unknown, even in strict mode
+        check(
+            r#"[{"a": 1}, {"b": 2}]"#,
+            "strict $[*] ? (@.a == 1)",
+            r#"[{"a": 1}]"#,
+        );
+        check("[1, 0, 2]", "$[*] ? (1 / @ > 0)", "[1, 2]");
+        // lax mode finds any true pair, while strict mode stops at any unknown pair
+        check(
+            r#"{"a": [1, "x"]}"#,
+            "$ ? (@.a[*] > 0)",
+            r#"[{"a": [1, "x"]}]"#,
+        );
+        check(r#"{"a": [1, "x"]}"#, "strict $ ? (@.a[*] > 0)", "[]");
+        check(
+            r#"{"a": [{"b": 1}, {"b": 5}]}"#,
+            "$.a ? (@.b > 2 && @.b < $.a.size() * 3).b",
+            "[5]",
+        );
+        // arrays are unwrapped by filters in lax mode
+        check("[[1, 2], [3]]", "$[*] ? (@[*] == 3)", "[3]");
+        check("[[1, 2], [3]]", "strict $[*] ? (@[*] == 3)", "[[3]]");
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn like_regex() {
+        let json = r#"["abc", "ABD", "a.c", "x", 1]"#;
+        check(json, r#"$[*] ? (@ like_regex "^ab")"#, r#"["abc"]"#);
+        check(
+            json,
+            r#"$[*] ? (@ like_regex "^ab" flag "i")"#,
+            r#"["abc", "ABD"]"#,
+        );
+        check(
+            json,
+            r#"$[*] ? (@ like_regex "a.c" flag "q")"#,
+            r#"["a.c"]"#,
+        );
+        check(
+            json,
+            r#"$[*] ? (@ like_regex "a . c" flag "x")"#,
+            r#"["abc", "a.c"]"#,
+        );
+        check(json, r#"$[*] ? ((@ like_regex "c$") is unknown)"#, "[1]");
+        check(
+            r#""line\nbreak""#,
+            r#"$ ? (@ like_regex "^break$" flag "m")"#,
+            r#"["line\nbreak"]"#,
+        );
+        for (path, message) in [
+            (
+                "$ like_regex 1",
+                "expected a string after `like_regex` at line 1 column 14",
+            ),
+            (
+                r#"$ like_regex "a" flag "z""#,
+                "unrecognized flag character `z` in like_regex",
+            ),
+            (r#"$ like_regex "(""#, "invalid regular expression"),
+        ] {
+            let err = SqlJsonPath::new(path).unwrap_err();
+            assert!(err.to_string().contains(message), "{path}: {err}");
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "regex"))]
+    fn like_regex_disabled() {
+        let err = SqlJsonPath::new(r#"$ like_regex "a""#).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`like_regex` requires the `regex` feature"));
+    }
+
+    #[test]
+    fn strings() {
+        let json = r#"["abc", "ABD", "a.c", "x", 1]"#;
+        check(json, r#"$[*] ? (@ starts with "a")"#, r#"["abc", "a.c"]"#);
+        check_with(
+            json,
+            "$[*] ? (@ starts with $p)",
+            r#"{"p": "AB"}"#,
+            r#"["ABD"]"#,
+        );
+        check(r#"{"é\n": 1}"#, r#"$."é\n""#, "[1]");
+        check(r#"{"😀": 1}"#, r#"$."😀""#, "[1]");
+        check("null", r#""\x41é\t""#, r#"["Aé\t"]"#);
+    }
+
+    #[test]
+    fn arithmetic() {
+        check(r#"{"a": 12}"#, "$.a + 2", "[14]");
+        check(r#"{"a": 12}"#, "$.a - 2 * 3", "[6]");
+        check(r#"{"a": 12}"#, "($.a - 2) * 3", "[30]");
+        check(r#"{"a": 12}"#, "$.a / 5", "[2.4]");
+        check(r#"{"a": 12}"#, "$.a / 4", "[3]");
+        check(r#"{"a": 12}"#, "$.a % 5", "[2]");
+        check(r#"{"a": 12}"#, "-$.a", "[-12]");
+        check(r#"{"a": 1.5}"#, "$.a * 2", "[3]");
+        check("[1, 2]", "-$", "[-1, -2]");
+        check("[1, 2]", "+$", "[1, 2]");
+        check("[3]", "$ + 1", "[4]");
+        check("null", "1 + 2", "[3]");
+        check("null", "9223372036854775807 + 1", "[9223372036854775808.0]");
+        check_error(
+            "[1, 2]",
+            "$ + 1",
+            "left operand of jsonpath operator + is not a single numeric value",
+        );
+        check_error(
+            "1",
+            r#"$ * "a""#,
+            "right operand of jsonpath operator * is not a single numeric value",
+        );
+        check_error("1", "$ / 0", "division by zero");
+        check_error("1", "$ % 0.0", "division by zero");
+        check_error(
+            r#""a""#,
+            "-$",
+            "operand of unary jsonpath operator - is not a numeric value",
+        );
+        check_error("1e300", "$ * $", "numeric value out of range");
+    }
+
+    #[test]
+    fn methods() {
+        check(
+            r#"[null, true, 1, "s", [], {}]"#,
+            "$[*].type()",
+            r#"["null", "boolean", "number", "string", "array", "object"]"#,
+        );
+        check("[1, 2]", "$.type()", r#"["array"]"#);
+        check("[1, [1, 2], {}]", "$[*].size()", "[1, 2, 1]");
+        check_error(
+            "[1, [1, 2]]",
+            "strict $[*].size()",
+            ".size() can only be applied to an array",
+        );
+        check(r#"[1, "2.5", " 3 "]"#, "$.double()", "[1, 2.5, 3]");
+        check_error(
+            "true",
+            "$.double()",
+            ".double() can only be applied to a string or numeric value",
+        );
+        check_error(r#""x""#, "$.double()", "invalid for type double precision");
+        check_error(r#""NaN""#, "$.double()", "NaN or Infinity is not allowed");
+        check("[1.3, -1.3, 2]", "$.ceiling()", "[2, -1, 2]");
+        check("[1.7, -1.3, 2]", "$.floor()", "[1, -2, 2]");
+        check("[1.5, -1.5, -3]", "$.abs()", "[1.5, 1.5, 3]");
+        check_error(
+            r#""x""#,
+            "$.abs()",
+            ".abs() can only be applied to a numeric value",
+        );
+        check(
+            r#"{"a": 1, "b": [1, 2]}"#,
+            "$.keyvalue()",
+            r#"[{"id": 0, "key": "a", "value": 1}, {"id": 0, "key": "b", "value": [1, 2]}]"#,
+        );
+        check(
+            r#"[{"a": 1}, {"b": 2}]"#,
+            "$.keyvalue().key",
+            r#"["a", "b"]"#,
+        );
+        check_error(
+            "[1]",
+            "strict $.keyvalue()",
+            ".keyvalue() can only be applied to an object",
+        );
+        // identifiers are unique per object
+        let value: Value = r#"{"a": {"x": 1}, "b": {"y": 2}}"#.parse().unwrap();
+        let path = SqlJsonPath::new("$.*.keyvalue().id").unwrap();
+        let ids = path.query(value.as_ref(), None).unwrap();
+        assert_ne!(ids[0], ids[1]);
+        assert!(ids.iter().all(|id| id.as_ref().as_i64().unwrap() > 0));
+
+        check(r#"["123", 1.6, -2]"#, "$.bigint()", "[123, 2, -2]");
+        check(r#"["123", 1.4]"#, "$.integer()", "[123, 1]");
+        check_error("1e10", "$.integer()", "invalid for type integer");
+        check_error(r#""1.5""#, "$.bigint()", "invalid for type bigint");
+        check(r#"[1.5, "-2e1"]"#, "$.number()", "[1.5, -20]");
+        check_error(r#""x""#, "$.number()", "invalid for type numeric");
+        check(
+            r#"["yes", "Off", 0, 5, true]"#,
+            "$.boolean()",
+            "[true, false, false, true, true]",
+        );
+        check_error("1.5", "$.boolean()", "invalid for type boolean");
+        check(
+            r#"[1.5, true, "s"]"#,
+            "$.string()",
+            r#"["1.5", "true", "s"]"#,
+        );
+        check_error("null", "$.string()", ".string() can only be applied to");
+
+        // numbers are printed as PostgreSQL does, except that the scale of the input is not kept
+        let text = |json: &str, path: &str| query_with(json, path, "{}").unwrap().to_string();
+        assert_eq!(
+            text(r#"["1e2", "-0", "1.5e1"]"#, "$.number()"),
+            "[100,0,15]"
+        );
+        assert_eq!(
+            text("[1e2, 1e21, 1e-7]", "$.string()"),
+            r#"["100","1000000000000000000000","0.0000001"]"#
+        );
+        // PostgreSQL gives 1.50 and "1.50"
+        assert_eq!(text(r#"["1.50"]"#, "$.number()"), "[1.5]");
+        assert_eq!(text("[1.50]", "$.string()"), r#"["1.5"]"#);
+        check(r#"{"a": "1.5"}"#, "$.a.double() * 2 + 1", "[4]");
+    }
+
+    #[test]
+    fn variables() {
+        let vars = r#"{"min": 2, "max": 3, "x y": "s"}"#;
+        check_with(
+            "[1, 2, 3, 4]",
+            "$[*] ? (@ >= $min && @ <= $max)",
+            vars,
+            "[2, 3]",
+        );
+        check_with("[1]", r#"$"x y""#, vars, r#"["s"]"#);
+        check_with("[1]", "$max.type()", vars, r#"["number"]"#);
+
+        let value = Value::from(1);
+        let path = SqlJsonPath::new("$ ? (@ > $min)").unwrap();
+        let err = path.query(value.as_ref(), None).unwrap_err();
+        assert_eq!(err.to_string(), r#"could not find jsonpath variable "min""#);
+        let err = path
+            .query(value.as_ref(), Some(value.as_ref()))
+            .unwrap_err();
+        assert_eq!(err.to_string(), r#""vars" argument is not an object"#);
+        // variables are checked even if they are not evaluated
+        let path = SqlJsonPath::new("$.missing ? (@ > $min)").unwrap();
+        assert!(path.query(value.as_ref(), None).is_err());
+    }
+
+    #[test]
+    fn predicates() {
+        check(r#"{"a": 12}"#, "$.a > 10", "[true]");
+        check(r#"{"a": 12}"#, "$.a < 10", "[false]");
+        check(r#"{"a": "x"}"#, "$.a > 10", "[null]");
+        check(r#"{"a": 12}"#, "exists($.a)", "[true]");
+        check(
+            r#"{"a": [1, 2]}"#,
+            "$.a[*] == 2 && $.a.size() == 2",
+            "[true]",
+        );
+
+        let value: Value = r#"{"a": 1, "b": "x"}"#.parse().unwrap();
+        let matches = |path: &str| {
+            SqlJsonPath::new(path)
+                .unwrap()
+                .matches(value.as_ref(), None)
+        };
+        assert_eq!(matches("$.a == 1").unwrap(), Some(true));
+        assert_eq!(matches("$.a != 1").unwrap(), Some(false));
+        assert_eq!(matches("$.b > 1").unwrap(), None);
+        assert_eq!(matches("$.c == 1").unwrap(), Some(false));
+        assert_eq!(matches("($.b > 1) is unknown").unwrap(), Some(true));
+        let err = matches("$.a").unwrap_err();
+        assert_eq!(err.to_string(), "single boolean result is expected");
+        assert!(matches("strict $.c == 1").unwrap().is_none());
+
+        let exists = |path: &str| SqlJsonPath::new(path).unwrap().exists(value.as_ref(), None);
+        assert!(exists("$.a").unwrap());
+        assert!(!exists("$.c").unwrap());
+        assert!(exists("strict $.c").is_err());
+        assert!(!exists("$.a ? (@ > 1)").unwrap());
+        let path = SqlJsonPath::new("$.*").unwrap();
+        let first = path.query_first(value.as_ref(), None).unwrap().unwrap();
+        assert_eq!(first.into_owned(), Value::from(1));
+    }
+
+    #[test]
+    fn raw_json() {
+        let mut builder = crate::Builder::<Vec<u8>>::new();
+        builder.begin_object();
+        builder.add_string("r");
+        builder.add_raw_json(r#"{"x": [1, {"y": 2}]}"#).unwrap();
+        builder.end_object();
+        let value = builder.finish();
+        let path = SqlJsonPath::new("$.r.x[1].y").unwrap();
+        let items = path.query(value.as_ref(), None).unwrap();
+        // borrowed from the value of the raw JSON
+        assert!(matches!(&items[..], [JsonItem::Borrowed(v)] if v.as_u64() == Some(2)));
+    }
+
+    #[test]
+    fn syntax() {
+        let path = SqlJsonPath::new("strict $.a").unwrap();
+        assert!(path.is_strict());
+        assert_eq!(path.to_string(), "strict $.a");
+        assert!(!"$.a".parse::<SqlJsonPath>().unwrap().is_strict());
+        for (path, message) in [
+            ("", "unexpected end of input at line 1 column 1"),
+            ("$.", "unexpected end of input at line 1 column 3"),
+            ("$[", "unexpected end of input at line 1 column 3"),
+            ("$[1", "expected `]`, found end of input at line 1 column 4"),
+            (
+                "@",
+                "`@` is not allowed outside of filters at line 1 column 1",
+            ),
+            (
+                "last",
+                "`last` is allowed only in array subscripts at line 1 column 1",
+            ),
+            ("$ ? (@ + 1)", "expected a predicate at line 1 column 11"),
+            ("$ ? (1)", "expected a predicate at line 1 column 7"),
+            ("$ + (1 > 2)", "unexpected predicate at line 1 column 12"),
+            ("(1 > 2).type()", "unexpected predicate at line 1 column 15"),
+            ("1 > 2 > 3", "unexpected `>` at line 1 column 7"),
+            ("$.foo()", "unknown item method `.foo()` at line 1 column 3"),
+            ("$.a(", "unknown item method `.a()` at line 1 column 3"),
+            (
+                "$.datetime()",
+                "unknown item method `.datetime()` at line 1 column 3",
+            ),
+            (
+                "$.**{-1}",
+                "expected a non-negative integer or `last` at line 1 column 6",
+            ),
+            ("!$", "unexpected `$` at line 1 column 2"),
+            ("$ &&", "expected a predicate at line 1 column 5"),
+            (
+                "$ starts with 1",
+                "expected a string or variable after `starts with`",
+            ),
+            ("$ ? (@ > 1) is unknown", "unexpected `is`"),
+            (r#""abc"#, "unterminated string literal at line 1 column 5"),
+            (r#""\q""#, "invalid escape at line 1 column 2"),
+            (r#""\ud800""#, "invalid Unicode surrogate pair"),
+            (
+                "1a",
+                "trailing junk after numeric literal at line 1 column 2",
+            ),
+            ("01", "invalid numeric literal at line 1 column 1"),
+            ("$ # 1", "unexpected character `#` at line 1 column 3"),
+            ("strict", "unexpected end of input"),
+        ] {
+            let err = SqlJsonPath::new(path).unwrap_err();
+            assert!(matches!(err, Error::Parse { .. }));
+            assert!(err.to_string().contains(message), "{path}: {err}");
+        }
+    }
+}
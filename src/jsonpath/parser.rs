@@ -0,0 +1,819 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of SQL/JSON path expressions into a plan.
+
+use std::fmt;
+
+#[cfg(feature = "regex")]
+use regex::{Regex, RegexBuilder};
+
+use crate::{Error, Value};
+
+/// A compiled path expression.
+#[derive(Debug, Clone)]
+pub(super) struct Plan {
+    pub strict: bool,
+    pub expr: Expr,
+    /// The names of all variables referenced by the expression.
+    pub variables: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    /// `$`
+    Root,
+    /// `@`
+    Current,
+    /// `last`
+    Last,
+    /// `$name`
+    Variable(String),
+    Literal(Value),
+    /// An expression followed by accessors and item methods.
+    Accessors(Box<Expr>, Vec<Accessor>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    // predicates
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    IsUnknown(Box<Expr>),
+    Exists(Box<Expr>),
+    #[cfg(feature = "regex")]
+    LikeRegex(Box<Expr>, Box<Regex>),
+    StartsWith(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Returns true if the expression is a predicate, which evaluates to true, false or unknown.
+    pub fn is_predicate(&self) -> bool {
+        matches!(
+            self,
+            Expr::Compare(..)
+                | Expr::And(..)
+                | Expr::Or(..)
+                | Expr::Not(..)
+                | Expr::IsUnknown(..)
+                | Expr::Exists(..)
+                | Expr::StartsWith(..)
+        ) || self.is_like_regex()
+    }
+
+    #[cfg(feature = "regex")]
+    fn is_like_regex(&self) -> bool {
+        matches!(self, Expr::LikeRegex(..))
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn is_like_regex(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Accessor {
+    /// `.key` or `."key"`
+    Member(String),
+    /// `.*`
+    MemberWildcard,
+    /// `[*]`
+    ElementWildcard,
+    /// `[index, from to to, ...]`
+    Elements(Vec<(Expr, Option<Expr>)>),
+    /// `.**{min to max}`, where `last` is `u32::MAX`.
+    Descendants {
+        min: u32,
+        max: u32,
+    },
+    /// `? (predicate)`
+    Filter(Expr),
+    Method(Method),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Method {
+    Type,
+    Size,
+    Double,
+    Ceiling,
+    Floor,
+    Abs,
+    KeyValue,
+    BigInt,
+    Integer,
+    Number,
+    Boolean,
+    String,
+}
+
+impl Method {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "type" => Method::Type,
+            "size" => Method::Size,
+            "double" => Method::Double,
+            "ceiling" => Method::Ceiling,
+            "floor" => Method::Floor,
+            "abs" => Method::Abs,
+            "keyvalue" => Method::KeyValue,
+            "bigint" => Method::BigInt,
+            "integer" => Method::Integer,
+            "number" => Method::Number,
+            "boolean" => Method::Boolean,
+            "string" => Method::String,
+            _ => return None,
+        })
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Method::Type => "type",
+            Method::Size => "size",
+            Method::Double => "double",
+            Method::Ceiling => "ceiling",
+            Method::Floor => "floor",
+            Method::Abs => "abs",
+            Method::KeyValue => "keyvalue",
+            Method::BigInt => "bigint",
+            Method::Integer => "integer",
+            Method::Number => "number",
+            Method::Boolean => "boolean",
+            Method::String => "string",
+        };
+        write!(f, ".{name}()")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum UnaryOp {
+    Plus,
+    Minus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Punct(&'static str),
+    /// An identifier or keyword.
+    Ident(String),
+    String(String),
+    Number(Value),
+    Variable(String),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Punct(p) => write!(f, "`{p}`"),
+            Token::Ident(s) => write!(f, "`{s}`"),
+            Token::String(s) => write!(f, "string {s:?}"),
+            Token::Number(n) => write!(f, "number {n}"),
+            Token::Variable(s) => write!(f, "variable `${s}`"),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Punctuations, with the longer ones first.
+const PUNCTS: &[&str] = &[
+    "**", "==", "!=", "<>", "<=", ">=", "&&", "||", "$", "@", ".", "[", "]", "(", ")", "{", "}",
+    ",", "?", "*", "+", "-", "/", "%", "<", ">", "!",
+];
+
+/// Parses a path expression.
+pub(super) fn parse(text: &str) -> Result<Plan, Error> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        text,
+        tokens,
+        index: 0,
+        filter_depth: 0,
+        subscript_depth: 0,
+        variables: vec![],
+    };
+    let strict = parser.eat_keyword("strict");
+    if !strict {
+        parser.eat_keyword("lax");
+    }
+    let expr = parser.parse_or()?;
+    if *parser.peek() != Token::Eof {
+        return Err(parser.unexpected());
+    }
+    Ok(Plan {
+        strict,
+        expr,
+        variables: parser.variables,
+    })
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, Error> {
+    let bytes = text.as_bytes();
+    let error = |message: &str, pos| Error::parse(message, bytes, pos);
+    let is_ident_start = |c: u8| c.is_ascii_alphabetic() || c == b'_' || c >= 0x80;
+    let is_ident = |c: u8| c.is_ascii_alphanumeric() || c == b'_' || c >= 0x80;
+    let mut tokens = vec![];
+    let mut pos = 0;
+    loop {
+        while bytes.get(pos).is_some_and(u8::is_ascii_whitespace) {
+            pos += 1;
+        }
+        let start = pos;
+        let Some(&c) = bytes.get(pos) else {
+            tokens.push((Token::Eof, pos));
+            return Ok(tokens);
+        };
+        let token = match c {
+            b'"' => {
+                let (s, len) = unescape(&text[pos..]).map_err(|(m, i)| error(m, pos + i))?;
+                pos += len;
+                Token::String(s)
+            }
+            b'$' if bytes.get(pos + 1) == Some(&b'"') => {
+                let (s, len) =
+                    unescape(&text[pos + 1..]).map_err(|(m, i)| error(m, pos + 1 + i))?;
+                pos += 1 + len;
+                Token::Variable(s)
+            }
+            b'$' if bytes.get(pos + 1).is_some_and(|&c| is_ident(c)) => {
+                let len = bytes[pos + 1..]
+                    .iter()
+                    .take_while(|&&c| is_ident(c))
+                    .count();
+                pos += 1 + len;
+                Token::Variable(text[start + 1..pos].into())
+            }
+            b'0'..=b'9' => {
+                pos += bytes[pos..]
+                    .iter()
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+                // a dot is part of the number only if followed by a digit, e.g. `$[1.5]` but `1.type()`
+                if bytes.get(pos) == Some(&b'.')
+                    && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit)
+                {
+                    pos += 1;
+                    pos += bytes[pos..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count();
+                }
+                if matches!(bytes.get(pos), Some(b'e' | b'E')) {
+                    pos += 1;
+                    if matches!(bytes.get(pos), Some(b'+' | b'-')) {
+                        pos += 1;
+                    }
+                    pos += bytes[pos..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count();
+                }
+                if bytes.get(pos).is_some_and(|&c| is_ident(c)) {
+                    return Err(error("trailing junk after numeric literal", pos));
+                }
+                match Value::from_text(&bytes[start..pos]) {
+                    Ok(number) => Token::Number(number),
+                    Err(_) => return Err(error("invalid numeric literal", start)),
+                }
+            }
+            c if is_ident_start(c) => {
+                pos += bytes[pos..].iter().take_while(|&&c| is_ident(c)).count();
+                Token::Ident(text[start..pos].into())
+            }
+            _ => match PUNCTS
+                .iter()
+                .find(|p| bytes[pos..].starts_with(p.as_bytes()))
+            {
+                Some(p) => {
+                    pos += p.len();
+                    Token::Punct(p)
+                }
+                None => {
+                    let c = text[pos..].chars().next().unwrap();
+                    return Err(error(&format!("unexpected character `{c}`"), pos));
+                }
+            },
+        };
+        tokens.push((token, start));
+    }
+}
+
+/// Parses a double-quoted string literal at the start of the text.
+///
+/// Returns the string and the length of the literal, or the error message and its position.
+fn unescape(text: &str) -> Result<(String, usize), (&'static str, usize)> {
+    let mut s = String::new();
+    let mut chars = text.char_indices().skip(1);
+    let hex = |chars: &mut std::iter::Skip<std::str::CharIndices>, n: usize, i: usize| {
+        let mut code = 0;
+        for _ in 0..n {
+            let digit = chars.next().and_then(|(_, c)| c.to_digit(16));
+            code = code * 16 + digit.ok_or(("invalid hexadecimal escape", i))?;
+        }
+        Ok(code)
+    };
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((s, i + 1)),
+            '\\' => {
+                let Some((_, c)) = chars.next() else { break };
+                match c {
+                    '"' | '\\' | '/' => s.push(c),
+                    'b' => s.push('\x08'),
+                    'f' => s.push('\x0c'),
+                    'n' => s.push('\n'),
+                    'r' => s.push('\r'),
+                    't' => s.push('\t'),
+                    'v' => s.push('\x0b'),
+                    'x' => {
+                        let code = hex(&mut chars, 2, i)?;
+                        s.push(char::from_u32(code).ok_or(("invalid escape", i))?);
+                    }
+                    'u' => {
+                        let mut code = hex(&mut chars, 4, i)?;
+                        if (0xD800..0xDC00).contains(&code) {
+                            // a surrogate pair
+                            let low = match (chars.next(), chars.next()) {
+                                (Some((_, '\\')), Some((_, 'u'))) => hex(&mut chars, 4, i)?,
+                                _ => return Err(("invalid Unicode surrogate pair", i)),
+                            };
+                            if !(0xDC00..0xE000).contains(&low) {
+                                return Err(("invalid Unicode surrogate pair", i));
+                            }
+                            code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                        }
+                        s.push(char::from_u32(code).ok_or(("invalid Unicode escape", i))?);
+                    }
+                    _ => return Err(("invalid escape", i)),
+                }
+            }
+            _ => s.push(c),
+        }
+    }
+    Err(("unterminated string literal", text.len()))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+    filter_depth: usize,
+    subscript_depth: usize,
+    variables: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&self, n: usize) -> &Token {
+        &self.tokens[(self.index + n).min(self.tokens.len() - 1)].0
+    }
+
+    /// Returns the current token and advances, also past the end of input.
+    fn next(&mut self) -> Token {
+        let token = self.peek().clone();
+        self.index += 1;
+        token
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = matches!(self.peek(), Token::Punct(p) if *p == punct);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = matches!(self.peek(), Token::Ident(s) if s == keyword);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), Error> {
+        if self.eat_punct(punct) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{punct}`, found {}", self.peek())))
+        }
+    }
+
+    /// Returns an error at the current token.
+    #[cold]
+    fn error(&self, message: String) -> Error {
+        Error::parse(
+            message,
+            self.text.as_bytes(),
+            self.tokens[self.index.min(self.tokens.len() - 1)].1,
+        )
+    }
+
+    #[cold]
+    fn unexpected(&self) -> Error {
+        self.error(format!("unexpected {}", self.peek()))
+    }
+
+    /// Returns an error if the expression is not a predicate, or is one if `predicate` is false.
+    fn check(&self, expr: &Expr, predicate: bool) -> Result<(), Error> {
+        match (expr.is_predicate(), predicate) {
+            (false, true) => Err(self.error("expected a predicate".into())),
+            (true, false) => Err(self.error("unexpected predicate".into())),
+            _ => Ok(()),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_and()?;
+        while self.eat_punct("||") {
+            self.check(&left, true)?;
+            let right = self.parse_and()?;
+            self.check(&right, true)?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_not()?;
+        while self.eat_punct("&&") {
+            self.check(&left, true)?;
+            let right = self.parse_not()?;
+            self.check(&right, true)?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, Error> {
+        if !self.eat_punct("!") {
+            return self.parse_comparison();
+        }
+        // the operand must be a parenthesized predicate or `exists`
+        if !matches!(self.peek(), Token::Punct("("))
+            && !matches!(self.peek(), Token::Ident(s) if s == "exists")
+        {
+            return Err(self.unexpected());
+        }
+        let expr = self.parse_primary()?;
+        self.check(&expr, true)?;
+        Ok(Expr::Not(Box::new(expr)))
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, Error> {
+        let left = self.parse_additive()?;
+        let op = match self.peek() {
+            Token::Punct("==") => CompareOp::Eq,
+            Token::Punct("!=" | "<>") => CompareOp::Ne,
+            Token::Punct("<") => CompareOp::Lt,
+            Token::Punct("<=") => CompareOp::Le,
+            Token::Punct(">") => CompareOp::Gt,
+            Token::Punct(">=") => CompareOp::Ge,
+            Token::Ident(s) if s == "like_regex" => {
+                self.check(&left, false)?;
+                self.index += 1;
+                let Token::String(pattern) = self.next() else {
+                    self.index -= 1;
+                    return Err(self.error("expected a string after `like_regex`".into()));
+                };
+                let mut flags = String::new();
+                if self.eat_keyword("flag") {
+                    let Token::String(f) = self.next() else {
+                        self.index -= 1;
+                        return Err(self.error("expected a string after `flag`".into()));
+                    };
+                    flags = f;
+                }
+                #[cfg(feature = "regex")]
+                {
+                    let regex = self.compile_regex(&pattern, &flags)?;
+                    return Ok(Expr::LikeRegex(Box::new(left), Box::new(regex)));
+                }
+                #[cfg(not(feature = "regex"))]
+                {
+                    let _ = (pattern, flags);
+                    return Err(self.error("`like_regex` requires the `regex` feature".into()));
+                }
+            }
+            Token::Ident(s)
+                if s == "starts" && matches!(self.peek_nth(1), Token::Ident(s) if s == "with") =>
+            {
+                self.check(&left, false)?;
+                self.index += 2;
+                let prefix = match self.next() {
+                    Token::String(s) => Expr::Literal(Value::from(s.as_str())),
+                    Token::Variable(name) => {
+                        self.variables.push(name.clone());
+                        Expr::Variable(name)
+                    }
+                    _ => {
+                        self.index -= 1;
+                        return Err(
+                            self.error("expected a string or variable after `starts with`".into())
+                        );
+                    }
+                };
+                return Ok(Expr::StartsWith(Box::new(left), Box::new(prefix)));
+            }
+            _ => return Ok(left),
+        };
+        self.check(&left, false)?;
+        self.index += 1;
+        let right = self.parse_additive()?;
+        self.check(&right, false)?;
+        Ok(Expr::Compare(op, Box::new(left), Box::new(right)))
+    }
+
+    /// Compiles a `like_regex` pattern with XQuery flags.
+    #[cfg(feature = "regex")]
+    fn compile_regex(&self, pattern: &str, flags: &str) -> Result<Regex, Error> {
+        // `q` quotes the whole pattern, so that `x` has no effect
+        let quoted = flags.contains('q');
+        let pattern = if quoted {
+            regex::escape(pattern)
+        } else {
+            pattern.to_owned()
+        };
+        let mut builder = RegexBuilder::new(&pattern);
+        for flag in flags.chars() {
+            match flag {
+                'i' => builder.case_insensitive(true),
+                's' => builder.dot_matches_new_line(true),
+                'm' => builder.multi_line(true),
+                'x' => builder.ignore_whitespace(!quoted),
+                'q' => &mut builder,
+                _ => {
+                    return Err(self.error(format!(
+                        "unrecognized flag character `{flag}` in like_regex"
+                    )))
+                }
+            };
+        }
+        builder
+            .build()
+            .map_err(|e| self.error(format!("invalid regular expression: {e}")))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let op = match self.peek() {
+                Token::Punct("+") => BinaryOp::Add,
+                Token::Punct("-") => BinaryOp::Sub,
+                _ => return Ok(left),
+            };
+            self.check(&left, false)?;
+            self.index += 1;
+            let right = self.parse_multiplicative()?;
+            self.check(&right, false)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, Error> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Punct("*") => BinaryOp::Mul,
+                Token::Punct("/") => BinaryOp::Div,
+                Token::Punct("%") => BinaryOp::Rem,
+                _ => return Ok(left),
+            };
+            self.check(&left, false)?;
+            self.index += 1;
+            let right = self.parse_unary()?;
+            self.check(&right, false)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        let op = match self.peek() {
+            Token::Punct("+") => UnaryOp::Plus,
+            Token::Punct("-") => UnaryOp::Minus,
+            _ => return self.parse_accessors(),
+        };
+        self.index += 1;
+        let expr = self.parse_unary()?;
+        self.check(&expr, false)?;
+        Ok(Expr::Unary(op, Box::new(expr)))
+    }
+
+    fn parse_accessors(&mut self) -> Result<Expr, Error> {
+        let base = self.parse_primary()?;
+        let mut accessors = vec![];
+        loop {
+            let accessor = match self.peek() {
+                Token::Punct(".") => {
+                    self.index += 1;
+                    self.parse_dot_accessor()?
+                }
+                Token::Punct("[") => {
+                    self.index += 1;
+                    self.parse_subscripts()?
+                }
+                Token::Punct("?") => {
+                    self.index += 1;
+                    self.expect_punct("(")?;
+                    self.filter_depth += 1;
+                    let predicate = self.parse_or()?;
+                    self.filter_depth -= 1;
+                    self.check(&predicate, true)?;
+                    self.expect_punct(")")?;
+                    Accessor::Filter(predicate)
+                }
+                _ => break,
+            };
+            self.check(&base, false)?;
+            accessors.push(accessor);
+        }
+        if accessors.is_empty() {
+            Ok(base)
+        } else {
+            Ok(Expr::Accessors(Box::new(base), accessors))
+        }
+    }
+
+    /// Parses the accessor after a dot.
+    fn parse_dot_accessor(&mut self) -> Result<Accessor, Error> {
+        Ok(match self.next() {
+            Token::Punct("*") => Accessor::MemberWildcard,
+            Token::Punct("**") => {
+                let (mut min, mut max) = (0, u32::MAX);
+                if self.eat_punct("{") {
+                    min = self.parse_level()?;
+                    max = if self.eat_keyword("to") {
+                        self.parse_level()?
+                    } else {
+                        min
+                    };
+                    self.expect_punct("}")?;
+                }
+                Accessor::Descendants { min, max }
+            }
+            Token::Ident(name) if *self.peek() == Token::Punct("(") => {
+                let Some(method) = Method::from_name(&name) else {
+                    self.index -= 1;
+                    return Err(self.error(format!("unknown item method `.{name}()`")));
+                };
+                self.index += 1;
+                self.expect_punct(")")?;
+                Accessor::Method(method)
+            }
+            Token::Ident(key) | Token::String(key) => Accessor::Member(key),
+            _ => {
+                self.index -= 1;
+                return Err(self.unexpected());
+            }
+        })
+    }
+
+    /// Parses a level of `.**{}`.
+    fn parse_level(&mut self) -> Result<u32, Error> {
+        match self.next() {
+            Token::Ident(s) if s == "last" => Ok(u32::MAX),
+            Token::Number(n) if n.as_ref().as_u64().is_some_and(|n| n < u32::MAX as u64) => {
+                Ok(n.as_ref().as_u64().unwrap() as u32)
+            }
+            _ => {
+                self.index -= 1;
+                Err(self.error("expected a non-negative integer or `last`".into()))
+            }
+        }
+    }
+
+    /// Parses the subscripts after `[`.
+    fn parse_subscripts(&mut self) -> Result<Accessor, Error> {
+        if self.eat_punct("*") {
+            self.expect_punct("]")?;
+            return Ok(Accessor::ElementWildcard);
+        }
+        self.subscript_depth += 1;
+        let mut subscripts = vec![];
+        loop {
+            let from = self.parse_additive()?;
+            self.check(&from, false)?;
+            let to = if self.eat_keyword("to") {
+                let to = self.parse_additive()?;
+                self.check(&to, false)?;
+                Some(to)
+            } else {
+                None
+            };
+            subscripts.push((from, to));
+            if !self.eat_punct(",") {
+                break;
+            }
+        }
+        self.subscript_depth -= 1;
+        self.expect_punct("]")?;
+        Ok(Accessor::Elements(subscripts))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, Error> {
+        Ok(match self.next() {
+            Token::Punct("$") => Expr::Root,
+            Token::Punct("@") => {
+                if self.filter_depth == 0 {
+                    self.index -= 1;
+                    return Err(self.error("`@` is not allowed outside of filters".into()));
+                }
+                Expr::Current
+            }
+            Token::Variable(name) => {
+                self.variables.push(name.clone());
+                Expr::Variable(name)
+            }
+            Token::Number(n) => Expr::Literal(n),
+            Token::String(s) => Expr::Literal(Value::from(s.as_str())),
+            Token::Ident(s) => match s.as_str() {
+                "true" => Expr::Literal(Value::from(true)),
+                "false" => Expr::Literal(Value::from(false)),
+                "null" => Expr::Literal(Value::from(())),
+                "last" => {
+                    if self.subscript_depth == 0 {
+                        self.index -= 1;
+                        return Err(self.error("`last` is allowed only in array subscripts".into()));
+                    }
+                    Expr::Last
+                }
+                "exists" => {
+                    self.expect_punct("(")?;
+                    let expr = self.parse_or()?;
+                    self.check(&expr, false)?;
+                    self.expect_punct(")")?;
+                    Expr::Exists(Box::new(expr))
+                }
+                _ => {
+                    self.index -= 1;
+                    return Err(self.unexpected());
+                }
+            },
+            Token::Punct("(") => {
+                let expr = self.parse_or()?;
+                self.expect_punct(")")?;
+                if expr.is_predicate() && self.eat_keyword("is") {
+                    if !self.eat_keyword("unknown") {
+                        return Err(
+                            self.error(format!("expected `unknown`, found {}", self.peek()))
+                        );
+                    }
+                    return Ok(Expr::IsUnknown(Box::new(expr)));
+                }
+                expr
+            }
+            _ => {
+                self.index -= 1;
+                return Err(self.unexpected());
+            }
+        })
+    }
+}
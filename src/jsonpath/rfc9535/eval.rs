@@ -0,0 +1,308 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evaluator of RFC 9535 JSONPath queries.
+
+#[cfg(feature = "regex")]
+use super::parser::{compile_regex, Pattern};
+use super::parser::{Comparable, CompareOp, Function, Logical, Query, Selector};
+use super::{NormalizedPath, PathElement};
+use crate::jsonpath::JsonItem;
+use crate::{ObjectRef, Value, ValueRef};
+
+/// The location of a node, which is not tracked by `()`.
+pub(super) trait Location<'a>: Clone {
+    fn member(&self, object: ObjectRef<'a>, name: &str) -> Self;
+    fn key(&self, name: &'a str) -> Self;
+    fn index(&self, index: usize) -> Self;
+}
+
+impl Location<'_> for () {
+    fn member(&self, _: ObjectRef<'_>, _: &str) -> Self {}
+    fn key(&self, _: &str) -> Self {}
+    fn index(&self, _: usize) -> Self {}
+}
+
+impl<'a> Location<'a> for NormalizedPath<'a> {
+    fn member(&self, object: ObjectRef<'a>, name: &str) -> Self {
+        // borrow the name from the document
+        let name = object.keys().find(|k| *k == name).unwrap();
+        self.key(name)
+    }
+
+    fn key(&self, name: &'a str) -> Self {
+        let mut path = self.clone();
+        path.0.push(PathElement::Name(name));
+        path
+    }
+
+    fn index(&self, index: usize) -> Self {
+        let mut path = self.clone();
+        path.0.push(PathElement::Index(index));
+        path
+    }
+}
+
+pub(super) struct Evaluator<'a> {
+    pub root: ValueRef<'a>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// Returns the nodes selected by the query, starting from `current` for relative queries.
+    pub fn query<L: Location<'a>>(
+        &self,
+        query: &Query,
+        current: ValueRef<'a>,
+        location: L,
+    ) -> Vec<(L, ValueRef<'a>)> {
+        let start = if query.relative { current } else { self.root };
+        let mut nodes = vec![(location, start)];
+        for segment in &query.segments {
+            let mut output = vec![];
+            for (location, value) in &nodes {
+                if segment.descendant {
+                    self.descendants(&segment.selectors, location, *value, &mut output);
+                } else {
+                    for selector in &segment.selectors {
+                        self.select(selector, location, *value, &mut output);
+                    }
+                }
+            }
+            nodes = output;
+        }
+        nodes
+    }
+
+    /// Applies the selectors to the node and all its descendants, in document order.
+    fn descendants<L: Location<'a>>(
+        &self,
+        selectors: &[Selector],
+        location: &L,
+        value: ValueRef<'a>,
+        output: &mut Vec<(L, ValueRef<'a>)>,
+    ) {
+        for selector in selectors {
+            self.select(selector, location, value, output);
+        }
+        match value {
+            ValueRef::Array(array) => {
+                for (i, child) in array.iter().enumerate() {
+                    self.descendants(selectors, &location.index(i), child, output);
+                }
+            }
+            ValueRef::Object(object) => {
+                for (key, child) in object.iter() {
+                    self.descendants(selectors, &location.key(key), child, output);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Applies a selector to a node.
+    fn select<L: Location<'a>>(
+        &self,
+        selector: &Selector,
+        location: &L,
+        value: ValueRef<'a>,
+        output: &mut Vec<(L, ValueRef<'a>)>,
+    ) {
+        match (selector, value) {
+            (Selector::Name(name), ValueRef::Object(object)) => {
+                if let Some(child) = object.get(name) {
+                    output.push((location.member(object, name), child));
+                }
+            }
+            (Selector::Wildcard, ValueRef::Array(array)) => {
+                output.extend(
+                    (array.iter().enumerate()).map(|(i, child)| (location.index(i), child)),
+                );
+            }
+            (Selector::Wildcard, ValueRef::Object(object)) => {
+                output.extend(object.iter().map(|(k, child)| (location.key(k), child)));
+            }
+            (Selector::Index(index), ValueRef::Array(array)) => {
+                let len = array.len() as i64;
+                let index = if *index < 0 { len + index } else { *index };
+                if (0..len).contains(&index) {
+                    let child = array.get(index as usize).unwrap();
+                    output.push((location.index(index as usize), child));
+                }
+            }
+            (&Selector::Slice { start, end, step }, ValueRef::Array(array)) => {
+                let len = array.len() as i64;
+                let step = step.unwrap_or(1);
+                let normalize = |i: i64| if i >= 0 { i } else { len + i };
+                if step > 0 {
+                    let lower = normalize(start.unwrap_or(0)).clamp(0, len);
+                    let upper = normalize(end.unwrap_or(len)).clamp(0, len);
+                    let mut i = lower;
+                    while i < upper {
+                        output.push((location.index(i as usize), array.get(i as usize).unwrap()));
+                        i += step;
+                    }
+                } else if step < 0 {
+                    let upper = normalize(start.unwrap_or(len - 1)).clamp(-1, len - 1);
+                    let lower = end.map_or(-1, |end| normalize(end).clamp(-1, len - 1));
+                    let mut i = upper;
+                    while lower < i {
+                        output.push((location.index(i as usize), array.get(i as usize).unwrap()));
+                        i += step;
+                    }
+                }
+            }
+            (Selector::Filter(filter), ValueRef::Array(array)) => {
+                for (i, child) in array.iter().enumerate() {
+                    if self.logical(filter, child) {
+                        output.push((location.index(i), child));
+                    }
+                }
+            }
+            (Selector::Filter(filter), ValueRef::Object(object)) => {
+                for (k, child) in object.iter() {
+                    if self.logical(filter, child) {
+                        output.push((location.key(k), child));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Evaluates a logical expression with `@` as `current`.
+    fn logical(&self, expr: &Logical, current: ValueRef<'a>) -> bool {
+        match expr {
+            Logical::Or(operands) => operands.iter().any(|e| self.logical(e, current)),
+            Logical::And(operands) => operands.iter().all(|e| self.logical(e, current)),
+            Logical::Not(operand) => !self.logical(operand, current),
+            Logical::Exists(query) => !self.query(query, current, ()).is_empty(),
+            Logical::Function(function) => self.function_logical(function, current),
+            Logical::Compare(op, left, right) => {
+                let left = self.comparable(left, current);
+                let right = self.comparable(right, current);
+                let left = left.as_ref().map(JsonItem::as_ref);
+                let right = right.as_ref().map(JsonItem::as_ref);
+                match op {
+                    CompareOp::Eq => equal(left, right),
+                    CompareOp::Ne => !equal(left, right),
+                    CompareOp::Lt => less(left, right),
+                    CompareOp::Le => less(left, right) || equal(left, right),
+                    CompareOp::Gt => less(right, left),
+                    CompareOp::Ge => less(right, left) || equal(left, right),
+                }
+            }
+        }
+    }
+
+    /// Evaluates a comparable value, or returns `None` for Nothing.
+    fn comparable<'b>(&self, expr: &'b Comparable, current: ValueRef<'a>) -> Option<JsonItem<'b>>
+    where
+        'a: 'b,
+    {
+        match expr {
+            Comparable::Literal(value) => Some(JsonItem::Borrowed(value.as_ref())),
+            Comparable::Query(query) => {
+                let nodes = self.query(query, current, ());
+                nodes.first().map(|(_, v)| JsonItem::from_ref(*v))
+            }
+            Comparable::Function(function) => self.function_value(function, current),
+        }
+    }
+
+    /// Evaluates a function returning a value.
+    fn function_value<'b>(
+        &self,
+        function: &'b Function,
+        current: ValueRef<'a>,
+    ) -> Option<JsonItem<'b>>
+    where
+        'a: 'b,
+    {
+        match function {
+            Function::Length(arg) => {
+                let len = match self.comparable(arg, current)?.as_ref() {
+                    ValueRef::String(s) => s.as_str().chars().count(),
+                    ValueRef::Array(array) => array.len(),
+                    ValueRef::Object(object) => object.len(),
+                    _ => return None,
+                };
+                Some(JsonItem::Owned(Value::from(len as u64)))
+            }
+            Function::Count(query) => {
+                let count = self.query(query, current, ()).len();
+                Some(JsonItem::Owned(Value::from(count as u64)))
+            }
+            Function::Value(query) => match self.query(query, current, ()).as_slice() {
+                [(_, value)] => Some(JsonItem::from_ref(*value)),
+                _ => None,
+            },
+            #[cfg(feature = "regex")]
+            Function::Match(..) | Function::Search(..) => unreachable!("logical function"),
+        }
+    }
+
+    /// Evaluates a function returning a logical value.
+    #[cfg(not(feature = "regex"))]
+    fn function_logical(&self, _: &Function, _: ValueRef<'a>) -> bool {
+        unreachable!("value function")
+    }
+
+    /// Evaluates a function returning a logical value.
+    #[cfg(feature = "regex")]
+    fn function_logical(&self, function: &Function, current: ValueRef<'a>) -> bool {
+        let (value, pattern, compiled, full) = match function {
+            Function::Match(value, pattern, compiled) => (value, pattern, compiled, true),
+            Function::Search(value, pattern, compiled) => (value, pattern, compiled, false),
+            _ => unreachable!("value function"),
+        };
+        let Some(value) = self.comparable(value, current) else {
+            return false;
+        };
+        let Some(value) = value.as_ref().as_str() else {
+            return false;
+        };
+        match compiled {
+            Pattern::Compiled(regex) => regex.as_ref().is_some_and(|r| r.is_match(value)),
+            Pattern::Dynamic => {
+                let Some(pattern) = self.comparable(pattern, current) else {
+                    return false;
+                };
+                let regex = pattern
+                    .as_ref()
+                    .as_str()
+                    .and_then(|p| compile_regex(p, full));
+                regex.is_some_and(|r| r.is_match(value))
+            }
+        }
+    }
+}
+
+/// `==` where `None` is Nothing.
+fn equal(a: Option<ValueRef<'_>>, b: Option<ValueRef<'_>>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// `<`, which is only true for two numbers or two strings.
+fn less(a: Option<ValueRef<'_>>, b: Option<ValueRef<'_>>) -> bool {
+    match (a, b) {
+        (Some(ValueRef::Number(a)), Some(ValueRef::Number(b))) => a < b,
+        // UTF-8 bytes sort in the order of Unicode scalar values
+        (Some(ValueRef::String(a)), Some(ValueRef::String(b))) => a.as_str() < b.as_str(),
+        _ => false,
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! JSONPath as standardized by RFC 9535.
+
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use self::eval::Evaluator;
+use self::parser::Query;
+use crate::{Error, ValueRef};
+
+mod eval;
+mod parser;
+
+/// A compiled RFC 9535 JSONPath query.
+///
+/// The query is compiled once, including its well-typedness checks and literal regular
+/// expressions, and evaluated directly over [`ValueRef`]s. The function extensions `length`,
+/// `count`, `match`, `search` and `value` are supported. `match` and `search` require the `regex`
+/// feature, which is enabled by default.
+///
+/// Raw JSON nodes are selected as a whole but never descended into.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::jsonpath::JsonPath;
+/// use jsonbb::Value;
+///
+/// let value: Value = r#"{"books": [{"price": 8}, {"price": 23, "isbn": "0-395"}]}"#.parse().unwrap();
+///
+/// let path = JsonPath::new("$.books[?@.price < 10 || @.isbn]").unwrap();
+/// let nodes = path.query(value.as_ref());
+/// assert_eq!(nodes.len(), 2);
+///
+/// let path = JsonPath::new("$..[?length(@) == 2]").unwrap();
+/// let located = path.query_located(value.as_ref());
+/// assert_eq!(located[0].0.to_string(), "$['books']");
+/// assert_eq!(located[1].0.to_string(), "$['books'][1]");
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+    text: String,
+    query: Query,
+}
+
+impl JsonPath {
+    /// Compiles a query.
+    ///
+    /// Returns [`Error::Parse`] if the query is not valid or not well-typed.
+    pub fn new(path: &str) -> Result<Self, Error> {
+        Ok(JsonPath {
+            text: path.into(),
+            query: parser::parse(path)?,
+        })
+    }
+
+    /// Returns the nodes selected by the query.
+    pub fn query<'a>(&self, value: ValueRef<'a>) -> Vec<ValueRef<'a>> {
+        let evaluator = Evaluator { root: value };
+        let nodes = evaluator.query(&self.query, value, ());
+        nodes.into_iter().map(|(_, node)| node).collect()
+    }
+
+    /// Returns the nodes selected by the query along with their normalized paths.
+    pub fn query_located<'a>(
+        &self,
+        value: ValueRef<'a>,
+    ) -> Vec<(NormalizedPath<'a>, ValueRef<'a>)> {
+        let evaluator = Evaluator { root: value };
+        evaluator.query(&self.query, value, NormalizedPath::default())
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// The location of a node in a value, such as `$['a'][0]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NormalizedPath<'a>(Vec<PathElement<'a>>);
+
+/// An element of a [`NormalizedPath`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathElement<'a> {
+    /// A member name of an object.
+    Name(&'a str),
+    /// An index of an array.
+    Index(usize),
+}
+
+impl<'a> NormalizedPath<'a> {
+    /// Returns the elements of the path from the root.
+    pub fn elements(&self) -> &[PathElement<'a>] {
+        &self.0
+    }
+
+    /// Returns the path as a JSON pointer (RFC 6901), such as `/a/0`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::jsonpath::JsonPath;
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a/b": [1]}"#.parse().unwrap();
+    /// let path = JsonPath::new("$.*[0]").unwrap();
+    /// let (location, _) = &path.query_located(value.as_ref())[0];
+    /// assert_eq!(location.to_json_pointer(), "/a~1b/0");
+    /// assert_eq!(value.pointer(&location.to_json_pointer()).unwrap().as_u64(), Some(1));
+    /// ```
+    pub fn to_json_pointer(&self) -> String {
+        let mut pointer = String::new();
+        for element in &self.0 {
+            pointer.push('/');
+            match element {
+                PathElement::Name(name) => {
+                    pointer.push_str(&name.replace('~', "~0").replace('/', "~1"))
+                }
+                PathElement::Index(index) => write!(pointer, "{index}").unwrap(),
+            }
+        }
+        pointer
+    }
+}
+
+impl fmt::Display for NormalizedPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('$')?;
+        for element in &self.0 {
+            match element {
+                PathElement::Name(name) => {
+                    f.write_str("['")?;
+                    for c in name.chars() {
+                        match c {
+                            '\x08' => f.write_str("\\b")?,
+                            '\x0c' => f.write_str("\\f")?,
+                            '\n' => f.write_str("\\n")?,
+                            '\r' => f.write_str("\\r")?,
+                            '\t' => f.write_str("\\t")?,
+                            '\'' => f.write_str("\\'")?,
+                            '\\' => f.write_str("\\\\")?,
+                            '\0'..='\x1f' => write!(f, "\\u{:04x}", c as u32)?,
+                            _ => f.write_char(c)?,
+                        }
+                    }
+                    f.write_str("']")?;
+                }
+                PathElement::Index(index) => write!(f, "[{index}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Value;
+
+    /// Runs the official JSONPath Compliance Test Suite
+    /// <https://github.com/jsonpath-standard/jsonpath-compliance-test-suite>.
+    ///
+    /// See `tests/jsonpath-compliance-test-suite/README.md` for the vendored version.
+    #[test]
+    #[cfg(feature = "regex")]
+    fn compliance() {
+        let suite: serde_json::Value = serde_json::from_str(include_str!(
+            "../../../tests/jsonpath-compliance-test-suite/cts.json"
+        ))
+        .unwrap();
+        assert_eq!(suite["tests"].as_array().unwrap().len(), 703);
+        let parse = |v: &serde_json::Value| v.to_string().parse::<Value>().unwrap();
+        let mut failures = vec![];
+        for test in suite["tests"].as_array().unwrap() {
+            let name = test["name"].as_str().unwrap();
+            let selector = test["selector"].as_str().unwrap();
+            let path = JsonPath::new(selector);
+            if test["invalid_selector"].as_bool() == Some(true) {
+                if path.is_ok() {
+                    failures.push(format!("{name}: `{selector}` should be invalid"));
+                }
+                continue;
+            }
+            let path = match path {
+                Ok(path) => path,
+                Err(e) => {
+                    failures.push(format!("{name}: `{selector}` failed to parse: {e}"));
+                    continue;
+                }
+            };
+            let document = parse(&test["document"]);
+            let located = path.query_located(document.as_ref());
+            let nodes = Value::array(located.iter().map(|(_, v)| *v));
+            let paths = located
+                .iter()
+                .map(|(p, _)| p.to_string())
+                .collect::<Vec<_>>();
+
+            // a single result, or any of the results for non-deterministic ordering
+            let (results, result_paths) = match test.get("result") {
+                Some(result) => (vec![result], vec![test.get("result_paths")]),
+                None => {
+                    let results = test["results"].as_array().unwrap();
+                    let paths = test.get("results_paths").and_then(|p| p.as_array());
+                    let paths = (0..results.len()).map(|i| paths.map(|p| &p[i])).collect();
+                    (results.iter().collect(), paths)
+                }
+            };
+            let matched = results.iter().zip(result_paths).any(|(result, expected)| {
+                parse(result) == nodes
+                    && expected.is_none_or(|expected| {
+                        expected
+                            .as_array()
+                            .unwrap()
+                            .iter()
+                            .map(|p| p.as_str().unwrap())
+                            .eq(paths.iter().map(|p| p.as_str()))
+                    })
+            });
+            if !matched {
+                failures.push(format!(
+                    "{name}: `{selector}` returned {nodes} at {paths:?}"
+                ));
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn normalized_path() {
+        let value: Value = r#"{"a'\\\n\u0001": [0, {"b": 1}]}"#.parse().unwrap();
+        let path = JsonPath::new("$..b").unwrap();
+        let located = path.query_located(value.as_ref());
+        assert_eq!(located.len(), 1);
+        assert_eq!(located[0].0.to_string(), r"$['a\'\\\n\u0001'][1]['b']");
+        assert_eq!(
+            located[0].0.elements(),
+            &[
+                PathElement::Name("a'\\\n\u{1}"),
+                PathElement::Index(1),
+                PathElement::Name("b")
+            ]
+        );
+        assert_eq!(located[0].1.as_u64(), Some(1));
+    }
+
+    #[test]
+    fn raw_json() {
+        let mut builder = crate::Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        builder.add_raw_json(r#"{"b": 1}"#).unwrap();
+        builder.add_raw_json("1").unwrap();
+        builder.end_array();
+        let value = builder.finish();
+
+        let path = JsonPath::new("$[0]").unwrap();
+        assert!(path.query(value.as_ref())[0].is_raw_json());
+        let path = JsonPath::new("$..b").unwrap();
+        assert!(path.query(value.as_ref()).is_empty());
+        // raw JSON nodes are compared by their values
+        let path = JsonPath::new("$[?@ == 1]").unwrap();
+        let located = path.query_located(value.as_ref());
+        assert_eq!(located.len(), 1);
+        assert_eq!(located[0].0.to_string(), "$[1]");
+    }
+}
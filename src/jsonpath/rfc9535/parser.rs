@@ -0,0 +1,734 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser of RFC 9535 JSONPath queries.
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::{Error, Value};
+
+/// The largest integer allowed in indexes and slices, 2^53 - 1.
+const MAX_INT: i64 = (1 << 53) - 1;
+
+/// A query, either absolute (`$...`) or relative to the current node (`@...`).
+#[derive(Debug, Clone)]
+pub(super) struct Query {
+    pub relative: bool,
+    pub segments: Vec<Segment>,
+}
+
+impl Query {
+    /// Returns true if the query produces at most one node.
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|s| {
+            !s.descendant
+                && matches!(
+                    s.selectors.as_slice(),
+                    [Selector::Name(_) | Selector::Index(_)]
+                )
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Segment {
+    /// True for `..`.
+    pub descendant: bool,
+    pub selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice {
+        start: Option<i64>,
+        end: Option<i64>,
+        step: Option<i64>,
+    },
+    Filter(Box<Logical>),
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Logical {
+    Or(Vec<Logical>),
+    And(Vec<Logical>),
+    Not(Box<Logical>),
+    Compare(CompareOp, Comparable, Comparable),
+    /// True if the query selects any node.
+    Exists(Query),
+    /// A function returning a logical value.
+    Function(Function),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// An operand of comparisons and a function argument of value type.
+#[derive(Debug, Clone)]
+pub(super) enum Comparable {
+    Literal(Value),
+    /// A singular query.
+    Query(Query),
+    /// A function returning a value.
+    Function(Box<Function>),
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Function {
+    Length(Comparable),
+    Count(Query),
+    #[cfg(feature = "regex")]
+    Match(Comparable, Comparable, Pattern),
+    #[cfg(feature = "regex")]
+    Search(Comparable, Comparable, Pattern),
+    Value(Query),
+}
+
+/// The regular expression of `match` or `search`.
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub(super) enum Pattern {
+    /// The pattern is not a literal and is compiled on evaluation.
+    Dynamic,
+    /// A literal pattern, or `None` if it is not a valid I-Regexp.
+    Compiled(Option<Regex>),
+}
+
+/// The type of a function result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FunctionType {
+    Value,
+    #[cfg_attr(not(feature = "regex"), allow(dead_code))]
+    Logical,
+}
+
+impl Function {
+    fn result_type(&self) -> FunctionType {
+        match self {
+            Function::Length(_) | Function::Count(_) | Function::Value(_) => FunctionType::Value,
+            #[cfg(feature = "regex")]
+            Function::Match(..) | Function::Search(..) => FunctionType::Logical,
+        }
+    }
+}
+
+/// Translates an I-Regexp (RFC 9485) to the syntax of the `regex` crate.
+///
+/// The only difference is that `.` does not match `\n` and `\r`. Returns `None` if the pattern is
+/// invalid. `full` anchors the pattern at both ends.
+#[cfg(feature = "regex")]
+pub(super) fn compile_regex(pattern: &str, full: bool) -> Option<Regex> {
+    let mut translated = String::with_capacity(pattern.len() + 16);
+    translated.push_str(if full { r"\A(?:" } else { "(?:" });
+    let mut in_class = false;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                translated.push(c);
+                translated.push(chars.next()?);
+            }
+            '[' if !in_class => {
+                in_class = true;
+                translated.push(c);
+            }
+            ']' if in_class => {
+                in_class = false;
+                translated.push(c);
+            }
+            '.' if !in_class => translated.push_str(r"[^\n\r]"),
+            _ => translated.push(c),
+        }
+    }
+    translated.push_str(if full { r")\z" } else { ")" });
+    Regex::new(&translated).ok()
+}
+
+/// Parses a query.
+pub(super) fn parse(text: &str) -> Result<Query, Error> {
+    let mut parser = Parser {
+        text,
+        bytes: text.as_bytes(),
+        pos: 0,
+    };
+    if parser.peek() != Some(b'$') {
+        return Err(parser.error("expected `$`"));
+    }
+    let query = parser.parse_query()?;
+    if parser.pos < text.len() {
+        return Err(parser.error("unexpected character"));
+    }
+    Ok(query)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+fn is_name_first(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c >= 0x80
+}
+
+fn is_name_char(c: u8) -> bool {
+    is_name_first(c) || c.is_ascii_digit()
+}
+
+impl Parser<'_> {
+    #[cold]
+    fn error(&self, message: &str) -> Error {
+        Error::parse(message, self.bytes, self.pos)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        let found = self.bytes[self.pos..].starts_with(s.as_bytes());
+        if found {
+            self.pos += s.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", c as char)))
+        }
+    }
+
+    /// Skips optional blank space.
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a query starting with `$` or `@`.
+    fn parse_query(&mut self) -> Result<Query, Error> {
+        let relative = self.peek() == Some(b'@');
+        self.pos += 1;
+        let mut segments = vec![];
+        loop {
+            // blank space is allowed before segments, but not after the query
+            let start = self.pos;
+            self.skip_blank();
+            let segment = if self.eat("..") {
+                let selectors = match self.peek() {
+                    Some(b'[') => self.parse_bracketed()?,
+                    Some(b'*') => {
+                        self.pos += 1;
+                        vec![Selector::Wildcard]
+                    }
+                    Some(c) if is_name_first(c) => vec![self.parse_member_name()],
+                    _ => return Err(self.error("expected a selector after `..`")),
+                };
+                Segment {
+                    descendant: true,
+                    selectors,
+                }
+            } else if self.eat(".") {
+                let selector = match self.peek() {
+                    Some(b'*') => {
+                        self.pos += 1;
+                        Selector::Wildcard
+                    }
+                    Some(c) if is_name_first(c) => self.parse_member_name(),
+                    _ => return Err(self.error("expected a member name or `*` after `.`")),
+                };
+                Segment {
+                    descendant: false,
+                    selectors: vec![selector],
+                }
+            } else if self.peek() == Some(b'[') {
+                Segment {
+                    descendant: false,
+                    selectors: self.parse_bracketed()?,
+                }
+            } else {
+                self.pos = start;
+                return Ok(Query { relative, segments });
+            };
+            segments.push(segment);
+        }
+    }
+
+    fn parse_member_name(&mut self) -> Selector {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.pos += 1;
+        }
+        Selector::Name(self.text[start..self.pos].into())
+    }
+
+    /// Parses `[selector, ...]`.
+    fn parse_bracketed(&mut self) -> Result<Vec<Selector>, Error> {
+        self.expect(b'[')?;
+        let mut selectors = vec![];
+        loop {
+            self.skip_blank();
+            selectors.push(self.parse_selector()?);
+            self.skip_blank();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(selectors);
+                }
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, Error> {
+        match self.peek() {
+            Some(b'\'' | b'"') => Ok(Selector::Name(self.parse_string()?)),
+            Some(b'*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some(b'?') => {
+                self.pos += 1;
+                self.skip_blank();
+                Ok(Selector::Filter(Box::new(self.parse_or()?)))
+            }
+            Some(b'-' | b'0'..=b'9' | b':') => {
+                let start = self.parse_optional_int()?;
+                self.skip_blank();
+                if self.peek() != Some(b':') {
+                    return start
+                        .map(Selector::Index)
+                        .ok_or_else(|| self.error("expected an index"));
+                }
+                self.pos += 1;
+                self.skip_blank();
+                let end = self.parse_optional_int()?;
+                self.skip_blank();
+                let mut step = None;
+                if self.peek() == Some(b':') {
+                    self.pos += 1;
+                    self.skip_blank();
+                    step = self.parse_optional_int()?;
+                }
+                Ok(Selector::Slice { start, end, step })
+            }
+            _ => Err(self.error("expected a selector")),
+        }
+    }
+
+    /// Parses an integer if there is one.
+    fn parse_optional_int(&mut self) -> Result<Option<i64>, Error> {
+        if !matches!(self.peek(), Some(b'-' | b'0'..=b'9')) {
+            return Ok(None);
+        }
+        let start = self.pos;
+        self.eat("-");
+        let digits = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text = &self.text[digits..self.pos];
+        let valid = match text.as_bytes() {
+            [] => false,
+            // no leading zeros and no negative zero
+            [b'0'] => digits == start,
+            [b'0', ..] => false,
+            _ => true,
+        };
+        let value = self.text[start..self.pos].parse::<i64>().ok();
+        match value {
+            Some(value) if valid && (-MAX_INT..=MAX_INT).contains(&value) => Ok(Some(value)),
+            _ => {
+                self.pos = start;
+                Err(self.error("invalid integer"))
+            }
+        }
+    }
+
+    /// Parses a single- or double-quoted string literal.
+    fn parse_string(&mut self) -> Result<String, Error> {
+        let quote = self.bytes[self.pos];
+        self.pos += 1;
+        let mut s = String::new();
+        loop {
+            let Some(c) = self.text[self.pos..].chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            match c {
+                _ if c as u32 == quote as u32 => {
+                    self.pos += 1;
+                    return Ok(s);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some(b'b') => '\x08',
+                        Some(b'f') => '\x0c',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'/') => '/',
+                        Some(b'\\') => '\\',
+                        Some(c) if c == quote => c as char,
+                        Some(b'u') => {
+                            self.pos += 1;
+                            s.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    s.push(escaped);
+                }
+                '\0'..='\x1f' => return Err(self.error("control character in string")),
+                _ => {
+                    self.pos += c.len_utf8();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    /// Parses the hexadecimal digits after `\u`, and the low surrogate if any.
+    fn parse_unicode_escape(&mut self) -> Result<char, Error> {
+        let hex4 = |p: &mut Self| -> Result<u32, Error> {
+            let code = p
+                .text
+                .get(p.pos..p.pos + 4)
+                .filter(|h| h.bytes().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                .ok_or_else(|| p.error("invalid unicode escape"))?;
+            p.pos += 4;
+            Ok(code)
+        };
+        let high = hex4(self)?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                if !self.eat("\\u") {
+                    return Err(self.error("expected a low surrogate"));
+                }
+                let low = hex4(self)?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error("invalid low surrogate"));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error("unpaired low surrogate")),
+            _ => high,
+        };
+        Ok(char::from_u32(code).unwrap())
+    }
+
+    fn parse_or(&mut self) -> Result<Logical, Error> {
+        let mut operands = vec![self.parse_and()?];
+        loop {
+            let start = self.pos;
+            self.skip_blank();
+            if !self.eat("||") {
+                self.pos = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.parse_and()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Logical::Or(operands)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Logical, Error> {
+        let mut operands = vec![self.parse_basic()?];
+        loop {
+            let start = self.pos;
+            self.skip_blank();
+            if !self.eat("&&") {
+                self.pos = start;
+                break;
+            }
+            self.skip_blank();
+            operands.push(self.parse_basic()?);
+        }
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Logical::And(operands)
+        })
+    }
+
+    /// Parses a parenthesized expression, a comparison or a test expression.
+    fn parse_basic(&mut self) -> Result<Logical, Error> {
+        if self.eat("!") {
+            self.skip_blank();
+            let operand = match self.peek() {
+                Some(b'(') => self.parse_paren()?,
+                _ => {
+                    let start = self.pos;
+                    match self.parse_operand()? {
+                        Operand::Query(q) => Logical::Exists(q),
+                        Operand::Function(f) if f.result_type() == FunctionType::Logical => {
+                            Logical::Function(f)
+                        }
+                        _ => {
+                            self.pos = start;
+                            return Err(self.error("expected a test expression after `!`"));
+                        }
+                    }
+                }
+            };
+            return Ok(Logical::Not(Box::new(operand)));
+        }
+        if self.peek() == Some(b'(') {
+            return self.parse_paren();
+        }
+        let start = self.pos;
+        let left = self.parse_operand()?;
+        let end = self.pos;
+        self.skip_blank();
+        let op = [
+            ("==", CompareOp::Eq),
+            ("!=", CompareOp::Ne),
+            ("<=", CompareOp::Le),
+            (">=", CompareOp::Ge),
+            ("<", CompareOp::Lt),
+            (">", CompareOp::Gt),
+        ]
+        .into_iter()
+        .find(|(s, _)| self.eat(s));
+        let Some((_, op)) = op else {
+            self.pos = end;
+            // a test expression
+            return match left {
+                Operand::Query(q) => Ok(Logical::Exists(q)),
+                Operand::Function(f) if f.result_type() == FunctionType::Logical => {
+                    Ok(Logical::Function(f))
+                }
+                _ => {
+                    self.pos = start;
+                    Err(self.error("expected a logical expression"))
+                }
+            };
+        };
+        let left = self.comparable(left, start)?;
+        self.skip_blank();
+        let start = self.pos;
+        let right = self.parse_operand()?;
+        let right = self.comparable(right, start)?;
+        Ok(Logical::Compare(op, left, right))
+    }
+
+    fn parse_paren(&mut self) -> Result<Logical, Error> {
+        self.expect(b'(')?;
+        self.skip_blank();
+        let expr = self.parse_or()?;
+        self.skip_blank();
+        self.expect(b')')?;
+        Ok(expr)
+    }
+
+    /// Converts an operand to a comparable value, which must be a literal, a singular query or a
+    /// function returning a value.
+    fn comparable(&mut self, operand: Operand, start: usize) -> Result<Comparable, Error> {
+        match operand {
+            Operand::Literal(v) => Ok(Comparable::Literal(v)),
+            Operand::Query(q) if q.is_singular() => Ok(Comparable::Query(q)),
+            Operand::Function(f) if f.result_type() == FunctionType::Value => {
+                Ok(Comparable::Function(Box::new(f)))
+            }
+            Operand::Query(_) => {
+                self.pos = start;
+                Err(self.error("expected a singular query"))
+            }
+            _ => {
+                self.pos = start;
+                Err(self.error("expected a function returning a value"))
+            }
+        }
+    }
+
+    /// Parses a literal, a query or a function expression.
+    fn parse_operand(&mut self) -> Result<Operand, Error> {
+        match self.peek() {
+            Some(b'$' | b'@') => Ok(Operand::Query(self.parse_query()?)),
+            Some(b'\'' | b'"') => Ok(Operand::Literal(Value::from(self.parse_string()?.as_str()))),
+            Some(b'-' | b'0'..=b'9') => self.parse_number().map(Operand::Literal),
+            Some(b'a'..=b'z') => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(b'a'..=b'z' | b'_' | b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                let name = &self.text[start..self.pos];
+                if self.peek() == Some(b'(') {
+                    return self.parse_function(name, start).map(Operand::Function);
+                }
+                match name {
+                    "true" => Ok(Operand::Literal(Value::from(true))),
+                    "false" => Ok(Operand::Literal(Value::from(false))),
+                    "null" => Ok(Operand::Literal(Value::from(()))),
+                    _ => {
+                        self.pos = start;
+                        Err(self.error("expected a literal, query or function"))
+                    }
+                }
+            }
+            _ => Err(self.error("expected a literal, query or function")),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        self.eat("-");
+        let int_start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let int = &self.bytes[int_start..self.pos];
+        if int.is_empty() || (int.len() > 1 && int[0] == b'0') {
+            self.pos = start;
+            return Err(self.error("invalid number"));
+        }
+        if self.eat(".") {
+            let frac = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if self.pos == frac {
+                return Err(self.error("expected digits after `.`"));
+            }
+        }
+        if self.eat("e") || self.eat("E") {
+            if !self.eat("-") {
+                self.eat("+");
+            }
+            let exp = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if self.pos == exp {
+                return Err(self.error("expected digits in exponent"));
+            }
+        }
+        let text = &self.text[start..self.pos];
+        let value = if text == "-0" {
+            // the JSON parser keeps negative zero as a float
+            Ok(Value::from(0))
+        } else {
+            Value::from_text(text.as_bytes())
+        };
+        value.map_err(|_| {
+            self.pos = start;
+            self.error("invalid number")
+        })
+    }
+
+    /// Parses the arguments of a function and checks their types.
+    fn parse_function(&mut self, name: &str, start: usize) -> Result<Function, Error> {
+        self.expect(b'(')?;
+        let mut args = vec![];
+        self.skip_blank();
+        if self.peek() != Some(b')') {
+            loop {
+                let arg_start = self.pos;
+                args.push((self.parse_operand()?, arg_start));
+                self.skip_blank();
+                if !self.eat(",") {
+                    break;
+                }
+                self.skip_blank();
+            }
+        }
+        self.expect(b')')?;
+        let arity = match name {
+            "length" | "count" | "value" => 1,
+            #[cfg(feature = "regex")]
+            "match" | "search" => 2,
+            #[cfg(not(feature = "regex"))]
+            "match" | "search" => {
+                self.pos = start;
+                return Err(self.error(&format!("function `{name}` requires the `regex` feature")));
+            }
+            _ => {
+                self.pos = start;
+                return Err(self.error(&format!("unknown function `{name}`")));
+            }
+        };
+        if args.len() != arity {
+            self.pos = start;
+            return Err(self.error(&format!(
+                "function `{name}` expects {arity} argument(s), found {}",
+                args.len()
+            )));
+        }
+        let mut args = args.into_iter();
+        let mut value_arg = |p: &mut Self| {
+            let (arg, start) = args.next().unwrap();
+            (p.comparable(arg, start), args.len())
+        };
+        Ok(match name {
+            "length" => Function::Length(value_arg(self).0?),
+            #[cfg(feature = "regex")]
+            "match" | "search" => {
+                let value = value_arg(self).0?;
+                let pattern = value_arg(self).0?;
+                let compiled = match &pattern {
+                    Comparable::Literal(p) => match p.as_ref().as_str() {
+                        Some(p) => Pattern::Compiled(compile_regex(p, name == "match")),
+                        None => Pattern::Compiled(None),
+                    },
+                    _ => Pattern::Dynamic,
+                };
+                if name == "match" {
+                    Function::Match(value, pattern, compiled)
+                } else {
+                    Function::Search(value, pattern, compiled)
+                }
+            }
+            _ => {
+                // `count` and `value` take a nodelist
+                let (arg, arg_start) = args.next().unwrap();
+                let Operand::Query(query) = arg else {
+                    self.pos = arg_start;
+                    return Err(self.error(&format!("function `{name}` expects a query")));
+                };
+                if name == "count" {
+                    Function::Count(query)
+                } else {
+                    Function::Value(query)
+                }
+            }
+        })
+    }
+}
+
+enum Operand {
+    Literal(Value),
+    Query(Query),
+    Function(Function),
+}
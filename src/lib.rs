@@ -108,21 +108,49 @@
 //! payload: [key, value] x n + [kentry, ventry] x n + n (u32) + len (u32)
 //!          ^start                                                       ^ptr
 //! where:   len = ptr - start
+//!          the highest bit of n is set if keys are ordered by length first
+//!          the second highest bit of n is set if the insertion order is kept, in which case
+//!          [index] x n (u32) follows the entries
+//!
+//! # Raw JSON
+//! entry: 0x7 | offset
+//! payload: len (u32) + JSON text + len (u32) + encoded value (data + root entry)
+//!          ^ptr
 //! ```
 
+mod batch;
 mod builder;
 mod entry;
+mod error;
+pub mod jq;
+pub mod jsonpath;
 mod macros;
+mod operators;
+mod parser;
 mod partial_eq;
+mod projected;
+mod push;
 mod serde;
+mod spans;
+mod stream;
+mod validate;
 mod value;
 mod value_ref;
+mod writer;
 
+pub use self::batch::{parse_batch, parse_batch_into};
 pub use self::builder::*;
 use self::entry::*;
+pub use self::error::Error;
+pub use self::parser::ParseOptions;
+pub use self::projected::ProjectedValue;
+pub use self::push::PushParser;
 pub use self::serde::*;
+pub use self::spans::{Span, Spans};
+pub use self::stream::{StreamFormat, StreamReader};
 pub use self::value::*;
 pub use self::value_ref::*;
+pub use self::writer::{FloatFormat, WriteOptions};
 pub use entry::Entry;
 
 // for `json!` macro
@@ -0,0 +1,1742 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operators of PostgreSQL's `jsonb` type.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use crate::{ArrayRef, Builder, Error, Index, ObjectRef, Value, ValueRef};
+
+impl<'a> ValueRef<'a> {
+    /// Returns `true` if the value contains the other value, as PostgreSQL's `@>` operator.
+    ///
+    /// - An object contains another object if it has every key of the other object, with a value
+    ///   that contains the other value.
+    /// - An array contains another array if every element of the other array is contained in some
+    ///   element of the array, regardless of order and duplicates.
+    /// - An array contains a scalar that is one of its elements, but only at the top level.
+    /// - A scalar contains an equal scalar. Numbers are compared by value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [1, 2, {"b": true}], "c": "d"}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert!(value.contains(r#"{"a": [{}, 2.0, 2]}"#.parse::<Value>().unwrap().as_ref()));
+    /// assert!(!value.contains(r#"{"a": 1}"#.parse::<Value>().unwrap().as_ref()));
+    ///
+    /// let array: Value = r#"[1, [2, 3]]"#.parse().unwrap();
+    /// assert!(array.as_ref().contains(Value::from(1).as_ref()));
+    /// assert!(array.as_ref().contains(r#"[[3]]"#.parse::<Value>().unwrap().as_ref()));
+    /// assert!(!array.as_ref().contains(r#"[3]"#.parse::<Value>().unwrap().as_ref()));
+    /// ```
+    pub fn contains(self, other: ValueRef<'_>) -> bool {
+        match (self, other) {
+            (ValueRef::RawJson(raw), _) => raw.value().contains(other),
+            (_, ValueRef::RawJson(raw)) => self.contains(raw.value()),
+            // a top-level array contains its scalar elements
+            (ValueRef::Array(array), ValueRef::Null)
+            | (ValueRef::Array(array), ValueRef::Bool(_))
+            | (ValueRef::Array(array), ValueRef::Number(_))
+            | (ValueRef::Array(array), ValueRef::String(_)) => {
+                array.iter().any(|element| deep_contains(element, other))
+            }
+            _ => deep_contains(self, other),
+        }
+    }
+
+    /// Returns `true` if the value is contained in the other value, as PostgreSQL's `<@`
+    /// operator.
+    ///
+    /// This is the same as `other.contains(self)`.
+    pub fn contained_by(self, other: ValueRef<'_>) -> bool {
+        other.contains(self)
+    }
+
+    /// Returns `true` if the key exists, as PostgreSQL's `?` operator.
+    ///
+    /// For an object, this checks whether the key is present. For an array, this checks whether
+    /// a string element is equal to the key. A string is treated as an array of itself.
+    /// Nested values are not searched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let object: Value = r#"{"a": 1, "b": {"c": 2}}"#.parse().unwrap();
+    /// assert!(object.as_ref().exists("a"));
+    /// assert!(!object.as_ref().exists("c"));
+    ///
+    /// let array: Value = r#"["a", 1, ["b"]]"#.parse().unwrap();
+    /// assert!(array.as_ref().exists("a"));
+    /// assert!(!array.as_ref().exists("1"));
+    /// assert!(!array.as_ref().exists("b"));
+    /// ```
+    pub fn exists(self, key: &str) -> bool {
+        match self {
+            ValueRef::Object(object) => object.contains_key(key),
+            ValueRef::Array(array) => array.iter().any(|element| is_key(element, key)),
+            ValueRef::String(s) => s.as_str() == key,
+            ValueRef::RawJson(raw) => raw.value().exists(key),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if any of the keys exists, as PostgreSQL's `?|` operator.
+    ///
+    /// If the keys are sorted in the [`key_order`](crate::ObjectRef::key_order) of an object,
+    /// they are matched in a single pass over the entries of the object. Otherwise each key is
+    /// looked up separately.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+    /// assert!(value.as_ref().exists_any(&["b", "x"]));
+    /// assert!(!value.as_ref().exists_any(&["x", "y"]));
+    /// assert!(!value.as_ref().exists_any(&[]));
+    /// ```
+    pub fn exists_any(self, keys: &[&str]) -> bool {
+        match self {
+            ValueRef::Object(object) if is_sorted_for(object, keys) => {
+                let order = object.key_order();
+                let mut entries = object.stored_iter().peekable();
+                for key in keys {
+                    while entries
+                        .next_if(|(k, _)| order.cmp(k, key) == Ordering::Less)
+                        .is_some()
+                    {}
+                    match entries.peek() {
+                        Some((k, _)) if k == key => return true,
+                        Some(_) => {}
+                        None => return false,
+                    }
+                }
+                false
+            }
+            ValueRef::Array(array) => array
+                .iter()
+                .any(|element| keys.iter().any(|key| is_key(element, key))),
+            ValueRef::RawJson(raw) => raw.value().exists_any(keys),
+            _ => keys.iter().any(|key| self.exists(key)),
+        }
+    }
+
+    /// Returns `true` if all of the keys exist, as PostgreSQL's `?&` operator.
+    ///
+    /// If the keys are sorted in the [`key_order`](crate::ObjectRef::key_order) of an object,
+    /// they are matched in a single pass over the entries of the object. Otherwise each key is
+    /// looked up separately.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+    /// assert!(value.as_ref().exists_all(&["a", "b"]));
+    /// assert!(!value.as_ref().exists_all(&["a", "x"]));
+    /// assert!(value.as_ref().exists_all(&[]));
+    /// ```
+    pub fn exists_all(self, keys: &[&str]) -> bool {
+        match self {
+            ValueRef::Object(object) if is_sorted_for(object, keys) => {
+                let order = object.key_order();
+                let mut entries = object.stored_iter().peekable();
+                keys.iter().all(|key| {
+                    while entries
+                        .next_if(|(k, _)| order.cmp(k, key) == Ordering::Less)
+                        .is_some()
+                    {}
+                    matches!(entries.peek(), Some((k, _)) if k == key)
+                })
+            }
+            ValueRef::RawJson(raw) => raw.value().exists_all(keys),
+            _ => keys.iter().all(|key| self.exists(key)),
+        }
+    }
+}
+
+impl<'a> ValueRef<'a> {
+    /// Looks up a value by a path of keys and array indices, as PostgreSQL's `#>` operator.
+    ///
+    /// Each element of the path is a key of an object or, for an array, an integer index.
+    /// Negative indices count from the end of the array. An empty path returns the value itself.
+    /// Indices are parsed as PostgreSQL's `strtoint` does: leading whitespace and a sign are
+    /// allowed, and the index must fit in an `i32`. Raw JSON on the path is looked into.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [1, {"b": 2}, 3]}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert_eq!(value.get_path(&["a", "1", "b"]).unwrap().as_u64(), Some(2));
+    /// assert_eq!(value.get_path(&["a", "-1"]).unwrap().as_u64(), Some(3));
+    /// assert_eq!(value.get_path(&["a", " 1", "b"]).unwrap().as_u64(), Some(2));
+    /// assert_eq!(value.get_path(&["a", "3"]), None);
+    /// assert_eq!(value.get_path(&["a", "x"]), None);
+    /// assert_eq!(value.get_path(&[]), Some(value));
+    /// ```
+    pub fn get_path(self, path: &[&str]) -> Option<ValueRef<'a>> {
+        path.iter()
+            .try_fold(self, |value, key| match value.resolve() {
+                ValueRef::Object(object) => object.get(key),
+                ValueRef::Array(array) => array.get(array_index(array, strtoint(key)?.into())?),
+                _ => None,
+            })
+    }
+
+    /// Index into a JSON array or object and returns the value as text, as PostgreSQL's `->>`
+    /// operator.
+    ///
+    /// See [`to_text`](Self::to_text) for how the value is converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": "x", "b": [1, "y"], "c": null}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert_eq!(value.get_text("a").as_deref(), Some("x"));
+    /// assert_eq!(value.get_text("b").as_deref(), Some(r#"[1, "y"]"#));
+    /// assert_eq!(value.get_text("c"), None);
+    /// ```
+    pub fn get_text(self, index: impl Index) -> Option<Cow<'a, str>> {
+        self.get(index)?.to_text()
+    }
+
+    /// Looks up a value by a path and returns it as text, as PostgreSQL's `#>>` operator.
+    ///
+    /// See [`get_path`](Self::get_path) for how the path is resolved and
+    /// [`to_text`](Self::to_text) for how the value is converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [1, {"b": "c"}]}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert_eq!(value.get_path_text(&["a", "-1", "b"]).as_deref(), Some("c"));
+    /// assert_eq!(value.get_path_text(&["a", "0"]).as_deref(), Some("1"));
+    /// ```
+    pub fn get_path_text(self, path: &[&str]) -> Option<Cow<'a, str>> {
+        self.get_path(path)?.to_text()
+    }
+
+    /// Returns the value as text in the way of PostgreSQL's `->>` and `#>>` operators.
+    ///
+    /// A string is returned unquoted and borrowed from the value. A null returns `None`.
+    /// Other values are printed as JSON text in the same format as
+    /// [`to_pg_text`](Self::to_pg_text).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::borrow::Cow;
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"["a\"b", 1.50, true, null]"#.parse().unwrap();
+    /// let array = value.as_array().unwrap();
+    /// assert!(matches!(array.get(0).unwrap().to_text(), Some(Cow::Borrowed("a\"b"))));
+    /// // PostgreSQL gives `1.50`, see `to_pg_text`
+    /// assert_eq!(array.get(1).unwrap().to_text().as_deref(), Some("1.5"));
+    /// assert_eq!(array.get(2).unwrap().to_text().as_deref(), Some("true"));
+    /// assert_eq!(array.get(3).unwrap().to_text(), None);
+    /// assert_eq!(value.as_ref().to_text().as_deref(), Some(r#"["a\"b", 1.5, true, null]"#));
+    /// ```
+    pub fn to_text(self) -> Option<Cow<'a, str>> {
+        match self {
+            ValueRef::Null => None,
+            ValueRef::String(s) => Some(Cow::Borrowed(s.as_str())),
+            ValueRef::RawJson(raw) => match raw.value() {
+                ValueRef::Null => None,
+                ValueRef::String(s) => Some(Cow::Owned(s.as_str().to_owned())),
+                value => Some(Cow::Owned(value.to_pg_text())),
+            },
+            value => Some(Cow::Owned(value.to_pg_text())),
+        }
+    }
+}
+
+impl ValueRef<'_> {
+    /// Removes a key from an object, or the string elements equal to the key from an array, as
+    /// PostgreSQL's `-` operator with a text operand.
+    ///
+    /// Returns an error if the value is a scalar.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+    /// assert_eq!(value.as_ref().remove_key("a").unwrap().to_string(), r#"{"b":2}"#);
+    ///
+    /// let value: Value = r#"["a", "b", "a"]"#.parse().unwrap();
+    /// assert_eq!(value.as_ref().remove_key("a").unwrap().to_string(), r#"["b"]"#);
+    ///
+    /// assert!(Value::from(1).as_ref().remove_key("a").is_err());
+    /// ```
+    pub fn remove_key(self, key: &str) -> Result<Value, Error> {
+        self.remove_keys(&[key])
+    }
+
+    /// Removes multiple keys from an object, or the string elements equal to any of the keys
+    /// from an array, as PostgreSQL's `-` operator with a `text[]` operand.
+    ///
+    /// Returns an error if the value is a scalar.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": 1, "b": 2, "c": 3}"#.parse().unwrap();
+    /// assert_eq!(value.as_ref().remove_keys(&["c", "a"]).unwrap().to_string(), r#"{"b":2}"#);
+    /// ```
+    pub fn remove_keys(self, keys: &[&str]) -> Result<Value, Error> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        match self {
+            ValueRef::Object(object) => {
+                let order = object.key_order();
+                let mut keys = keys.to_vec();
+                keys.sort_unstable_by(|a, b| order.cmp(a, b));
+                let removed = |k: &str| keys.binary_search_by(|x| order.cmp(x, k)).is_ok();
+                builder.begin_object();
+                for (k, v) in object.iter().filter(|(k, _)| !removed(k)) {
+                    builder.add_string(k);
+                    builder.add_value(v);
+                }
+                builder.end_object_like(object);
+            }
+            ValueRef::Array(array) => {
+                builder.begin_array();
+                for element in array.iter() {
+                    if !keys.iter().any(|key| is_key(element, key)) {
+                        builder.add_value(element);
+                    }
+                }
+                builder.end_array();
+            }
+            ValueRef::RawJson(raw) => return raw.value().remove_keys(keys),
+            _ => return Err(operator_error("cannot delete from scalar")),
+        }
+        Ok(builder.finish())
+    }
+
+    /// Removes the element at the index from an array, as PostgreSQL's `-` operator with an
+    /// integer operand.
+    ///
+    /// Negative indices count from the end of the array. An index out of range leaves the array
+    /// unchanged. Returns an error if the value is not an array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"[1, 2, 3]"#.parse().unwrap();
+    /// assert_eq!(value.as_ref().remove_index(0).unwrap().to_string(), "[2,3]");
+    /// assert_eq!(value.as_ref().remove_index(-1).unwrap().to_string(), "[1,2]");
+    /// assert_eq!(value.as_ref().remove_index(3).unwrap().to_string(), "[1,2,3]");
+    /// ```
+    pub fn remove_index(self, index: i64) -> Result<Value, Error> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        match self {
+            ValueRef::Array(array) => {
+                let index = array_index(array, index);
+                builder.begin_array();
+                for (i, element) in array.iter().enumerate() {
+                    if Some(i) != index {
+                        builder.add_value(element);
+                    }
+                }
+                builder.end_array();
+            }
+            ValueRef::Object(_) => {
+                return Err(operator_error(
+                    "cannot delete from object using integer index",
+                ))
+            }
+            ValueRef::RawJson(raw) => return raw.value().remove_index(index),
+            _ => return Err(operator_error("cannot delete from scalar")),
+        }
+        Ok(builder.finish())
+    }
+
+    /// Removes the value at the path, as PostgreSQL's `#-` operator.
+    ///
+    /// The path is resolved as in [`get_path`](Self::get_path). If it does not lead to a value,
+    /// the value is returned unchanged. Returns an error if the value is a scalar, or if an
+    /// element of the path addressing an array is not an integer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [1, {"b": 2, "c": 3}]}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// assert_eq!(
+    ///     value.remove_path(&["a", "-1", "b"]).unwrap().to_string(),
+    ///     r#"{"a":[1,{"c":3}]}"#
+    /// );
+    /// assert_eq!(value.remove_path(&["a", "0"]).unwrap().to_string(), r#"{"a":[{"b":2,"c":3}]}"#);
+    /// assert_eq!(value.remove_path(&["x", "y"]).unwrap(), value.to_owned());
+    /// assert!(value.remove_path(&["a", "x"]).is_err());
+    /// ```
+    pub fn remove_path(self, path: &[&str]) -> Result<Value, Error> {
+        match self {
+            ValueRef::Object(_) | ValueRef::Array(_) => {}
+            ValueRef::RawJson(raw) => return raw.value().remove_path(path),
+            _ => return Err(operator_error("cannot delete path in scalar")),
+        }
+        let mut builder = Builder::<Vec<u8>>::new();
+        update_path_into(&mut builder, self, path, 0, ValueRef::Null, PathOp::Delete)?;
+        Ok(builder.finish())
+    }
+
+    /// Replaces the value at the path, as PostgreSQL's `jsonb_set`.
+    ///
+    /// The path is resolved as in [`get_path`](Self::get_path). If the last element of the path
+    /// is missing and `create_missing` is `true`, the new value is added to the object, or to the
+    /// start or end of the array for an index out of range. Otherwise the value is returned
+    /// unchanged. Returns an error if the value is a scalar, or if an element of the path
+    /// addressing an array is not an integer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"[{"a": 1}, 2]"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// let new_value = Value::from("x");
+    /// let new_value = new_value.as_ref();
+    /// assert_eq!(
+    ///     value.set_path(&["0", "a"], new_value, true).unwrap().to_string(),
+    ///     r#"[{"a":"x"},2]"#
+    /// );
+    /// assert_eq!(
+    ///     value.set_path(&["0", "b"], new_value, true).unwrap().to_string(),
+    ///     r#"[{"a":1,"b":"x"},2]"#
+    /// );
+    /// assert_eq!(
+    ///     value.set_path(&["0", "b"], new_value, false).unwrap().to_string(),
+    ///     r#"[{"a":1},2]"#
+    /// );
+    /// assert_eq!(
+    ///     value.set_path(&["-5"], new_value, true).unwrap().to_string(),
+    ///     r#"["x",{"a":1},2]"#
+    /// );
+    /// ```
+    pub fn set_path(
+        self,
+        path: &[&str],
+        new_value: ValueRef<'_>,
+        create_missing: bool,
+    ) -> Result<Value, Error> {
+        let op = if create_missing {
+            PathOp::Create
+        } else {
+            PathOp::Replace
+        };
+        self.update_path(path, new_value, op)
+    }
+
+    /// Inserts a value at the path, as PostgreSQL's `jsonb_insert`.
+    ///
+    /// If the path addresses an array element, the new value is inserted before it, or after it
+    /// if `insert_after` is `true`. An index out of range inserts at the start or end of the
+    /// array. If the path addresses a missing key of an object, the new value is added with that
+    /// key. Returns an error if the key already exists, if the value is a scalar, or if an element
+    /// of the path addressing an array is not an integer.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [0, 1, 2]}"#.parse().unwrap();
+    /// let value = value.as_ref();
+    /// let new_value = Value::from("x");
+    /// let new_value = new_value.as_ref();
+    /// assert_eq!(
+    ///     value.insert_path(&["a", "1"], new_value, false).unwrap().to_string(),
+    ///     r#"{"a":[0,"x",1,2]}"#
+    /// );
+    /// assert_eq!(
+    ///     value.insert_path(&["a", "1"], new_value, true).unwrap().to_string(),
+    ///     r#"{"a":[0,1,"x",2]}"#
+    /// );
+    /// assert!(value.insert_path(&["a"], new_value, false).is_err());
+    /// ```
+    pub fn insert_path(
+        self,
+        path: &[&str],
+        new_value: ValueRef<'_>,
+        insert_after: bool,
+    ) -> Result<Value, Error> {
+        let op = if insert_after {
+            PathOp::InsertAfter
+        } else {
+            PathOp::InsertBefore
+        };
+        self.update_path(path, new_value, op)
+    }
+
+    /// Concatenates two values, as PostgreSQL's `||` operator.
+    ///
+    /// Two objects are merged shallowly, with the values of the other object replacing those of
+    /// the same keys. Otherwise the values are concatenated into an array, where an array
+    /// contributes its elements and any other value is an element by itself.
+    ///
+    /// Objects with the same key order are merged in one pass over their sorted entries. The
+    /// elements of arrays are copied at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let a: Value = r#"{"a": 1, "b": {"c": 2}}"#.parse().unwrap();
+    /// let b: Value = r#"{"b": {"d": 3}, "e": 4}"#.parse().unwrap();
+    /// assert_eq!(a.as_ref().concat(b.as_ref()).to_string(), r#"{"a":1,"b":{"d":3},"e":4}"#);
+    ///
+    /// let a: Value = r#"[1, 2]"#.parse().unwrap();
+    /// let b: Value = r#"[3]"#.parse().unwrap();
+    /// assert_eq!(a.as_ref().concat(b.as_ref()).to_string(), "[1,2,3]");
+    /// assert_eq!(a.as_ref().concat(Value::from(3).as_ref()).to_string(), "[1,2,3]");
+    /// assert_eq!(Value::from(1).as_ref().concat(Value::null().as_ref()).to_string(), "[1,null]");
+    /// ```
+    pub fn concat(self, other: ValueRef<'_>) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        match (self, other) {
+            (ValueRef::RawJson(raw), _) => return raw.value().concat(other),
+            (_, ValueRef::RawJson(raw)) => return self.concat(raw.value()),
+            (ValueRef::Object(a), ValueRef::Object(b)) => merge_objects_into(&mut builder, a, b),
+            _ => {
+                builder.begin_array();
+                for value in [self, other] {
+                    match value {
+                        ValueRef::Array(array) => builder.add_array_elements(array),
+                        _ => builder.add_value(value),
+                    }
+                }
+                builder.end_array();
+            }
+        }
+        builder.finish()
+    }
+
+    /// Applies a JSON merge patch to the value, as defined in [RFC 7396].
+    ///
+    /// If the patch is an object, its members are merged into the value recursively, where a
+    /// null member removes the key. A value that is not an object is treated as an empty
+    /// object. If the patch is not an object, it replaces the value.
+    ///
+    /// [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": "b", "c": {"d": "e", "f": "g"}}"#.parse().unwrap();
+    /// let patch: Value = r#"{"a": "z", "c": {"f": null}}"#.parse().unwrap();
+    /// assert_eq!(
+    ///     value.as_ref().merge_patch(patch.as_ref()).to_string(),
+    ///     r#"{"a":"z","c":{"d":"e"}}"#
+    /// );
+    /// ```
+    pub fn merge_patch(self, patch: ValueRef<'_>) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        merge_patch_into(&mut builder, Some(self), patch);
+        builder.finish()
+    }
+
+    /// Returns the JSON merge patch that turns the value into the other value, as defined in
+    /// [RFC 7396].
+    ///
+    /// Keys missing from the other object are removed with null, and keys with different values
+    /// are diffed recursively if both values are objects, or replaced otherwise. If either value
+    /// is not an object, the patch is the other value itself.
+    ///
+    /// Since null removes a key, members with a null value in the other value can not be
+    /// restored by the patch.
+    ///
+    /// [RFC 7396]: https://www.rfc-editor.org/rfc/rfc7396
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{Value, ValueRef};
+    ///
+    /// let a: Value = r#"{"a": 1, "b": {"c": 2, "d": 3}}"#.parse().unwrap();
+    /// let b: Value = r#"{"b": {"c": 2, "d": 4}, "e": 5}"#.parse().unwrap();
+    /// let patch = ValueRef::merge_patch_diff(a.as_ref(), b.as_ref());
+    /// assert_eq!(patch.to_string(), r#"{"a":null,"b":{"d":4},"e":5}"#);
+    /// assert_eq!(a.as_ref().merge_patch(patch.as_ref()), b);
+    /// ```
+    pub fn merge_patch_diff(self, other: ValueRef<'_>) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        merge_patch_diff_into(&mut builder, self, other);
+        builder.finish()
+    }
+
+    fn update_path(
+        self,
+        path: &[&str],
+        new_value: ValueRef<'_>,
+        op: PathOp,
+    ) -> Result<Value, Error> {
+        match self {
+            ValueRef::Object(_) | ValueRef::Array(_) => {}
+            ValueRef::RawJson(raw) => return raw.value().update_path(path, new_value, op),
+            _ => return Err(operator_error("cannot set path in scalar")),
+        }
+        let mut builder = Builder::<Vec<u8>>::new();
+        update_path_into(&mut builder, self, path, 0, new_value, op)?;
+        Ok(builder.finish())
+    }
+}
+
+/// How [`update_path_into`] changes the value at the end of the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathOp {
+    /// Remove the value.
+    Delete,
+    /// Replace the value if it exists.
+    Replace,
+    /// Replace the value, or add it if the last element of the path is missing.
+    Create,
+    /// Insert the value before an array element, or add it to an object.
+    InsertBefore,
+    /// Insert the value after an array element, or add it to an object.
+    InsertAfter,
+}
+
+impl PathOp {
+    /// Returns `true` if the value is added when the last element of the path is missing.
+    fn creates(self) -> bool {
+        matches!(
+            self,
+            PathOp::Create | PathOp::InsertBefore | PathOp::InsertAfter
+        )
+    }
+}
+
+/// Adds the value updated at `path[depth..]` to the builder, as PostgreSQL's `setPath`.
+///
+/// Only the containers along the path are rebuilt. Their other children are copied as is, so
+/// the cost depends on the depth of the path and the width of the containers on it, but not on
+/// the size of the subtrees.
+fn update_path_into(
+    builder: &mut Builder,
+    value: ValueRef<'_>,
+    path: &[&str],
+    depth: usize,
+    new_value: ValueRef<'_>,
+    op: PathOp,
+) -> Result<(), Error> {
+    let Some(&key) = path.get(depth) else {
+        builder.add_value(value);
+        return Ok(());
+    };
+    let last = depth + 1 == path.len();
+    match value {
+        ValueRef::Object(object) => {
+            let mut found = false;
+            builder.begin_object();
+            for (k, v) in object.iter() {
+                if found || k != key {
+                    builder.add_string(k);
+                    builder.add_value(v);
+                    continue;
+                }
+                found = true;
+                if !last {
+                    builder.add_string(k);
+                    update_path_into(builder, v, path, depth + 1, new_value, op)?;
+                } else if matches!(op, PathOp::InsertBefore | PathOp::InsertAfter) {
+                    return Err(operator_error("cannot replace existing key"));
+                } else if op != PathOp::Delete {
+                    builder.add_string(k);
+                    builder.add_value(new_value);
+                }
+            }
+            if !found && last && op.creates() {
+                builder.add_string(key);
+                builder.add_value(new_value);
+            }
+            builder.end_object_like(object);
+        }
+        ValueRef::Array(array) => {
+            let index = path_index(key, depth)?;
+            let target = array_index(array, index);
+            let create = target.is_none() && last && op.creates();
+            builder.begin_array();
+            if create && index < 0 {
+                builder.add_value(new_value);
+            }
+            for (i, element) in array.iter().enumerate() {
+                if Some(i) != target {
+                    builder.add_value(element);
+                    continue;
+                }
+                match op {
+                    _ if !last => {
+                        update_path_into(builder, element, path, depth + 1, new_value, op)?
+                    }
+                    PathOp::Delete => {}
+                    PathOp::Replace | PathOp::Create => builder.add_value(new_value),
+                    PathOp::InsertBefore => {
+                        builder.add_value(new_value);
+                        builder.add_value(element);
+                    }
+                    PathOp::InsertAfter => {
+                        builder.add_value(element);
+                        builder.add_value(new_value);
+                    }
+                }
+            }
+            if create && index >= 0 {
+                builder.add_value(new_value);
+            }
+            builder.end_array();
+        }
+        ValueRef::RawJson(raw) => {
+            update_path_into(builder, raw.value(), path, depth, new_value, op)?
+        }
+        _ => builder.add_value(value),
+    }
+    Ok(())
+}
+
+/// Adds the entries of both objects to the builder, with those of `b` replacing those of `a`.
+fn merge_objects_into(builder: &mut Builder, a: ObjectRef<'_>, b: ObjectRef<'_>) {
+    builder.begin_object();
+    let order = a.key_order();
+    if order != b.key_order() || a.preserves_order() || b.preserves_order() {
+        for (k, v) in a.iter().chain(b.iter()) {
+            builder.add_string(k);
+            builder.add_value(v);
+        }
+        builder.end_object_like(a);
+        return;
+    }
+    for (k, va, vb) in merge_join(a, b) {
+        builder.add_string(k);
+        builder.add_value(vb.or(va).unwrap());
+    }
+    builder.end_sorted_object(order);
+}
+
+/// Adds the target with the merge patch applied to the builder, where `None` is a missing target.
+fn merge_patch_into(builder: &mut Builder, target: Option<ValueRef<'_>>, patch: ValueRef<'_>) {
+    let patch = match patch {
+        ValueRef::Object(patch) => patch,
+        ValueRef::RawJson(raw) => return merge_patch_into(builder, target, raw.value()),
+        _ => return builder.add_value(patch),
+    };
+    let target = match target {
+        Some(ValueRef::Object(target)) => Some(target),
+        Some(ValueRef::RawJson(raw)) => {
+            return merge_patch_into(builder, Some(raw.value()), ValueRef::Object(patch))
+        }
+        _ => None,
+    };
+    builder.begin_object();
+    match target {
+        Some(target) if target.key_order() == patch.key_order() && !target.preserves_order() => {
+            for (k, value, patch) in merge_join(target, patch) {
+                add_patched_entry(builder, k, value, patch);
+            }
+            builder.end_sorted_object(target.key_order());
+        }
+        Some(target) => {
+            for (k, v) in target.iter() {
+                add_patched_entry(builder, k, Some(v), patch.get(k));
+            }
+            for (k, v) in patch.iter().filter(|(k, _)| !target.contains_key(k)) {
+                add_patched_entry(builder, k, None, Some(v));
+            }
+            builder.end_object_like(target);
+        }
+        None => {
+            for (k, v) in patch.iter() {
+                add_patched_entry(builder, k, None, Some(v));
+            }
+            builder.end_object_like(patch);
+        }
+    }
+}
+
+/// Adds an entry of the target with the member of the patch applied to the builder.
+fn add_patched_entry(
+    builder: &mut Builder,
+    key: &str,
+    value: Option<ValueRef<'_>>,
+    patch: Option<ValueRef<'_>>,
+) {
+    match (value, patch) {
+        (_, Some(patch)) if patch.is_null() => {}
+        (_, Some(patch)) => {
+            builder.add_string(key);
+            merge_patch_into(builder, value, patch);
+        }
+        (Some(value), None) => {
+            builder.add_string(key);
+            builder.add_value(value);
+        }
+        (None, None) => {}
+    }
+}
+
+/// Adds the merge patch that turns `a` into `b` to the builder.
+fn merge_patch_diff_into(builder: &mut Builder, a: ValueRef<'_>, b: ValueRef<'_>) {
+    let (a, b) = match (a, b) {
+        (ValueRef::RawJson(raw), _) => return merge_patch_diff_into(builder, raw.value(), b),
+        (_, ValueRef::RawJson(raw)) => return merge_patch_diff_into(builder, a, raw.value()),
+        (ValueRef::Object(a), ValueRef::Object(b)) => (a, b),
+        _ => return builder.add_value(b),
+    };
+    builder.begin_object();
+    let order = b.key_order();
+    if order == a.key_order() && !b.preserves_order() {
+        for (k, va, vb) in merge_join(a, b) {
+            match vb {
+                Some(vb) => add_diff_entry(builder, k, va, vb),
+                None => add_removed_entry(builder, k),
+            }
+        }
+        builder.end_sorted_object(order);
+    } else {
+        for (k, vb) in b.iter() {
+            add_diff_entry(builder, k, a.get(k), vb);
+        }
+        for (k, _) in a.iter().filter(|(k, _)| !b.contains_key(k)) {
+            add_removed_entry(builder, k);
+        }
+        builder.end_object_like(b);
+    }
+}
+
+/// Adds an entry of the merge patch that turns the value of `a` into the value of `b`.
+fn add_diff_entry(builder: &mut Builder, key: &str, a: Option<ValueRef<'_>>, b: ValueRef<'_>) {
+    match a {
+        Some(a) if a == b => {}
+        Some(a) if a.is_object() && b.is_object() => {
+            builder.add_string(key);
+            merge_patch_diff_into(builder, a, b);
+        }
+        _ => {
+            builder.add_string(key);
+            builder.add_value(b);
+        }
+    }
+}
+
+/// Adds an entry of the merge patch that removes the key.
+fn add_removed_entry(builder: &mut Builder, key: &str) {
+    builder.add_string(key);
+    builder.add_null();
+}
+
+/// Parses an element of a path that addresses an array.
+fn path_index(key: &str, depth: usize) -> Result<i64, Error> {
+    strtoint(key).map(i64::from).ok_or_else(|| {
+        operator_error(format!(
+            "path element at position {} is not an integer: \"{key}\"",
+            depth + 1
+        ))
+    })
+}
+
+/// Parses an integer as PostgreSQL's `strtoint` with nothing left over: after optional
+/// leading whitespace and a sign, the text must be all digits and fit in an `i32`.
+fn strtoint(key: &str) -> Option<i32> {
+    let key = key.trim_start_matches([' ', '\t', '\n', '\x0b', '\x0c', '\r']);
+    let digits = key.strip_prefix(['+', '-']).unwrap_or(key);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    key.parse().ok()
+}
+
+/// Resolves a possibly negative index into the array.
+fn array_index(array: ArrayRef<'_>, index: i64) -> Option<usize> {
+    let index = if index < 0 {
+        (array.len() as i64).checked_add(index)?
+    } else {
+        index
+    };
+    usize::try_from(index).ok().filter(|&i| i < array.len())
+}
+
+fn operator_error(message: impl Into<String>) -> Error {
+    Error::Operator {
+        message: message.into(),
+    }
+}
+
+/// Returns `true` if the value is a string equal to the key.
+fn is_key(value: ValueRef<'_>, key: &str) -> bool {
+    match value {
+        ValueRef::String(s) => s.as_str() == key,
+        ValueRef::RawJson(raw) => raw.value().as_str() == Some(key),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the keys are sorted in the key order of the object.
+fn is_sorted_for(object: ObjectRef<'_>, keys: &[&str]) -> bool {
+    let order = object.key_order();
+    keys.is_sorted_by(|a, b| order.cmp(a, b) != Ordering::Greater)
+}
+
+/// Containment below the top level, where values of different types never contain each other.
+fn deep_contains(value: ValueRef<'_>, other: ValueRef<'_>) -> bool {
+    match (value, other) {
+        (ValueRef::RawJson(raw), _) => deep_contains(raw.value(), other),
+        (_, ValueRef::RawJson(raw)) => deep_contains(value, raw.value()),
+        (ValueRef::Object(a), ValueRef::Object(b)) => object_contains(a, b),
+        (ValueRef::Array(a), ValueRef::Array(b)) => array_contains(a, b),
+        (ValueRef::Object(_) | ValueRef::Array(_), _)
+        | (_, ValueRef::Object(_) | ValueRef::Array(_)) => false,
+        (a, b) => a == b,
+    }
+}
+
+/// Returns `true` if every element of `b` is contained in some element of `a`.
+fn array_contains(a: ArrayRef<'_>, b: ArrayRef<'_>) -> bool {
+    b.iter().all(|y| a.iter().any(|x| deep_contains(x, y)))
+}
+
+/// Returns `true` if `a` has every key of `b` with a value containing the value in `b`.
+fn object_contains(a: ObjectRef<'_>, b: ObjectRef<'_>) -> bool {
+    if a.len() < b.len() {
+        return false;
+    }
+    if a.key_order() != b.key_order() {
+        return b
+            .iter()
+            .all(|(k, v)| a.get(k).is_some_and(|x| deep_contains(x, v)));
+    }
+    merge_join(a, b).all(|(_, x, v)| v.is_none_or(|v| x.is_some_and(|x| deep_contains(x, v))))
+}
+
+/// Walks the entries of two objects with the same key order in one pass, yielding each key with
+/// its value in `a` and in `b`.
+fn merge_join<'a>(
+    a: ObjectRef<'a>,
+    b: ObjectRef<'a>,
+) -> impl Iterator<Item = (&'a str, Option<ValueRef<'a>>, Option<ValueRef<'a>>)> {
+    let order = a.key_order();
+    let mut a = a.stored_iter().peekable();
+    let mut b = b.stored_iter().peekable();
+    std::iter::from_fn(move || {
+        let ord = match (a.peek(), b.peek()) {
+            (Some((ka, _)), Some((kb, _))) => order.cmp(ka, kb),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+        Some(match ord {
+            Ordering::Less => a.next().map(|(k, v)| (k, Some(v), None))?,
+            Ordering::Equal => a
+                .next()
+                .map(|(k, v)| (k, Some(v), b.next().map(|(_, v)| v)))?,
+            Ordering::Greater => b.next().map(|(k, v)| (k, None, Some(v)))?,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use serde::de::DeserializeSeed;
+
+    use crate::{Builder, KeyOrder, Value, ValueRef};
+
+    fn contains(a: &str, b: &str) -> bool {
+        let a: Value = a.parse().unwrap();
+        let b: Value = b.parse().unwrap();
+        a.as_ref().contains(b.as_ref())
+    }
+
+    #[test]
+    fn contains_scalars() {
+        assert!(contains("1", "1"));
+        assert!(contains("1", "1.0"));
+        assert!(contains(r#""a""#, r#""a""#));
+        assert!(contains("null", "null"));
+        assert!(!contains("1", "2"));
+        assert!(!contains("1", r#""1""#));
+        assert!(!contains("true", "[true]"));
+        assert!(!contains(r#"{"a": 1}"#, "1"));
+    }
+
+    #[test]
+    fn contains_arrays() {
+        assert!(contains("[1, 2, 3]", "[3, 1]"));
+        assert!(contains("[1, 2, 3]", "[1, 1, 1]"));
+        assert!(contains("[1, 2]", "[]"));
+        assert!(contains("[]", "[]"));
+        assert!(!contains("[]", "[1]"));
+        assert!(!contains("[1, 2]", "[1, 3]"));
+        assert!(contains("[1, [2, 3]]", "[[3]]"));
+        assert!(contains(r#"[{"a": 1, "b": 2}]"#, r#"[{"a": 1}]"#));
+        assert!(!contains("[1, [2, 3]]", "[3]"));
+        assert!(!contains("[[1]]", "[1]"));
+        assert!(!contains("[1]", "[[1]]"));
+        // scalars are contained in top-level arrays only
+        assert!(contains(r#"["foo", "bar"]"#, r#""foo""#));
+        assert!(contains("[1]", "1.0"));
+        assert!(!contains("[[1]]", "1"));
+        assert!(!contains(r#"{"a": [1]}"#, r#"{"a": 1}"#));
+    }
+
+    #[test]
+    fn contains_objects() {
+        let value = r#"{"a": 1, "b": {"c": [1, 2], "d": null}, "e": "f"}"#;
+        assert!(contains(value, "{}"));
+        assert!(contains(value, r#"{"a": 1.0, "e": "f"}"#));
+        assert!(contains(value, r#"{"b": {"c": [2]}}"#));
+        assert!(contains(value, r#"{"b": {}}"#));
+        assert!(!contains(value, r#"{"b": {"c": 2}}"#));
+        assert!(!contains(value, r#"{"a": 2}"#));
+        assert!(!contains(value, r#"{"x": 1}"#));
+        assert!(!contains(value, r#"{"a": 1, "z": 1}"#));
+        assert!(!contains(value, r#"{"b": []}"#));
+        assert!(!contains("{}", r#"{"a": 1}"#));
+        assert!(!contains(value, "[]"));
+
+        let b: Value = r#"{"a": 1, "e": "f"}"#.parse().unwrap();
+        assert!(b
+            .as_ref()
+            .contained_by(value.parse::<Value>().unwrap().as_ref()));
+    }
+
+    #[test]
+    fn contains_key_orders() {
+        let a = parse_with(
+            r#"{"bb": 1, "a": 2, "ccc": {"x": 1, "yy": 2}}"#,
+            KeyOrder::LengthFirst,
+            false,
+        );
+        let b = parse_with(
+            r#"{"ccc": {"yy": 2}, "bb": 1}"#,
+            KeyOrder::LengthFirst,
+            false,
+        );
+        let c = parse_with(
+            r#"{"ccc": {"yy": 2}, "bb": 1}"#,
+            KeyOrder::Lexicographic,
+            false,
+        );
+        let d = parse_with(r#"{"bb": 1, "zz": 1}"#, KeyOrder::LengthFirst, false);
+        assert!(a.as_ref().contains(b.as_ref()));
+        assert!(a.as_ref().contains(c.as_ref()));
+        assert!(!a.as_ref().contains(d.as_ref()));
+        assert!(!b.as_ref().contains(a.as_ref()));
+    }
+
+    #[test]
+    fn contains_raw_json() {
+        let value = raw_array(&[r#"{"a": [1, 2]}"#, "3"]);
+        let other: Value = r#"[{"a": [2]}, 3]"#.parse().unwrap();
+        assert!(value.as_ref().contains(other.as_ref()));
+        assert!(value.as_ref().contains(Value::from(3).as_ref()));
+    }
+
+    #[test]
+    fn exists() {
+        let object: Value = r#"{"a": 1, "b": {"c": 2}, "": null}"#.parse().unwrap();
+        let object = object.as_ref();
+        assert!(object.exists("a"));
+        assert!(object.exists("b"));
+        assert!(object.exists(""));
+        assert!(!object.exists("c"));
+
+        let array: Value = r#"["a", 1, "1", ["b"], {"c": 1}]"#.parse().unwrap();
+        let array = array.as_ref();
+        assert!(array.exists("a"));
+        assert!(array.exists("1"));
+        assert!(!array.exists("b"));
+        assert!(!array.exists("c"));
+
+        assert!(Value::from("a").as_ref().exists("a"));
+        assert!(!Value::from("a").as_ref().exists("b"));
+        assert!(!Value::from(1).as_ref().exists("1"));
+        assert!(!Value::null().as_ref().exists("null"));
+    }
+
+    #[test]
+    fn exists_any_all() {
+        let object: Value = r#"{"a": 1, "b": 2, "d": 3}"#.parse().unwrap();
+        let object = object.as_ref();
+        // sorted keys
+        assert!(object.exists_any(&["a", "c"]));
+        assert!(object.exists_any(&["c", "d"]));
+        assert!(!object.exists_any(&["c", "e"]));
+        assert!(!object.exists_any(&[]));
+        assert!(object.exists_all(&["a", "b", "d"]));
+        assert!(object.exists_all(&["a", "a", "d"]));
+        assert!(!object.exists_all(&["a", "c"]));
+        assert!(!object.exists_all(&["d", "e"]));
+        assert!(object.exists_all(&[]));
+        // unsorted keys
+        assert!(object.exists_any(&["e", "d"]));
+        assert!(!object.exists_any(&["e", "c"]));
+        assert!(object.exists_all(&["d", "a"]));
+        assert!(!object.exists_all(&["d", "c"]));
+
+        let array: Value = r#"["a", "b", 1]"#.parse().unwrap();
+        let array = array.as_ref();
+        assert!(array.exists_any(&["x", "b"]));
+        assert!(!array.exists_any(&["x", "1"]));
+        assert!(array.exists_all(&["b", "a"]));
+        assert!(!array.exists_all(&["a", "1"]));
+        assert!(array.exists_all(&[]));
+
+        assert!(!Value::from(1).as_ref().exists_any(&["1"]));
+        assert!(Value::from(1).as_ref().exists_all(&[]));
+    }
+
+    #[test]
+    fn exists_key_orders() {
+        let json = r#"{"bb": 1, "a": 2, "ccc": 3, "d": 4}"#;
+        let object = parse_with(json, KeyOrder::LengthFirst, false);
+        let object = object.as_ref();
+        // sorted by length first
+        assert!(object.exists_all(&["a", "d", "bb", "ccc"]));
+        assert!(!object.exists_all(&["a", "d", "bb", "cc"]));
+        assert!(object.exists_any(&["b", "x", "ccc"]));
+        assert!(!object.exists_any(&["b", "x", "cc"]));
+        // sorted lexicographically
+        assert!(object.exists_all(&["a", "bb", "ccc", "d"]));
+        assert!(object.exists_any(&["b", "ccc"]));
+    }
+
+    #[test]
+    fn exists_raw_json() {
+        let value = raw_array(&[r#""a""#, r#"{"b": 1}"#]);
+        assert!(value.as_ref().exists("a"));
+        assert!(!value.as_ref().exists("b"));
+        assert!(value.as_ref().exists_all(&["a"]));
+
+        let value = raw(r#"{"b": 1, "c": 2}"#);
+        assert!(value.as_ref().exists("b"));
+        assert!(value.as_ref().exists_any(&["a", "c"]));
+        assert!(!value.as_ref().exists_all(&["a", "c"]));
+    }
+
+    #[test]
+    fn get_path() {
+        let value: Value = r#"{"a": [1, {"b": [true]}, "x"], "": {"1": 2}}"#.parse().unwrap();
+        let value = value.as_ref();
+        let get = |path: &[&str]| value.get_path(path).map(|v| v.to_string());
+        assert_eq!(get(&[]), Some(value.to_string()));
+        assert_eq!(get(&["a", "0"]).as_deref(), Some("1"));
+        assert_eq!(get(&["a", "1", "b", "0"]).as_deref(), Some("true"));
+        assert_eq!(get(&["a", "-1"]).as_deref(), Some(r#""x""#));
+        assert_eq!(get(&["a", "-3"]).as_deref(), Some("1"));
+        assert_eq!(get(&["a", "+1", "b"]).as_deref(), Some("[true]"));
+        assert_eq!(get(&["", "1"]).as_deref(), Some("2"));
+        assert_eq!(get(&["a", "-4"]), None);
+        assert_eq!(get(&["a", "3"]), None);
+        assert_eq!(get(&["a", "b"]), None);
+        assert_eq!(get(&["a", ""]), None);
+        assert_eq!(get(&["a", "0", "0"]), None);
+        assert_eq!(get(&["b"]), None);
+        assert_eq!(get(&["a", "99999999999999999999"]), None);
+        assert_eq!(get(&["a", "-9223372036854775808"]), None);
+        // indices are parsed as PostgreSQL's `strtoint`
+        assert_eq!(get(&["a", " \t\n1", "b"]).as_deref(), Some("[true]"));
+        assert_eq!(get(&["a", " -1"]).as_deref(), Some(r#""x""#));
+        assert_eq!(get(&["a", "0001"]).as_deref(), Some(r#"{"b":[true]}"#));
+        assert_eq!(get(&["a", "1 "]), None);
+        assert_eq!(get(&["a", " "]), None);
+        assert_eq!(get(&["a", "- 1"]), None);
+        assert_eq!(get(&["a", "+-1"]), None);
+        assert_eq!(get(&["a", "2147483648"]), None);
+        assert_eq!(get(&["a", "-2147483649"]), None);
+    }
+
+    #[test]
+    fn get_text() {
+        let value: Value =
+            r#"{"s": "a\nb", "n": -1.5e2, "b": false, "z": null, "o": {"k": [1, {}]}}"#
+                .parse()
+                .unwrap();
+        let value = value.as_ref();
+        assert!(matches!(value.get_text("s"), Some(Cow::Borrowed("a\nb"))));
+        assert_eq!(value.get_text("n").as_deref(), Some("-150"));
+        assert_eq!(value.get_text("b").as_deref(), Some("false"));
+        assert_eq!(value.get_text("z"), None);
+        assert_eq!(value.get_text("x"), None);
+        assert_eq!(value.get_text("o").as_deref(), Some(r#"{"k": [1, {}]}"#));
+        assert_eq!(value.get_path_text(&["o", "k", "0"]).as_deref(), Some("1"));
+        assert_eq!(
+            value.get_path_text(&["o", "k", "-1"]).as_deref(),
+            Some("{}")
+        );
+        assert_eq!(value.get_path_text(&["o", "k", "2"]), None);
+
+        let array: Value = r#"["a", null]"#.parse().unwrap();
+        assert_eq!(array.as_ref().get_text(0).as_deref(), Some("a"));
+        assert_eq!(array.as_ref().get_text(1), None);
+        assert_eq!(Value::null().as_ref().get_path_text(&[]), None);
+    }
+
+    #[test]
+    fn get_text_raw_json() {
+        let value = raw_array(&[r#""a\u0062""#, "null", r#"{"b":1}"#]);
+        let value = value.as_ref();
+        assert_eq!(value.get_text(0).as_deref(), Some("ab"));
+        assert_eq!(value.get_text(1), None);
+        assert_eq!(value.get_text(2).as_deref(), Some(r#"{"b": 1}"#));
+        assert_eq!(value.get_path_text(&["2", "b"]).as_deref(), Some("1"));
+    }
+
+    fn json(s: &str) -> Value {
+        s.parse().unwrap()
+    }
+
+    /// Parses a value whose objects have the given key order and keep their insertion order if
+    /// `preserve` is set.
+    fn parse_with(json: &str, order: KeyOrder, preserve: bool) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new()
+            .with_key_order(order)
+            .with_preserve_order(preserve);
+        builder
+            .deserialize(&mut serde_json::Deserializer::from_str(json))
+            .unwrap();
+        builder.finish()
+    }
+
+    /// Builds a raw JSON value.
+    fn raw(text: &str) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.add_raw_json(text).unwrap();
+        builder.finish()
+    }
+
+    /// Builds an array of raw JSON elements.
+    fn raw_array(texts: &[&str]) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        for text in texts {
+            builder.add_raw_json(text).unwrap();
+        }
+        builder.end_array();
+        builder.finish()
+    }
+
+    /// Builds an object of raw JSON members.
+    fn raw_object(members: &[(&str, &str)]) -> Value {
+        let mut builder = Builder::<Vec<u8>>::new();
+        builder.begin_object();
+        for (key, text) in members {
+            builder.add_string(key);
+            builder.add_raw_json(text).unwrap();
+        }
+        builder.end_object();
+        builder.finish()
+    }
+
+    #[test]
+    fn remove_keys() {
+        let object = json(r#"{"a": 1, "b": {"a": 2}, "c": 3}"#);
+        let object = object.as_ref();
+        assert_eq!(
+            object.remove_key("a").unwrap(),
+            json(r#"{"b": {"a": 2}, "c": 3}"#)
+        );
+        assert_eq!(object.remove_key("x").unwrap(), object.to_owned());
+        assert_eq!(
+            object.remove_keys(&["c", "a", "c"]).unwrap(),
+            json(r#"{"b": {"a": 2}}"#)
+        );
+        assert_eq!(object.remove_keys(&["a", "b", "c"]).unwrap(), json("{}"));
+        assert_eq!(object.remove_keys(&[]).unwrap(), object.to_owned());
+
+        let array = json(r#"["a", 1, "b", ["a"], "a"]"#);
+        let array = array.as_ref();
+        assert_eq!(array.remove_key("a").unwrap(), json(r#"[1, "b", ["a"]]"#));
+        assert_eq!(array.remove_key("1").unwrap(), array.to_owned());
+        assert_eq!(
+            array.remove_keys(&["a", "b"]).unwrap(),
+            json(r#"[1, ["a"]]"#)
+        );
+
+        let error = json("1").as_ref().remove_key("a").unwrap_err();
+        assert_eq!(error.to_string(), "cannot delete from scalar");
+        assert!(json(r#""a""#).as_ref().remove_keys(&["a"]).is_err());
+    }
+
+    #[test]
+    fn remove_keys_layout() {
+        let json = r#"{"ccc": 1, "a": 2, "bb": 3, "d": 4}"#;
+        let value = parse_with(json, KeyOrder::LengthFirst, true);
+        let removed = value.as_ref().remove_keys(&["bb", "a"]).unwrap();
+        let object = removed.as_object().unwrap();
+        assert_eq!(object.key_order(), KeyOrder::LengthFirst);
+        assert!(object.preserves_order());
+        assert_eq!(removed.to_string(), r#"{"ccc":1,"d":4}"#);
+        assert_eq!(object.get("d").unwrap().as_u64(), Some(4));
+    }
+
+    #[test]
+    fn remove_index() {
+        let array = json("[1, 2, 3]");
+        let array = array.as_ref();
+        assert_eq!(array.remove_index(1).unwrap(), json("[1, 3]"));
+        assert_eq!(array.remove_index(-3).unwrap(), json("[2, 3]"));
+        assert_eq!(array.remove_index(3).unwrap(), json("[1, 2, 3]"));
+        assert_eq!(array.remove_index(-4).unwrap(), json("[1, 2, 3]"));
+        assert_eq!(array.remove_index(i64::MIN).unwrap(), json("[1, 2, 3]"));
+        assert_eq!(json("[]").as_ref().remove_index(0).unwrap(), json("[]"));
+
+        let error = json(r#"{"0": 1}"#).as_ref().remove_index(0).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot delete from object using integer index"
+        );
+        assert!(json("null").as_ref().remove_index(0).is_err());
+    }
+
+    #[test]
+    fn remove_path() {
+        let value = json(r#"{"a": [1, {"b": 2, "c": [3, 4]}], "d": {"e": null}}"#);
+        let value = value.as_ref();
+        let remove = |path: &[&str]| value.remove_path(path).unwrap();
+        assert_eq!(remove(&[]), value.to_owned());
+        assert_eq!(remove(&["d"]), json(r#"{"a": [1, {"b": 2, "c": [3, 4]}]}"#));
+        assert_eq!(
+            remove(&["d", "e"]),
+            json(r#"{"a": [1, {"b": 2, "c": [3, 4]}], "d": {}}"#)
+        );
+        assert_eq!(
+            remove(&["a", "1", "c", "-2"]),
+            json(r#"{"a": [1, {"b": 2, "c": [4]}], "d": {"e": null}}"#)
+        );
+        assert_eq!(
+            remove(&["a", "-2"]),
+            json(r#"{"a": [{"b": 2, "c": [3, 4]}], "d": {"e": null}}"#)
+        );
+        // missing paths leave the value unchanged
+        assert_eq!(remove(&["x"]), value.to_owned());
+        assert_eq!(remove(&["a", "5"]), value.to_owned());
+        assert_eq!(remove(&["a", "0", "x"]), value.to_owned());
+        assert_eq!(remove(&["d", "e", "f"]), value.to_owned());
+
+        let error = value.remove_path(&["a", "1", "c", "x"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"path element at position 4 is not an integer: "x""#
+        );
+        let error = json("1").as_ref().remove_path(&["a"]).unwrap_err();
+        assert_eq!(error.to_string(), "cannot delete path in scalar");
+    }
+
+    #[test]
+    fn remove_raw_json() {
+        let value = raw_object(&[("a", r#"{"b": 1, "c": [2, 3]}"#), ("d", "[4]")]);
+        let value = value.as_ref();
+        assert_eq!(
+            value.remove_path(&["a", "c", "0"]).unwrap(),
+            json(r#"{"a": {"b": 1, "c": [3]}, "d": [4]}"#)
+        );
+        assert_eq!(value.remove_key("a").unwrap(), json(r#"{"d": [4]}"#));
+        // `#>` looks into raw JSON as `#-` does
+        assert_eq!(value.get_path(&["a", "c", "0"]).unwrap().as_u64(), Some(2));
+        assert_eq!(value.get_path(&["d", "-1"]).unwrap().as_u64(), Some(4));
+        assert_eq!(value.get_path_text(&["a", "c"]).as_deref(), Some("[2, 3]"));
+
+        let value = raw(r#"["a", 1]"#);
+        assert_eq!(value.as_ref().remove_key("a").unwrap(), json("[1]"));
+        assert_eq!(value.as_ref().remove_index(-1).unwrap(), json(r#"["a"]"#));
+    }
+
+    #[test]
+    fn set_path() {
+        let value = json(r#"{"a": [1, {"b": 2}], "c": {}}"#);
+        let value = value.as_ref();
+        let new_value = json(r#"{"x": [true]}"#);
+        let set = |path: &[&str], create| value.set_path(path, new_value.as_ref(), create).unwrap();
+        assert_eq!(set(&[], true), value.to_owned());
+        assert_eq!(
+            set(&["c"], false),
+            json(r#"{"a": [1, {"b": 2}], "c": {"x": [true]}}"#)
+        );
+        assert_eq!(
+            set(&["a", "1", "b"], false),
+            json(r#"{"a": [1, {"b": {"x": [true]}}], "c": {}}"#)
+        );
+        assert_eq!(
+            set(&["a", "-2"], false),
+            json(r#"{"a": [{"x": [true]}, {"b": 2}], "c": {}}"#)
+        );
+        // missing keys and indices
+        assert_eq!(set(&["d"], false), value.to_owned());
+        assert_eq!(
+            set(&["d"], true),
+            json(r#"{"a": [1, {"b": 2}], "c": {}, "d": {"x": [true]}}"#)
+        );
+        assert_eq!(
+            set(&["c", "e"], true),
+            json(r#"{"a": [1, {"b": 2}], "c": {"e": {"x": [true]}}}"#)
+        );
+        assert_eq!(set(&["d", "e"], true), value.to_owned());
+        assert_eq!(set(&["a", "5"], false), value.to_owned());
+        assert_eq!(
+            set(&["a", "5"], true),
+            json(r#"{"a": [1, {"b": 2}, {"x": [true]}], "c": {}}"#)
+        );
+        assert_eq!(
+            set(&["a", "-3"], true),
+            json(r#"{"a": [{"x": [true]}, 1, {"b": 2}], "c": {}}"#)
+        );
+        assert_eq!(set(&["a", "5", "b"], true), value.to_owned());
+        // scalars along the path are left unchanged
+        assert_eq!(set(&["a", "0", "b"], true), value.to_owned());
+
+        let error = value
+            .set_path(&["a", "b"], new_value.as_ref(), true)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"path element at position 2 is not an integer: "b""#
+        );
+        assert_eq!(
+            set(&["a", " 0"], false),
+            json(r#"{"a": [{"x": [true]}, {"b": 2}], "c": {}}"#)
+        );
+        let error = value
+            .set_path(&["a", "4294967296"], new_value.as_ref(), true)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            r#"path element at position 2 is not an integer: "4294967296""#
+        );
+        let error = json("1")
+            .as_ref()
+            .set_path(&[], new_value.as_ref(), true)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "cannot set path in scalar");
+
+        let empty = json("[]");
+        assert_eq!(
+            empty
+                .as_ref()
+                .set_path(&["-1"], json("1").as_ref(), true)
+                .unwrap(),
+            json("[1]")
+        );
+    }
+
+    #[test]
+    fn insert_path() {
+        let value = json(r#"{"a": [1, 2], "b": {"c": 3}}"#);
+        let value = value.as_ref();
+        let new_value = json(r#""x""#);
+        let insert = |path: &[&str], after| value.insert_path(path, new_value.as_ref(), after);
+        assert_eq!(
+            insert(&["a", "0"], false).unwrap(),
+            json(r#"{"a": ["x", 1, 2], "b": {"c": 3}}"#)
+        );
+        assert_eq!(
+            insert(&["a", "0"], true).unwrap(),
+            json(r#"{"a": [1, "x", 2], "b": {"c": 3}}"#)
+        );
+        assert_eq!(
+            insert(&["a", "-1"], true).unwrap(),
+            json(r#"{"a": [1, 2, "x"], "b": {"c": 3}}"#)
+        );
+        assert_eq!(
+            insert(&["a", "9"], false).unwrap(),
+            json(r#"{"a": [1, 2, "x"], "b": {"c": 3}}"#)
+        );
+        assert_eq!(
+            insert(&["a", "-9"], true).unwrap(),
+            json(r#"{"a": ["x", 1, 2], "b": {"c": 3}}"#)
+        );
+        assert_eq!(
+            insert(&["b", "d"], false).unwrap(),
+            json(r#"{"a": [1, 2], "b": {"c": 3, "d": "x"}}"#)
+        );
+        assert_eq!(insert(&["x", "d"], false).unwrap(), value.to_owned());
+
+        let error = insert(&["b", "c"], false).unwrap_err();
+        assert_eq!(error.to_string(), "cannot replace existing key");
+        assert!(json("null")
+            .as_ref()
+            .insert_path(&["0"], new_value.as_ref(), true)
+            .is_err());
+    }
+
+    #[test]
+    fn set_path_layout() {
+        let json = r#"{"ccc": {"zz": 1, "y": 2}, "a": 3}"#;
+        let value = parse_with(json, KeyOrder::LengthFirst, true);
+        let new_value = Value::from(4);
+        let updated = value
+            .as_ref()
+            .set_path(&["ccc", "x"], new_value.as_ref(), true)
+            .unwrap();
+        assert_eq!(updated.to_string(), r#"{"ccc":{"zz":1,"y":2,"x":4},"a":3}"#);
+        let inner = updated.get("ccc").unwrap().as_object().unwrap();
+        assert_eq!(inner.key_order(), KeyOrder::LengthFirst);
+        assert_eq!(inner.get("x").unwrap().as_u64(), Some(4));
+    }
+
+    #[test]
+    fn set_path_raw_json() {
+        let value = raw_array(&[r#"{"a": [1]}"#]);
+        let new_value = Value::from(2);
+        assert_eq!(
+            value
+                .as_ref()
+                .set_path(&["0", "a", "0"], new_value.as_ref(), false)
+                .unwrap(),
+            json(r#"[{"a": [2]}]"#)
+        );
+        assert_eq!(
+            value
+                .as_ref()
+                .insert_path(&["0", "a", "0"], new_value.as_ref(), true)
+                .unwrap(),
+            json(r#"[{"a": [1, 2]}]"#)
+        );
+    }
+
+    fn concat(a: &str, b: &str) -> Value {
+        json(a).as_ref().concat(json(b).as_ref())
+    }
+
+    #[test]
+    fn concat_objects() {
+        assert_eq!(
+            concat(r#"{"a": 1, "c": 3}"#, r#"{"b": 2, "d": 4}"#),
+            json(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#)
+        );
+        assert_eq!(
+            concat(r#"{"a": {"x": 1}, "b": 2}"#, r#"{"a": {"y": 2}}"#),
+            json(r#"{"a": {"y": 2}, "b": 2}"#)
+        );
+        assert_eq!(concat("{}", r#"{"a": 1}"#), json(r#"{"a": 1}"#));
+        assert_eq!(concat(r#"{"a": 1}"#, "{}"), json(r#"{"a": 1}"#));
+        assert_eq!(concat("{}", "{}"), json("{}"));
+
+        let value = concat(r#"{"b": "x", "a": [1]}"#, r#"{"c": null, "b": "y"}"#);
+        assert_eq!(value.to_string(), r#"{"a":[1],"b":"y","c":null}"#);
+        let object = value.as_object().unwrap();
+        assert_eq!(object.get("a").unwrap().to_string(), "[1]");
+        assert_eq!(object.get("b").unwrap().as_str(), Some("y"));
+        assert_eq!(object.get("c"), Some(ValueRef::Null));
+        assert_eq!(object.get("d"), None);
+    }
+
+    #[test]
+    fn concat_arrays() {
+        assert_eq!(
+            concat(r#"[1, "a", null]"#, r#"[true, {"b": [2]}, 1.5]"#),
+            json(r#"[1, "a", null, true, {"b": [2]}, 1.5]"#)
+        );
+        assert_eq!(concat("[]", "[1]"), json("[1]"));
+        assert_eq!(concat("[1]", "[]"), json("[1]"));
+        assert_eq!(concat("[]", "[]"), json("[]"));
+        assert_eq!(concat(r#"["a"]"#, r#""b""#), json(r#"["a", "b"]"#));
+        assert_eq!(concat("1", "[2, 3]"), json("[1, 2, 3]"));
+        assert_eq!(concat("1", "2"), json("[1, 2]"));
+        assert_eq!(concat("null", "null"), json("[null, null]"));
+        assert_eq!(concat(r#"{"a": 1}"#, "[2]"), json(r#"[{"a": 1}, 2]"#));
+        assert_eq!(concat("[1]", r#"{"a": 2}"#), json(r#"[1, {"a": 2}]"#));
+        assert_eq!(concat(r#"{"a": 1}"#, "2"), json(r#"[{"a": 1}, 2]"#));
+        assert_eq!(concat("[[1]]", "[[2]]"), json("[[1], [2]]"));
+
+        let value = concat(r#"[{"a": "x"}, [1]]"#, r#"["y", [2, "z"]]"#);
+        ValueRef::try_from_bytes(value.as_bytes()).unwrap();
+        let array = value.as_array().unwrap();
+        assert_eq!(array.len(), 4);
+        assert_eq!(array.get(0).unwrap().get("a").unwrap().as_str(), Some("x"));
+        assert_eq!(array.get(2).unwrap().as_str(), Some("y"));
+        assert_eq!(array.get(3).unwrap().get(1).unwrap().as_str(), Some("z"));
+    }
+
+    #[test]
+    fn concat_layouts() {
+        let a = parse_with(
+            r#"{"ccc": 1, "a": 2, "dd": 3}"#,
+            KeyOrder::LengthFirst,
+            false,
+        );
+        let b = parse_with(
+            r#"{"b": 4, "dd": 5, "eeee": 6}"#,
+            KeyOrder::LengthFirst,
+            false,
+        );
+        let value = a.as_ref().concat(b.as_ref());
+        ValueRef::try_from_bytes(value.as_bytes()).unwrap();
+        let object = value.as_object().unwrap();
+        assert_eq!(object.key_order(), KeyOrder::LengthFirst);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":2,"b":4,"dd":5,"ccc":1,"eeee":6}"#
+        );
+        assert_eq!(object.get("dd").unwrap().as_u64(), Some(5));
+
+        let c = parse_with(r#"{"b": 4, "dd": 5}"#, KeyOrder::Lexicographic, false);
+        let value = a.as_ref().concat(c.as_ref());
+        assert_eq!(
+            value.as_object().unwrap().key_order(),
+            KeyOrder::LengthFirst
+        );
+        assert_eq!(value.to_string(), r#"{"a":2,"b":4,"dd":5,"ccc":1}"#);
+
+        let d = parse_with(r#"{"z": 1, "y": 2}"#, KeyOrder::Lexicographic, true);
+        let e = parse_with(r#"{"x": 3, "z": 4}"#, KeyOrder::Lexicographic, false);
+        let value = d.as_ref().concat(e.as_ref());
+        assert!(value.as_object().unwrap().preserves_order());
+        assert_eq!(value.to_string(), r#"{"z":4,"y":2,"x":3}"#);
+    }
+
+    #[test]
+    fn concat_raw_json() {
+        let a = raw("[1, 2]");
+        let b = raw_array(&[r#"{"b": 3}"#]);
+        // nested raw JSON is copied as is
+        let value = a.as_ref().concat(b.as_ref());
+        assert_eq!(value.to_string(), r#"[1,2,{"b": 3}]"#);
+    }
+
+    #[test]
+    fn merge_patch() {
+        // test cases from RFC 7396 Appendix A
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, r#"null"#, r#"null"#),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"a":1,"e":null}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+        ];
+        for (target, patch, result) in cases {
+            let value = json(target).as_ref().merge_patch(json(patch).as_ref());
+            assert_eq!(value.to_string(), result, "{target} + {patch}");
+            ValueRef::try_from_bytes(value.as_bytes()).unwrap();
+        }
+    }
+
+    #[test]
+    fn merge_patch_layouts() {
+        let target = r#"{"ccc": 1, "a": {"x": 1}, "dd": 3}"#;
+        let patch = r#"{"b": 4, "dd": null, "a": {"yy": 2}}"#;
+        let expected = json(r#"{"a": {"x": 1, "yy": 2}, "b": 4, "ccc": 1}"#);
+        for target_order in [KeyOrder::Lexicographic, KeyOrder::LengthFirst] {
+            for patch_order in [KeyOrder::Lexicographic, KeyOrder::LengthFirst] {
+                let t = parse_with(target, target_order, false);
+                let p = parse_with(patch, patch_order, false);
+                let value = t.as_ref().merge_patch(p.as_ref());
+                assert_eq!(value, expected);
+                assert_eq!(value.as_object().unwrap().key_order(), target_order);
+                assert_eq!(value.get("a").unwrap().get("yy").unwrap().as_u64(), Some(2));
+            }
+        }
+
+        let t = parse_with(target, KeyOrder::Lexicographic, true);
+        let p = parse_with(patch, KeyOrder::Lexicographic, false);
+        let value = t.as_ref().merge_patch(p.as_ref());
+        assert_eq!(value.to_string(), r#"{"ccc":1,"a":{"x":1,"yy":2},"b":4}"#);
+    }
+
+    #[test]
+    fn merge_patch_diff() {
+        let cases = [
+            (r#"{"a": 1, "b": 2}"#, r#"{"a": 1, "b": 3}"#, r#"{"b":3}"#),
+            (r#"{"a": 1, "b": 2}"#, r#"{"a": 1}"#, r#"{"b":null}"#),
+            (r#"{"a": 1}"#, r#"{"a": 1, "c": [1]}"#, r#"{"c":[1]}"#),
+            (r#"{"a": 1}"#, r#"{"a": 1.0}"#, r#"{}"#),
+            (
+                r#"{"a": {"b": 1, "c": {"d": 2}}, "e": [1]}"#,
+                r#"{"a": {"b": 1, "c": {"d": 3}}, "e": [2]}"#,
+                r#"{"a":{"c":{"d":3}},"e":[2]}"#,
+            ),
+            (r#"{"a": [1]}"#, r#"{"a": {"b": 1}}"#, r#"{"a":{"b":1}}"#),
+            (r#"{"a": {"b": 1}}"#, r#"{"a": "x"}"#, r#"{"a":"x"}"#),
+            (r#"[1]"#, r#"{"a": 1}"#, r#"{"a":1}"#),
+            (r#"{"a": 1}"#, r#"[1]"#, r#"[1]"#),
+            (r#"1"#, r#"null"#, r#"null"#),
+            (r#"{}"#, r#"{}"#, r#"{}"#),
+        ];
+        for (a, b, patch) in cases {
+            let (a, b) = (json(a), json(b));
+            let diff = ValueRef::merge_patch_diff(a.as_ref(), b.as_ref());
+            assert_eq!(diff.to_string(), patch, "{a} -> {b}");
+            assert_eq!(a.as_ref().merge_patch(diff.as_ref()), b, "{a} -> {b}");
+        }
+    }
+
+    #[test]
+    fn merge_patch_raw_json() {
+        let value = raw_object(&[("a", r#"{"b": 1, "c": 2}"#)]);
+        let patch = json(r#"{"a": {"c": null, "d": 3}}"#);
+        assert_eq!(
+            value.as_ref().merge_patch(patch.as_ref()),
+            json(r#"{"a": {"b": 1, "d": 3}}"#)
+        );
+
+        let patch = raw(r#"{"a": null, "b": {"c": 1}}"#);
+        assert_eq!(
+            json(r#"{"a": 1, "b": 2}"#)
+                .as_ref()
+                .merge_patch(patch.as_ref()),
+            json(r#"{"b": {"c": 1}}"#)
+        );
+        assert_eq!(
+            ValueRef::merge_patch_diff(value.as_ref(), json(r#"{"a": {"b": 2}}"#).as_ref()),
+            json(r#"{"a": {"b": 2, "c": null}}"#)
+        );
+    }
+}
@@ -0,0 +1,1707 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A JSON text parser that writes directly into a [`Builder`].
+//!
+//! The parser accepts the same input as `serde_json` and produces the same values, but avoids the
+//! overhead of serde's visitor dispatch. Containers are tracked with an explicit stack, strings are
+//! unescaped straight into the builder's buffer, and numbers are stored in their compact forms.
+//!
+//! With [`ParseOptions`], the parser can also limit the input and accept a subset of JSON5.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::spans::{Recorder, Spans};
+use crate::{Builder, DuplicateKeyPolicy, Error, NonFinitePolicy};
+
+/// The default maximum nesting depth of arrays and objects, the same as `serde_json`.
+const MAX_DEPTH: usize = 127;
+
+/// Options for parsing JSON text.
+///
+/// By default, the parser accepts the same input as `serde_json`.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::{NonFinitePolicy, ParseOptions, Value};
+///
+/// let options = ParseOptions::new()
+///     .max_depth(2)
+///     .json5(true)
+///     .non_finite_policy(NonFinitePolicy::Null);
+/// let value = Value::from_text_with(b"{a: [0x10, 'b', Infinity,], /* comment */}", &options).unwrap();
+/// assert_eq!(value.to_string(), r#"{"a":[16,"b",null]}"#);
+///
+/// assert!(Value::from_text_with(b"[[[]]]", &options).is_err());
+/// assert!(Value::from_text_with(b"[1, 2]", &ParseOptions::new().max_size(16)).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub(crate) max_depth: usize,
+    pub(crate) max_size: usize,
+    pub(crate) json5: bool,
+    duplicate_key_policy: DuplicateKeyPolicy,
+    non_finite_policy: NonFinitePolicy,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_DEPTH,
+            max_size: usize::MAX,
+            json5: false,
+            duplicate_key_policy: DuplicateKeyPolicy::default(),
+            non_finite_policy: NonFinitePolicy::default(),
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Creates options that accept the same input as `serde_json`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum nesting depth of arrays and objects. The default is 127.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the maximum size of the parsed value in jsonbb encoding, in bytes.
+    /// There is no limit by default.
+    pub fn max_size(mut self, bytes: usize) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Enables the following JSON5 extensions:
+    ///
+    /// - `//` and `/* */` comments
+    /// - trailing commas in arrays and objects
+    /// - single quoted strings
+    /// - unquoted object keys consisting of ASCII letters, digits, `_` and `$`
+    /// - hexadecimal integers, such as `0x1F`
+    /// - explicit `+` signs, `Infinity` and `NaN`
+    ///
+    /// `Infinity` and `NaN` are handled by the [non-finite policy](Self::non_finite_policy).
+    pub fn json5(mut self, enable: bool) -> Self {
+        self.json5 = enable;
+        self
+    }
+
+    /// Sets how duplicate keys are handled in objects.
+    pub fn duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_key_policy = policy;
+        self
+    }
+
+    /// Sets how infinite and NaN numbers are handled.
+    pub fn non_finite_policy(mut self, policy: NonFinitePolicy) -> Self {
+        self.non_finite_policy = policy;
+        self
+    }
+
+    /// Creates a builder with the policies of these options.
+    pub(crate) fn builder(&self, capacity: usize) -> Builder {
+        self.apply(Builder::with_capacity(capacity))
+    }
+
+    /// Applies the policies to the builder.
+    pub(crate) fn apply<W>(&self, builder: Builder<W>) -> Builder<W> {
+        builder
+            .with_duplicate_key_policy(self.duplicate_key_policy)
+            .with_non_finite_policy(self.non_finite_policy)
+    }
+}
+
+/// Parses JSON text that is known to be valid UTF-8 into the builder.
+pub(crate) fn parse_str<W: AsMut<Vec<u8>>>(
+    json: &str,
+    builder: &mut Builder<W>,
+) -> Result<(), Error> {
+    parse_with(json.as_bytes(), builder, false, &ParseOptions::default())
+}
+
+/// Parses JSON text into the builder with the given options.
+///
+/// The policies in the options are not applied to the builder.
+pub(crate) fn parse<W: AsMut<Vec<u8>>>(
+    json: &[u8],
+    builder: &mut Builder<W>,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    parse_with(json, builder, true, options)
+}
+
+fn parse_with<W: AsMut<Vec<u8>>>(
+    json: &[u8],
+    builder: &mut Builder<W>,
+    validate_utf8: bool,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    let mut parser = Parser::new(json, builder, validate_utf8, options);
+    match parser.parse_document(builder) {
+        Ok(()) => Ok(()),
+        Err(message) => Err(parser.error(message)),
+    }
+}
+
+/// Parses JSON text into the builder and returns the locations of all values.
+pub(crate) fn parse_with_spans<W: AsMut<Vec<u8>>>(
+    json: &[u8],
+    builder: &mut Builder<W>,
+) -> Result<Spans, Error> {
+    let mut parser = Parser::new(json, builder, true, &ParseOptions::default());
+    parser.spans = Some(Recorder::new());
+    match parser.parse_document(builder) {
+        Ok(()) => Ok(parser.spans.take().unwrap().finish()),
+        Err(message) => Err(parser.error(message)),
+    }
+}
+
+/// Parses the first JSON value in the text into the builder and returns its end position.
+///
+/// Returns `Ok(None)` if there is no value before the end of the text. Unless `eof` is set,
+/// this is also returned if the text may end in the middle of the value, including a number at
+/// the end of the text, in which case the builder contains a partial value.
+pub(crate) fn parse_prefix<W: AsMut<Vec<u8>>>(
+    json: &[u8],
+    builder: &mut Builder<W>,
+    options: &ParseOptions,
+    eof: bool,
+) -> Result<Option<usize>, Error> {
+    let mut parser = Parser::new(json, builder, true, options);
+    parser.skip_whitespace();
+    if parser.pos == json.len() {
+        return Ok(None);
+    }
+    let start = parser.pos;
+    match parser.parse_value(builder) {
+        // strings, containers and literals end with a fixed token, but a number at the end may
+        // continue in the following text
+        Ok(())
+            if !eof
+                && parser.pos == json.len()
+                && !matches!(json[start], b'"' | b'\'' | b'[' | b'{' | b't' | b'f' | b'n') =>
+        {
+            Ok(None)
+        }
+        Ok(()) => Ok(Some(parser.pos)),
+        Err(_) if !eof && parser.pos == json.len() => Ok(None),
+        Err(message) => Err(parser.error(message)),
+    }
+}
+
+/// Checks the syntax of JSON text without building a value.
+pub(crate) fn check_syntax(json: &[u8]) -> Result<(), Error> {
+    let options = ParseOptions::default();
+    let mut parser = Parser {
+        json,
+        pos: 0,
+        validate_utf8: true,
+        json5: false,
+        max_depth: options.max_depth,
+        max_size: options.max_size,
+        spans: None,
+    };
+    let result = parser.skip_value(0).and_then(|()| {
+        parser.skip_whitespace();
+        if parser.pos != json.len() {
+            return Err("trailing characters".into());
+        }
+        Ok(())
+    });
+    result.map_err(|message| parser.error(message))
+}
+
+/// Parses only the values at the given JSON pointers into the builder, see
+/// [`Value::from_text_projected`](crate::Value::from_text_projected).
+///
+/// Returns the original indices of the kept elements of each array on the way to a selected
+/// value, by the JSON pointer of the array.
+pub(crate) fn parse_projected<W: AsMut<Vec<u8>>>(
+    json: &[u8],
+    builder: &mut Builder<W>,
+    pointers: &[&str],
+) -> Result<HashMap<String, Vec<usize>>, Error> {
+    let mut projection = Projection::default();
+    for pointer in pointers {
+        projection.insert(pointer);
+    }
+    let options = ParseOptions::default();
+    let mut parser = Parser::new(json, builder, true, &options);
+    let mut indices = HashMap::new();
+    let result = parser
+        .parse_projected(builder, &projection, 0, &mut String::new(), &mut indices)
+        .and_then(|built| {
+            parser.skip_whitespace();
+            if parser.pos != json.len() {
+                return Err("trailing characters".into());
+            }
+            if !built {
+                return Err("no value is selected from a value that is not a container".into());
+            }
+            Ok(indices)
+        });
+    result.map_err(|message| parser.error(message))
+}
+
+/// A tree of the JSON pointers to parse.
+#[derive(Debug, Default)]
+struct Projection {
+    /// Whether the whole value is selected.
+    whole: bool,
+    /// The selected values under the keys or indices.
+    children: BTreeMap<String, Projection>,
+}
+
+impl Projection {
+    /// Selects the value at the JSON pointer. Invalid pointers select nothing.
+    fn insert(&mut self, pointer: &str) {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return;
+        }
+        let mut node = self;
+        for token in pointer.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            node = node.children.entry(token).or_default();
+        }
+        node.whole = true;
+    }
+
+    /// Returns the largest array index among the children.
+    fn max_index(&self) -> Option<usize> {
+        self.children
+            .keys()
+            .filter(|k| !k.starts_with('+') && (k.len() == 1 || !k.starts_with('0')))
+            .filter_map(|k| k.parse().ok())
+            .max()
+    }
+}
+
+/// The error message without position.
+type Message = std::borrow::Cow<'static, str>;
+
+struct Parser<'a> {
+    json: &'a [u8],
+    pos: usize,
+    /// Whether strings need to be checked for valid UTF-8.
+    validate_utf8: bool,
+    /// Whether JSON5 extensions are accepted.
+    json5: bool,
+    /// The maximum nesting depth of containers.
+    max_depth: usize,
+    /// The maximum encoded length of the builder, `usize::MAX` for no limit.
+    max_size: usize,
+    /// Records the locations of values if set.
+    spans: Option<Recorder>,
+}
+
+impl<'a> Parser<'a> {
+    fn new<W: AsMut<Vec<u8>>>(
+        json: &'a [u8],
+        builder: &mut Builder<W>,
+        validate_utf8: bool,
+        options: &ParseOptions,
+    ) -> Self {
+        Parser {
+            json,
+            pos: 0,
+            validate_utf8,
+            json5: options.json5,
+            max_depth: options.max_depth,
+            max_size: options.max_size.saturating_add(builder.encoded_len()),
+            spans: None,
+        }
+    }
+
+    fn parse_document<W: AsMut<Vec<u8>>>(
+        &mut self,
+        builder: &mut Builder<W>,
+    ) -> Result<(), Message> {
+        self.parse_value(builder)?;
+        self.skip_whitespace();
+        if self.pos != self.json.len() {
+            return Err("trailing characters".into());
+        }
+        Ok(())
+    }
+
+    /// Parses a value iteratively.
+    fn parse_value<W: AsMut<Vec<u8>>>(&mut self, builder: &mut Builder<W>) -> Result<(), Message> {
+        // the stack of containers being parsed, `true` for objects
+        let mut stack: Vec<bool> = vec![];
+        'value: loop {
+            self.skip_whitespace();
+            if let Some(spans) = &mut self.spans {
+                spans.start(self.json, self.pos);
+            }
+            match self.peek() {
+                Some(b'{') => {
+                    self.enter(&mut stack, true)?;
+                    builder.begin_object();
+                    self.skip_whitespace();
+                    if self.peek() == Some(b'}') {
+                        self.pos += 1;
+                        builder.try_end_object().map_err(|e| e.to_string())?;
+                        stack.pop();
+                    } else {
+                        if let Some(spans) = &mut self.spans {
+                            spans.enter(true);
+                        }
+                        self.parse_key(builder)?;
+                        self.record_key(builder);
+                        continue 'value;
+                    }
+                }
+                Some(b'[') => {
+                    self.enter(&mut stack, false)?;
+                    builder.begin_array();
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        self.pos += 1;
+                        builder.end_array();
+                        stack.pop();
+                    } else {
+                        if let Some(spans) = &mut self.spans {
+                            spans.enter(false);
+                        }
+                        continue 'value;
+                    }
+                }
+                Some(b'"') => {
+                    self.pos += 1;
+                    builder.add_string_with(|buf| self.parse_string(buf, b'"'))?;
+                }
+                Some(b'\'') if self.json5 => {
+                    self.pos += 1;
+                    builder.add_string_with(|buf| self.parse_string(buf, b'\''))?;
+                }
+                Some(b'n') => {
+                    self.parse_ident(b"null")?;
+                    builder.add_null();
+                }
+                Some(b't') => {
+                    self.parse_ident(b"true")?;
+                    builder.add_bool(true);
+                }
+                Some(b'f') => {
+                    self.parse_ident(b"false")?;
+                    builder.add_bool(false);
+                }
+                Some(b'+' | b'-' | b'0'..=b'9' | b'I' | b'N') if self.json5 => {
+                    self.parse_json5_number(builder)?
+                }
+                Some(b'-' | b'0'..=b'9') => self.parse_number(builder)?,
+                Some(_) => return Err("expected value".into()),
+                None => return Err("EOF while parsing a value".into()),
+            }
+            self.check_size(builder)?;
+            if let Some(spans) = &mut self.spans {
+                spans.end(self.pos);
+            }
+
+            // a value is finished, continue with the enclosing containers
+            while let Some(&is_object) = stack.last() {
+                self.skip_whitespace();
+                match (self.next(), is_object) {
+                    (Some(b','), true) => {
+                        self.skip_whitespace();
+                        if self.json5 && self.peek() == Some(b'}') {
+                            continue; // trailing comma
+                        }
+                        self.parse_key(builder)?;
+                        self.record_key(builder);
+                        continue 'value;
+                    }
+                    (Some(b','), false) => {
+                        if self.json5 {
+                            self.skip_whitespace();
+                            if self.peek() == Some(b']') {
+                                continue; // trailing comma
+                            }
+                        }
+                        continue 'value;
+                    }
+                    (Some(b'}'), true) => {
+                        builder.try_end_object().map_err(|e| {
+                            self.pos -= 1;
+                            e.to_string()
+                        })?;
+                        stack.pop();
+                        self.check_size(builder)?;
+                        if let Some(spans) = &mut self.spans {
+                            spans.exit(self.pos);
+                        }
+                    }
+                    (Some(b']'), false) => {
+                        builder.end_array();
+                        stack.pop();
+                        self.check_size(builder)?;
+                        if let Some(spans) = &mut self.spans {
+                            spans.exit(self.pos);
+                        }
+                    }
+                    (Some(_), true) => return Err("expected `,` or `}`".into()),
+                    (Some(_), false) => return Err("expected `,` or `]`".into()),
+                    (None, true) => return Err("EOF while parsing an object".into()),
+                    (None, false) => return Err("EOF while parsing a list".into()),
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    /// Parses the selected parts of a value at the JSON pointer `path` with `depth` tokens.
+    ///
+    /// Unselected object members and array elements are skipped, and the original indices of the
+    /// kept elements of arrays are recorded in `indices`. A value that is not a container is
+    /// skipped unless it is selected as a whole. Returns whether a value is added to the builder.
+    fn parse_projected<W: AsMut<Vec<u8>>>(
+        &mut self,
+        builder: &mut Builder<W>,
+        projection: &Projection,
+        depth: usize,
+        path: &mut String,
+        indices: &mut HashMap<String, Vec<usize>>,
+    ) -> Result<bool, Message> {
+        self.skip_whitespace();
+        if projection.whole {
+            let max_depth = self.max_depth;
+            self.max_depth = max_depth.saturating_sub(depth);
+            let result = self.parse_value(builder);
+            self.max_depth = max_depth;
+            return result.map(|()| true);
+        }
+        let is_object = match self.peek() {
+            Some(b'{') => true,
+            Some(b'[') => false,
+            _ => {
+                self.skip_value(depth)?;
+                return Ok(false);
+            }
+        };
+        if depth >= self.max_depth {
+            return Err("recursion limit exceeded".into());
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        let len = path.len();
+        if is_object {
+            builder.begin_object();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                builder.try_end_object().map_err(|e| e.to_string())?;
+                return Ok(true);
+            }
+            let mut key = vec![];
+            loop {
+                key.clear();
+                self.parse_key_into(&mut key)?;
+                let key = std::str::from_utf8(&key).unwrap();
+                self.skip_whitespace();
+                match projection.children.get(key) {
+                    Some(child) if child.whole || matches!(self.peek(), Some(b'{' | b'[')) => {
+                        builder.add_string(key);
+                        path.push('/');
+                        path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                        self.parse_projected(builder, child, depth + 1, path, indices)?;
+                        path.truncate(len);
+                    }
+                    _ => self.skip_value(depth + 1)?,
+                }
+                self.skip_whitespace();
+                match self.next() {
+                    Some(b',') => self.skip_whitespace(),
+                    Some(b'}') => break,
+                    Some(_) => return Err("expected `,` or `}`".into()),
+                    None => return Err("EOF while parsing an object".into()),
+                }
+            }
+            builder.try_end_object().map_err(|e| {
+                self.pos -= 1;
+                e.to_string()
+            })?;
+        } else {
+            builder.begin_array();
+            let mut kept = vec![];
+            if self.peek() == Some(b']') {
+                self.pos += 1;
+            } else {
+                let max_index = projection.max_index();
+                let mut index_buf = itoa::Buffer::new();
+                for index in 0.. {
+                    let child = match max_index {
+                        Some(max) if index <= max => {
+                            projection.children.get(index_buf.format(index))
+                        }
+                        _ => None,
+                    };
+                    self.skip_whitespace();
+                    match child {
+                        Some(child) if child.whole || matches!(self.peek(), Some(b'{' | b'[')) => {
+                            path.push('/');
+                            path.push_str(index_buf.format(index));
+                            self.parse_projected(builder, child, depth + 1, path, indices)?;
+                            path.truncate(len);
+                            kept.push(index);
+                        }
+                        _ => self.skip_value(depth + 1)?,
+                    }
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(b',') => {}
+                        Some(b']') => break,
+                        Some(_) => return Err("expected `,` or `]`".into()),
+                        None => return Err("EOF while parsing a list".into()),
+                    }
+                }
+            }
+            builder.end_array();
+            indices.insert(path.clone(), kept);
+        }
+        Ok(true)
+    }
+
+    /// Skips a value at the given depth, checking only its syntax.
+    fn skip_value(&mut self, depth: usize) -> Result<(), Message> {
+        // the stack of containers being skipped, `true` for objects
+        let mut stack: Vec<bool> = vec![];
+        let mut buf = vec![];
+        'value: loop {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(c @ (b'{' | b'[')) => {
+                    if depth + stack.len() >= self.max_depth {
+                        return Err("recursion limit exceeded".into());
+                    }
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.peek() == Some(if c == b'{' { b'}' } else { b']' }) {
+                        self.pos += 1;
+                    } else {
+                        stack.push(c == b'{');
+                        if c == b'{' {
+                            buf.clear();
+                            self.parse_key_into(&mut buf)?;
+                        }
+                        continue 'value;
+                    }
+                }
+                Some(b'"') => {
+                    self.pos += 1;
+                    buf.clear();
+                    self.parse_string(&mut buf, b'"')?;
+                }
+                Some(b'n') => self.parse_ident(b"null")?,
+                Some(b't') => self.parse_ident(b"true")?,
+                Some(b'f') => self.parse_ident(b"false")?,
+                Some(b'-' | b'0'..=b'9') => self.skip_number()?,
+                Some(_) => return Err("expected value".into()),
+                None => return Err("EOF while parsing a value".into()),
+            }
+
+            // a value is finished, continue with the enclosing containers
+            while let Some(&is_object) = stack.last() {
+                self.skip_whitespace();
+                match (self.next(), is_object) {
+                    (Some(b','), true) => {
+                        self.skip_whitespace();
+                        buf.clear();
+                        self.parse_key_into(&mut buf)?;
+                        continue 'value;
+                    }
+                    (Some(b','), false) => continue 'value,
+                    (Some(b'}'), true) | (Some(b']'), false) => _ = stack.pop(),
+                    (Some(_), true) => return Err("expected `,` or `}`".into()),
+                    (Some(_), false) => return Err("expected `,` or `]`".into()),
+                    (None, true) => return Err("EOF while parsing an object".into()),
+                    (None, false) => return Err("EOF while parsing a list".into()),
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    /// Skips a number, checking only its syntax.
+    fn skip_number(&mut self) -> Result<(), Message> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.next() {
+            Some(b'0') => {
+                if let Some(b'0'..=b'9') = self.peek() {
+                    return Err("invalid number".into());
+                }
+            }
+            Some(b'1'..=b'9') => self.skip_digits(),
+            Some(_) => {
+                self.pos -= 1;
+                return Err("invalid number".into());
+            }
+            None => return Err("EOF while parsing a value".into()),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            self.expect_digit()?;
+            self.skip_digits();
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.pos += 1;
+            }
+            self.expect_digit()?;
+            self.skip_digits();
+        }
+        Ok(())
+    }
+
+    fn skip_digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Parses an object key in double quotes and the following colon, writing the unescaped
+    /// key to `buf`.
+    fn parse_key_into(&mut self, buf: &mut Vec<u8>) -> Result<(), Message> {
+        match self.next() {
+            Some(b'"') => self.parse_string(buf, b'"')?,
+            Some(_) => {
+                self.pos -= 1;
+                return Err("key must be a string".into());
+            }
+            None => return Err("EOF while parsing an object".into()),
+        }
+        self.skip_whitespace();
+        match self.next() {
+            Some(b':') => Ok(()),
+            Some(_) => {
+                self.pos -= 1;
+                Err("expected `:`".into())
+            }
+            None => Err("EOF while parsing an object".into()),
+        }
+    }
+
+    /// Enters a container at the current position.
+    fn enter(&mut self, stack: &mut Vec<bool>, is_object: bool) -> Result<(), Message> {
+        if stack.len() >= self.max_depth {
+            return Err("recursion limit exceeded".into());
+        }
+        self.pos += 1;
+        stack.push(is_object);
+        Ok(())
+    }
+
+    /// Checks that the builder does not exceed the size limit.
+    #[inline]
+    fn check_size<W: AsMut<Vec<u8>>>(&self, builder: &mut Builder<W>) -> Result<(), Message> {
+        if self.max_size != usize::MAX && builder.encoded_len() > self.max_size {
+            return Err("size limit exceeded".into());
+        }
+        Ok(())
+    }
+
+    /// Records the key just parsed if the locations of values are recorded.
+    #[inline]
+    fn record_key<W: AsMut<Vec<u8>>>(&mut self, builder: &mut Builder<W>) {
+        if let Some(spans) = &mut self.spans {
+            spans.key(builder.key_at(builder.position()).expect("missing key"));
+        }
+    }
+
+    /// Parses an object key and the following colon.
+    fn parse_key<W: AsMut<Vec<u8>>>(&mut self, builder: &mut Builder<W>) -> Result<(), Message> {
+        match self.next() {
+            Some(b'"') => builder.add_string_with(|buf| self.parse_string(buf, b'"'))?,
+            Some(b'\'') if self.json5 => {
+                builder.add_string_with(|buf| self.parse_string(buf, b'\''))?
+            }
+            Some(c) if self.json5 && is_ident_start(c) => {
+                self.pos -= 1;
+                builder.add_string_with(|buf| {
+                    let len = self.json[self.pos..]
+                        .iter()
+                        .position(|&c| !is_ident_continue(c))
+                        .unwrap_or(self.json.len() - self.pos);
+                    buf.extend_from_slice(&self.json[self.pos..self.pos + len]);
+                    self.pos += len;
+                    Ok::<_, Message>(())
+                })?
+            }
+            Some(_) => {
+                self.pos -= 1;
+                return Err("key must be a string".into());
+            }
+            None => return Err("EOF while parsing an object".into()),
+        }
+        self.skip_whitespace();
+        match self.next() {
+            Some(b':') => Ok(()),
+            Some(_) => {
+                self.pos -= 1;
+                Err("expected `:`".into())
+            }
+            None => Err("EOF while parsing an object".into()),
+        }
+    }
+
+    fn parse_ident(&mut self, ident: &[u8]) -> Result<(), Message> {
+        for &expected in ident {
+            match self.next() {
+                Some(c) if c == expected => {}
+                Some(_) => return Err("expected ident".into()),
+                None => return Err("EOF while parsing a value".into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the rest of a string after the opening quote, writing unescaped bytes to `buf`.
+    fn parse_string(&mut self, buf: &mut Vec<u8>, quote: u8) -> Result<(), Message> {
+        loop {
+            let rest = &self.json[self.pos..];
+            let len = if quote == b'"' {
+                find_special(rest)
+            } else {
+                rest.iter()
+                    .position(|&c| matches!(c, b'\'' | b'\\' | 0x00..=0x1F))
+                    .unwrap_or(rest.len())
+            };
+            let chunk = &rest[..len];
+            if self.validate_utf8 && std::str::from_utf8(chunk).is_err() {
+                return Err(self.invalid_utf8(chunk));
+            }
+            buf.extend_from_slice(chunk);
+            self.pos += len;
+            match self.next() {
+                Some(c) if c == quote => return Ok(()),
+                Some(b'\\') => self.parse_escape(buf)?,
+                Some(_) => {
+                    self.pos -= 1;
+                    return Err(
+                        "control character (\\u0000-\\u001F) found while parsing a string".into(),
+                    );
+                }
+                None => return Err("EOF while parsing a string".into()),
+            }
+        }
+    }
+
+    /// Moves to the first invalid byte in the chunk and returns the error.
+    #[cold]
+    fn invalid_utf8(&mut self, chunk: &[u8]) -> Message {
+        let error = std::str::from_utf8(chunk).unwrap_err();
+        if error.error_len().is_none() && self.pos + chunk.len() == self.json.len() {
+            // the text ends in the middle of a character
+            self.pos = self.json.len();
+            return "EOF while parsing a string".into();
+        }
+        self.pos += error.valid_up_to();
+        "invalid unicode code point".into()
+    }
+
+    /// Parses an escape sequence after the backslash.
+    fn parse_escape(&mut self, buf: &mut Vec<u8>) -> Result<(), Message> {
+        let c = match self.next() {
+            Some(b'"') => b'"',
+            Some(b'\\') => b'\\',
+            Some(b'/') => b'/',
+            Some(b'b') => b'\x08',
+            Some(b'f') => b'\x0c',
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'\'') if self.json5 => b'\'',
+            Some(b'u') => {
+                let c = match self.parse_hex4()? {
+                    n @ 0xD800..=0xDBFF => {
+                        if self.next() != Some(b'\\') || self.next() != Some(b'u') {
+                            return Err("lone leading surrogate in hex escape".into());
+                        }
+                        let n2 = self.parse_hex4()?;
+                        if !(0xDC00..=0xDFFF).contains(&n2) {
+                            return Err("lone leading surrogate in hex escape".into());
+                        }
+                        let n = ((n as u32 - 0xD800) << 10 | (n2 as u32 - 0xDC00)) + 0x1_0000;
+                        char::from_u32(n).unwrap()
+                    }
+                    0xDC00..=0xDFFF => return Err("lone leading surrogate in hex escape".into()),
+                    n => char::from_u32(n as u32).unwrap(),
+                };
+                buf.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+            Some(_) => {
+                self.pos -= 1;
+                return Err("invalid escape".into());
+            }
+            None => return Err("EOF while parsing a string".into()),
+        };
+        buf.push(c);
+        Ok(())
+    }
+
+    fn parse_hex4(&mut self) -> Result<u16, Message> {
+        let mut n = 0;
+        for _ in 0..4 {
+            let digit = match self.next() {
+                Some(c @ b'0'..=b'9') => c - b'0',
+                Some(c @ b'a'..=b'f') => c - b'a' + 10,
+                Some(c @ b'A'..=b'F') => c - b'A' + 10,
+                Some(_) => {
+                    self.pos -= 1;
+                    return Err("invalid escape".into());
+                }
+                None => return Err("EOF while parsing a string".into()),
+            };
+            n = n << 4 | digit as u16;
+        }
+        Ok(n)
+    }
+
+    /// Parses a number into its most compact form.
+    ///
+    /// Integers are stored as i64 or u64 if they fit, otherwise as f64.
+    /// Numbers with a fraction or an exponent are always stored as f64.
+    /// The conversion follows `serde_json` so that both produce the same values.
+    fn parse_number<W: AsMut<Vec<u8>>>(&mut self, builder: &mut Builder<W>) -> Result<(), Message> {
+        let start = self.pos;
+        let positive = self.peek() != Some(b'-');
+        if !positive {
+            self.pos += 1;
+        }
+
+        // the value is `significand * 10^exponent`, ignoring digits that overflow the significand
+        let mut significand: u64 = 0;
+        let mut exponent: i32 = 0;
+
+        // integer part
+        let mut overflow = false;
+        match self.next() {
+            Some(b'0') => {
+                if let Some(b'0'..=b'9') = self.peek() {
+                    return Err("invalid number".into());
+                }
+            }
+            Some(c @ b'1'..=b'9') => {
+                significand = (c - b'0') as u64;
+                while let Some(c @ b'0'..=b'9') = self.peek() {
+                    self.pos += 1;
+                    if overflow {
+                        exponent += 1;
+                        continue;
+                    }
+                    match significand
+                        .checked_mul(10)
+                        .and_then(|s| s.checked_add((c - b'0') as u64))
+                    {
+                        Some(s) => significand = s,
+                        None => {
+                            overflow = true;
+                            exponent += 1;
+                        }
+                    }
+                }
+            }
+            Some(_) => {
+                self.pos -= 1;
+                return Err("invalid number".into());
+            }
+            None => return Err("EOF while parsing a value".into()),
+        }
+
+        let mut is_float = overflow;
+        // fraction part
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            is_float = true;
+            self.expect_digit()?;
+            let mut overflow = false;
+            while let Some(c @ b'0'..=b'9') = self.peek() {
+                self.pos += 1;
+                if overflow {
+                    continue;
+                }
+                match significand
+                    .checked_mul(10)
+                    .and_then(|s| s.checked_add((c - b'0') as u64))
+                {
+                    Some(s) => {
+                        significand = s;
+                        exponent -= 1;
+                    }
+                    None => overflow = true,
+                }
+            }
+        }
+        // exponent part
+        if let Some(b'e' | b'E') = self.peek() {
+            self.pos += 1;
+            is_float = true;
+            let positive_exp = match self.peek() {
+                Some(b'+') => {
+                    self.pos += 1;
+                    true
+                }
+                Some(b'-') => {
+                    self.pos += 1;
+                    false
+                }
+                _ => true,
+            };
+            self.expect_digit()?;
+            let mut exp: i32 = 0;
+            let mut overflow = false;
+            while let Some(c @ b'0'..=b'9') = self.peek() {
+                self.pos += 1;
+                match exp
+                    .checked_mul(10)
+                    .and_then(|e| e.checked_add((c - b'0') as i32))
+                {
+                    Some(e) => exp = e,
+                    None => overflow = true,
+                }
+            }
+            if overflow {
+                if significand != 0 && positive_exp {
+                    return Err("number out of range".into());
+                }
+                builder.add_f64(if positive { 0.0 } else { -0.0 });
+                return Ok(());
+            }
+            exponent = if positive_exp {
+                exponent.saturating_add(exp)
+            } else {
+                exponent.saturating_sub(exp)
+            };
+        }
+
+        if !is_float {
+            add_integer(builder, positive, significand);
+            return Ok(());
+        }
+        let v = self.f64_from_parts(start, positive, significand, exponent)?;
+        builder.add_f64(v);
+        Ok(())
+    }
+
+    /// Parses a number with JSON5 extensions.
+    fn parse_json5_number<W: AsMut<Vec<u8>>>(
+        &mut self,
+        builder: &mut Builder<W>,
+    ) -> Result<(), Message> {
+        let start = self.pos;
+        let sign = match self.peek() {
+            Some(c @ (b'+' | b'-')) => {
+                self.pos += 1;
+                Some(c)
+            }
+            _ => None,
+        };
+        let positive = sign != Some(b'-');
+        let v = match self.peek() {
+            Some(b'I') => {
+                self.parse_ident(b"Infinity")?;
+                if positive {
+                    f64::INFINITY
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+            Some(b'N') => {
+                self.parse_ident(b"NaN")?;
+                f64::NAN
+            }
+            Some(b'0') if matches!(self.json.get(self.pos + 1), Some(b'x' | b'X')) => {
+                self.pos += 2;
+                return self.parse_hex_number(builder, positive);
+            }
+            Some(b'+' | b'-') => return Err("invalid number".into()),
+            _ => {
+                if !positive {
+                    self.pos = start;
+                }
+                return self.parse_number(builder);
+            }
+        };
+        builder.try_add_f64(v).map_err(|e| {
+            self.pos = start;
+            e.to_string()
+        })?;
+        Ok(())
+    }
+
+    /// Parses the digits of a hexadecimal integer after `0x`.
+    fn parse_hex_number<W: AsMut<Vec<u8>>>(
+        &mut self,
+        builder: &mut Builder<W>,
+        positive: bool,
+    ) -> Result<(), Message> {
+        let mut n: u64 = 0;
+        let mut digits = 0;
+        while let Some(digit) = self.peek().and_then(|c| (c as char).to_digit(16)) {
+            self.pos += 1;
+            digits += 1;
+            n = n
+                .checked_mul(16)
+                .map(|n| n + digit as u64)
+                .ok_or("number out of range")?;
+        }
+        if digits == 0 {
+            return Err("invalid number".into());
+        }
+        add_integer(builder, positive, n);
+        Ok(())
+    }
+
+    /// Converts a float from its parts, rounded correctly by parsing the text.
+    #[cfg(feature = "float_roundtrip")]
+    fn f64_from_parts(
+        &mut self,
+        start: usize,
+        _positive: bool,
+        _significand: u64,
+        _exponent: i32,
+    ) -> Result<f64, Message> {
+        // SAFETY: the number only contains ASCII characters.
+        let text = unsafe { std::str::from_utf8_unchecked(&self.json[start..self.pos]) };
+        let f: f64 = text.parse().map_err(|_| "invalid number")?;
+        if f.is_infinite() {
+            return Err("number out of range".into());
+        }
+        Ok(f)
+    }
+
+    /// Converts a float from its parts, which may be off by one ulp.
+    // https://github.com/serde-rs/json/blob/v1.0.154/src/de.rs#L639
+    #[cfg(not(feature = "float_roundtrip"))]
+    fn f64_from_parts(
+        &mut self,
+        _start: usize,
+        positive: bool,
+        significand: u64,
+        mut exponent: i32,
+    ) -> Result<f64, Message> {
+        let mut f = significand as f64;
+        loop {
+            match POW10.get(exponent.unsigned_abs() as usize) {
+                Some(&pow) => {
+                    if exponent >= 0 {
+                        f *= pow;
+                        if f.is_infinite() {
+                            return Err("number out of range".into());
+                        }
+                    } else {
+                        f /= pow;
+                    }
+                    break;
+                }
+                None => {
+                    if f == 0.0 {
+                        break;
+                    }
+                    if exponent >= 0 {
+                        return Err("number out of range".into());
+                    }
+                    f /= 1e308;
+                    exponent += 308;
+                }
+            }
+        }
+        Ok(if positive { f } else { -f })
+    }
+
+    /// Expects a digit at the current position.
+    fn expect_digit(&mut self) -> Result<(), Message> {
+        match self.peek() {
+            Some(b'0'..=b'9') => Ok(()),
+            Some(_) => Err("invalid number".into()),
+            None => Err("EOF while parsing a value".into()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.peek() {
+            self.pos += 1;
+        }
+        if self.json5 && self.peek() == Some(b'/') {
+            self.skip_comments();
+        }
+    }
+
+    /// Skips comments and whitespace between them.
+    ///
+    /// An unterminated block comment skips to the end of input.
+    fn skip_comments(&mut self) {
+        loop {
+            let rest = &self.json[self.pos..];
+            match rest.get(1) {
+                Some(b'/') => {
+                    let len = rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+                    self.pos += len;
+                }
+                Some(b'*') => {
+                    let len = rest[2..]
+                        .windows(2)
+                        .position(|w| w == b"*/")
+                        .map_or(rest.len(), |i| i + 4);
+                    self.pos += len;
+                }
+                _ => return,
+            }
+            while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.peek() {
+                self.pos += 1;
+            }
+            if self.peek() != Some(b'/') {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.json.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    /// Creates an error at the current position.
+    #[cold]
+    fn error(&self, message: Message) -> Error {
+        Error::parse(message, self.json, self.pos)
+    }
+}
+
+/// Adds an integer with the given sign and absolute value to the builder.
+fn add_integer<W: AsMut<Vec<u8>>>(builder: &mut Builder<W>, positive: bool, n: u64) {
+    if positive {
+        builder.add_u64(n);
+    } else {
+        match (n as i64).wrapping_neg() {
+            // `-0` and numbers below `i64::MIN` are stored as f64
+            neg if neg >= 0 => builder.add_f64(-(n as f64)),
+            neg => builder.add_i64(neg),
+        }
+    }
+}
+
+/// Returns true if the byte can start an unquoted key in JSON5.
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_' || c == b'$'
+}
+
+/// Returns true if the byte can continue an unquoted key in JSON5.
+fn is_ident_continue(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$'
+}
+
+/// Powers of 10 from `1e0` to `1e308`.
+#[cfg(not(feature = "float_roundtrip"))]
+static POW10: [f64; 309] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22, 1e23, 1e24, 1e25, 1e26, 1e27, 1e28, 1e29, 1e30, 1e31, 1e32,
+    1e33, 1e34, 1e35, 1e36, 1e37, 1e38, 1e39, 1e40, 1e41, 1e42, 1e43, 1e44, 1e45, 1e46, 1e47, 1e48,
+    1e49, 1e50, 1e51, 1e52, 1e53, 1e54, 1e55, 1e56, 1e57, 1e58, 1e59, 1e60, 1e61, 1e62, 1e63, 1e64,
+    1e65, 1e66, 1e67, 1e68, 1e69, 1e70, 1e71, 1e72, 1e73, 1e74, 1e75, 1e76, 1e77, 1e78, 1e79, 1e80,
+    1e81, 1e82, 1e83, 1e84, 1e85, 1e86, 1e87, 1e88, 1e89, 1e90, 1e91, 1e92, 1e93, 1e94, 1e95, 1e96,
+    1e97, 1e98, 1e99, 1e100, 1e101, 1e102, 1e103, 1e104, 1e105, 1e106, 1e107, 1e108, 1e109, 1e110,
+    1e111, 1e112, 1e113, 1e114, 1e115, 1e116, 1e117, 1e118, 1e119, 1e120, 1e121, 1e122, 1e123,
+    1e124, 1e125, 1e126, 1e127, 1e128, 1e129, 1e130, 1e131, 1e132, 1e133, 1e134, 1e135, 1e136,
+    1e137, 1e138, 1e139, 1e140, 1e141, 1e142, 1e143, 1e144, 1e145, 1e146, 1e147, 1e148, 1e149,
+    1e150, 1e151, 1e152, 1e153, 1e154, 1e155, 1e156, 1e157, 1e158, 1e159, 1e160, 1e161, 1e162,
+    1e163, 1e164, 1e165, 1e166, 1e167, 1e168, 1e169, 1e170, 1e171, 1e172, 1e173, 1e174, 1e175,
+    1e176, 1e177, 1e178, 1e179, 1e180, 1e181, 1e182, 1e183, 1e184, 1e185, 1e186, 1e187, 1e188,
+    1e189, 1e190, 1e191, 1e192, 1e193, 1e194, 1e195, 1e196, 1e197, 1e198, 1e199, 1e200, 1e201,
+    1e202, 1e203, 1e204, 1e205, 1e206, 1e207, 1e208, 1e209, 1e210, 1e211, 1e212, 1e213, 1e214,
+    1e215, 1e216, 1e217, 1e218, 1e219, 1e220, 1e221, 1e222, 1e223, 1e224, 1e225, 1e226, 1e227,
+    1e228, 1e229, 1e230, 1e231, 1e232, 1e233, 1e234, 1e235, 1e236, 1e237, 1e238, 1e239, 1e240,
+    1e241, 1e242, 1e243, 1e244, 1e245, 1e246, 1e247, 1e248, 1e249, 1e250, 1e251, 1e252, 1e253,
+    1e254, 1e255, 1e256, 1e257, 1e258, 1e259, 1e260, 1e261, 1e262, 1e263, 1e264, 1e265, 1e266,
+    1e267, 1e268, 1e269, 1e270, 1e271, 1e272, 1e273, 1e274, 1e275, 1e276, 1e277, 1e278, 1e279,
+    1e280, 1e281, 1e282, 1e283, 1e284, 1e285, 1e286, 1e287, 1e288, 1e289, 1e290, 1e291, 1e292,
+    1e293, 1e294, 1e295, 1e296, 1e297, 1e298, 1e299, 1e300, 1e301, 1e302, 1e303, 1e304, 1e305,
+    1e306, 1e307, 1e308,
+];
+
+/// Returns the index of the first byte that ends a run of plain string characters,
+/// i.e. a quote, a backslash or a control character. Returns the length if not found.
+fn find_special(bytes: &[u8]) -> usize {
+    let mut i = 0;
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SAFETY: SSE2 is always available on x86_64.
+        unsafe {
+            use std::arch::x86_64::*;
+            let quote = _mm_set1_epi8(b'"' as i8);
+            let backslash = _mm_set1_epi8(b'\\' as i8);
+            let control = _mm_set1_epi8(0x1F);
+            while i + 16 <= bytes.len() {
+                let chunk = _mm_loadu_si128(bytes.as_ptr().add(i).cast());
+                // bytes <= 0x1F are the ones unchanged by max with 0x1F
+                let is_control = _mm_cmpeq_epi8(_mm_max_epu8(chunk, control), control);
+                let is_special = _mm_or_si128(
+                    _mm_or_si128(
+                        _mm_cmpeq_epi8(chunk, quote),
+                        _mm_cmpeq_epi8(chunk, backslash),
+                    ),
+                    is_control,
+                );
+                let mask = _mm_movemask_epi8(is_special);
+                if mask != 0 {
+                    return i + mask.trailing_zeros() as usize;
+                }
+                i += 16;
+            }
+        }
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        // SWAR: check 8 bytes at a time
+        const LO: u64 = u64::from_ne_bytes([0x01; 8]);
+        const HI: u64 = u64::from_ne_bytes([0x80; 8]);
+        let has_zero = |v: u64| v.wrapping_sub(LO) & !v & HI;
+        while i + 8 <= bytes.len() {
+            let v = u64::from_ne_bytes(bytes[i..i + 8].try_into().unwrap());
+            let is_special = has_zero(v ^ (LO * b'"' as u64))
+                | has_zero(v ^ (LO * b'\\' as u64))
+                | (v.wrapping_sub(LO * 0x20) & !v & HI);
+            if is_special != 0 {
+                break;
+            }
+            i += 8;
+        }
+    }
+    while i < bytes.len() && !matches!(bytes[i], b'"' | b'\\' | 0x00..=0x1F) {
+        i += 1;
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DuplicateKeyPolicy, NonFinitePolicy, Value};
+    use serde::de::DeserializeSeed;
+
+    /// Parses JSON text with the native parser.
+    fn parse_native(json: &str) -> Result<Value, Error> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        parse(json.as_bytes(), &mut builder, &ParseOptions::default())?;
+        Ok(builder.finish())
+    }
+
+    /// Parses JSON text through serde.
+    fn parse_serde(json: &str) -> serde_json::Result<Value> {
+        let mut builder = Builder::<Vec<u8>>::new();
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        builder.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(builder.finish())
+    }
+
+    /// Asserts that both parsers produce the same value or both fail.
+    #[track_caller]
+    fn assert_same(json: &str) {
+        match (parse_native(json), parse_serde(json)) {
+            (Ok(native), Ok(serde)) => {
+                assert!(native.as_bytes() == serde.as_bytes(), "{native} != {serde}")
+            }
+            (Err(_), Err(_)) => {}
+            (native, serde) => panic!("{json:?}: native = {native:?}, serde = {serde:?}"),
+        }
+    }
+
+    #[test]
+    fn values() {
+        for json in [
+            "null",
+            "true",
+            "false",
+            "0",
+            "-0",
+            "1",
+            "-1",
+            "127",
+            "-128",
+            "32767",
+            "2147483648",
+            "9223372036854775807",
+            "9223372036854775808",
+            "18446744073709551615",
+            "18446744073709551616",
+            "-9223372036854775808",
+            "-9223372036854775809",
+            "100000000000000000000000000000",
+            "0.0",
+            "-0.0",
+            "1.5",
+            "1e3",
+            "1E+3",
+            "1e-3",
+            "-1.25e-10",
+            "1.7976931348623157e308",
+            "5e-324",
+            "1e-400",
+            "0.1",
+            "3.141592653589793238462643383279",
+            "123456789012345678901234.5",
+            "0.000000000000000000000000000001234567890123456789",
+            "1e99999999999",
+            "0e99999999999",
+            "-1e-99999999999",
+            "1.7976931348623157e309",
+            "2.2250738585072011e-308",
+            r#""""#,
+            r#""hello""#,
+            r#""中文 🦀""#,
+            r#""\"\\\/\b\f\n\r\t""#,
+            r#""\u0000\u001fé中🦀""#,
+            r#""a long string with more than sixteen bytes, \"escaped\" in the middle""#,
+            "[]",
+            "[[]]",
+            "{}",
+            r#"{"a":{}}"#,
+            " \t\n\r[ 1 , 2 , [ 3 ] , { \"a\" : 4 } ] \n",
+            r#"{"b":1,"a":2,"b":3}"#,
+            r#"{"a":[1,2.5,"x",null,true,false,{"b":[]}],"c":{"d":{"e":"f"}}}"#,
+        ] {
+            assert_same(json);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for json in [
+            "",
+            " ",
+            "nul",
+            "nulL",
+            "tru",
+            "fals",
+            "01",
+            "-",
+            "-a",
+            "1.",
+            "1.e3",
+            "1e",
+            "1e+",
+            ".5",
+            "+1",
+            "1e400",
+            "-1e400",
+            "NaN",
+            "Infinity",
+            "1 2",
+            "[1,]",
+            "[1 2]",
+            "[,1]",
+            "[",
+            "]",
+            "{",
+            "{,}",
+            r#"{"a"}"#,
+            r#"{"a":}"#,
+            r#"{"a":1,}"#,
+            r#"{"a":1 "b":2}"#,
+            r#"{1:2}"#,
+            r#"{"a":1]"#,
+            r#"[1}"#,
+            r#"""#,
+            r#""abc"#,
+            "\"\x01\"",
+            "\"\n\"",
+            r#""\x""#,
+            r#""\u12""#,
+            r#""\u12g4""#,
+            r#""\ud800""#,
+            r#""\ud800A""#,
+            r#""\udc00""#,
+            "'a'",
+        ] {
+            assert!(parse_native(json).is_err(), "{json:?} should fail");
+            assert_same(json);
+        }
+        // invalid UTF-8
+        let mut builder = Builder::<Vec<u8>>::new();
+        assert!(parse(b"\"\xff\"", &mut builder, &ParseOptions::default()).is_err());
+    }
+
+    #[test]
+    fn recursion_limit() {
+        for depth in [126, 127, 128, 129] {
+            assert_same(&("[".repeat(depth) + &"]".repeat(depth)));
+            assert_same(&(r#"{"a":"#.repeat(depth) + "1" + &"}".repeat(depth)));
+        }
+    }
+
+    #[test]
+    fn error_position() {
+        let err = parse_native("[1,\n  2,\n  x]").unwrap_err();
+        assert_eq!(err.to_string(), "expected value at line 3 column 3");
+        assert!(parse_native(r#"{"a": 1, "a": 2}"#).is_ok());
+        let err = Value::from_text_with_policy(br#"{"a": 1, "a": 2}"#, DuplicateKeyPolicy::Error)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "duplicate key `a` at line 1 column 16");
+    }
+
+    /// Parses JSON text with the given options.
+    fn parse_with(json: &str, options: &ParseOptions) -> Result<Value, Error> {
+        Value::from_text_with(json.as_bytes(), options)
+    }
+
+    #[test]
+    fn max_depth() {
+        let options = ParseOptions::new().max_depth(3);
+        parse_with("[[[]]]", &options).unwrap();
+        parse_with(r#"{"a":[{}]}"#, &options).unwrap();
+        let err = parse_with("[[[[]]]]", &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "recursion limit exceeded at line 1 column 4"
+        );
+        parse_with("1", &ParseOptions::new().max_depth(0)).unwrap();
+        parse_with("[]", &ParseOptions::new().max_depth(0)).unwrap_err();
+
+        // deeper than the default limit
+        let json = "[".repeat(1000) + &"]".repeat(1000);
+        parse_with(&json, &ParseOptions::new()).unwrap_err();
+        parse_with(&json, &ParseOptions::new().max_depth(1000)).unwrap();
+    }
+
+    #[test]
+    fn max_size() {
+        let json = r#"{"a":[1,"hello"]}"#;
+        let size = Value::from_text(json.as_bytes()).unwrap().capacity();
+        parse_with(json, &ParseOptions::new().max_size(size)).unwrap();
+        for max_size in [0, 4, size / 2, size - 1] {
+            let err = parse_with(json, &ParseOptions::new().max_size(max_size)).unwrap_err();
+            assert!(err.to_string().starts_with("size limit exceeded"), "{err}");
+        }
+        // a long string is rejected as soon as it is parsed
+        let json = format!(r#"["{}", 1]"#, "x".repeat(100));
+        let err = parse_with(&json, &ParseOptions::new().max_size(100)).unwrap_err();
+        assert_eq!(err.to_string(), "size limit exceeded at line 1 column 104");
+    }
+
+    #[test]
+    fn json5() {
+        let options = ParseOptions::new()
+            .json5(true)
+            .non_finite_policy(NonFinitePolicy::String);
+        for (json, expected) in [
+            ("// comment\n1 // comment", "1"),
+            ("/* a */ /* b */ [/**/1/* c */, /* d */2]/**/", "[1,2]"),
+            ("[1, 2, ]", "[1,2]"),
+            (r#"{"a": 1, }"#, r#"{"a":1}"#),
+            ("[[],]", "[[]]"),
+            ("{a: {},}", r#"{"a":{}}"#),
+            (r#"'a"b\'c'"#, r#""a\"b'c""#),
+            (r#"{'a': 'b'}"#, r#"{"a":"b"}"#),
+            ("{_a1: 1, $b: 2, C: 3}", r#"{"$b":2,"C":3,"_a1":1}"#),
+            ("{true: 1, null: 2}", r#"{"null":2,"true":1}"#),
+            ("[0x0, 0x1F, 0XaB, -0x10, +0x10]", "[0,31,171,-16,16]"),
+            ("0xFFFFFFFFFFFFFFFF", "18446744073709551615"),
+            ("-0x8000000000000000", "-9223372036854775808"),
+            ("[+1, +1.5, -2, -0]", "[1,1.5,-2,-0.0]"),
+            (
+                "[Infinity, +Infinity, -Infinity, NaN]",
+                r#"["Infinity","Infinity","-Infinity","NaN"]"#,
+            ),
+        ] {
+            let value = parse_with(json, &options).unwrap();
+            assert_eq!(value.to_string(), expected, "{json}");
+        }
+        for json in [
+            "/* unterminated",
+            "/ 1",
+            "[1,,]",
+            "[,]",
+            "{,}",
+            "{a: 1,,}",
+            "{1a: 1}",
+            "{a b: 1}",
+            "'abc",
+            "0x",
+            "0xG",
+            "0x10000000000000000",
+            "++1",
+            "+-1",
+            "-+1",
+            "Inf",
+            "nan",
+        ] {
+            assert!(parse_with(json, &options).is_err(), "{json:?} should fail");
+        }
+        // JSON5 is disabled by default
+        for json in ["[1,]", "{a:1}", "'a'", "0x1", "+1", "// c\n1", "Infinity"] {
+            assert!(parse_with(json, &ParseOptions::new()).is_err(), "{json:?}");
+        }
+        // JSON text is parsed the same with JSON5 enabled
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/data");
+        for path in std::fs::read_dir(dir).unwrap() {
+            let json = std::fs::read_to_string(path.unwrap().path()).unwrap();
+            let value = parse_with(&json, &options).unwrap();
+            assert!(value.as_bytes() == parse_serde(&json).unwrap().as_bytes());
+        }
+    }
+
+    #[test]
+    fn non_finite_policy() {
+        let json = "[NaN, -Infinity]";
+        let options = ParseOptions::new().json5(true);
+        let err = parse_with(json, &options).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Infinite or NaN values are not JSON numbers: NaN at line 1 column 2"
+        );
+        let options = options.non_finite_policy(NonFinitePolicy::Null);
+        assert_eq!(
+            parse_with(json, &options).unwrap().to_string(),
+            "[null,null]"
+        );
+        // numbers out of range are still errors
+        parse_with("1e400", &options).unwrap_err();
+    }
+
+    #[test]
+    fn find_special() {
+        let text = "abcdefghijklmnopqrstuvwxyz0123456789";
+        for i in 0..text.len() {
+            for c in ['"', '\\', '\x00', '\x1f'] {
+                let mut s = text.to_string();
+                s.insert(i, c);
+                assert_eq!(super::find_special(s.as_bytes()), i, "{s:?}");
+            }
+            assert_eq!(super::find_special(&text.as_bytes()[i..]), text.len() - i);
+        }
+        assert_eq!(super::find_special("中文\x7f\u{80}".as_bytes()), 9);
+    }
+
+    #[test]
+    fn json_files() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/data");
+        for path in std::fs::read_dir(dir).unwrap() {
+            let json = std::fs::read_to_string(path.unwrap().path()).unwrap();
+            assert_same(&json);
+        }
+    }
+
+    #[test]
+    fn projected() {
+        let json =
+            r#"{"a": {"b": [1, {"c": 2, "d": [3]}, 4], "e": "f"}, "g": [[5], 6], "h": null}"#;
+        let full = Value::from_text(json.as_bytes()).unwrap();
+        let cases: &[(&[&str], &str)] = &[
+            (&[""], json),
+            (&["/h"], r#"{"h":null}"#),
+            (&["/a/e", "/g/0/0"], r#"{"a":{"e":"f"},"g":[[5]]}"#),
+            (&["/a/b/1/d", "/a/b/1/d/0"], r#"{"a":{"b":[{"d":[3]}]}}"#),
+            (&["/a/b/2"], r#"{"a":{"b":[4]}}"#),
+            (
+                &["/a", "/a/e"],
+                r#"{"a":{"b":[1,{"c":2,"d":[3]},4],"e":"f"}}"#,
+            ),
+            // missing and invalid pointers select nothing
+            (
+                &["/x", "/a/e/x", "/a/b/01", "/a/b/9", "a"],
+                r#"{"a":{"b":[]}}"#,
+            ),
+            (&[], "{}"),
+        ];
+        let (_, spans) = Value::from_text_with_spans(json.as_bytes()).unwrap();
+        for &(pointers, expected) in cases {
+            let value = Value::from_text_projected(json.as_bytes(), pointers).unwrap();
+            let expected: Value = expected.parse().unwrap();
+            assert_eq!(value.value(), &expected, "{pointers:?}");
+            for pointer in pointers {
+                assert_eq!(value.pointer(pointer), full.pointer(pointer), "{pointer}");
+            }
+            // no value is made up for the values that are not selected
+            for (pointer, _) in spans.iter() {
+                let Some(v) = value.pointer(&pointer) else {
+                    continue;
+                };
+                let expected = full.pointer(&pointer).unwrap();
+                if !expected.is_object() && !expected.is_array() {
+                    assert_eq!(v, expected, "{pointers:?} {pointer}");
+                }
+            }
+        }
+        let value = Value::from_text_projected(b"[1, [2]]", &["/0/a", "/1/0"]).unwrap();
+        assert_eq!(value.value().to_string(), "[[2]]");
+        assert_eq!(value.pointer("/0"), None);
+        assert_eq!(value.pointer("/1/0").unwrap().as_u64(), Some(2));
+        let value = Value::from_text_projected(br#"{"a/~b": [0, 1]}"#, &["/a~1~0b/1"]).unwrap();
+        assert_eq!(value.pointer("/a~1~0b/1").unwrap().as_u64(), Some(1));
+        assert_eq!(value.pointer("/a~1~0b/0"), None);
+        assert!(Value::from_text_projected(b"1", &["/a"]).is_err());
+        assert!(Value::from_text_projected(b"1", &[""])
+            .unwrap()
+            .value()
+            .is_number());
+
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/data");
+        let json = std::fs::read(format!("{dir}/twitter.json")).unwrap();
+        let full = Value::from_text(&json).unwrap();
+        let pointers = [
+            "/statuses/3/user/screen_name",
+            "/statuses/5/entities",
+            "/search_metadata",
+        ];
+        let value = Value::from_text_projected(&json, &pointers).unwrap();
+        for pointer in pointers {
+            assert!(value.pointer(pointer).is_some());
+            assert_eq!(value.pointer(pointer), full.pointer(pointer), "{pointer}");
+        }
+        assert!(value.value().capacity() < full.capacity() / 10);
+    }
+
+    #[test]
+    fn projected_invalid() {
+        // skipped values are still checked for syntax
+        for json in [
+            "",
+            "[1, 2",
+            r#"{"x": [1, 2,]}"#,
+            r#"{"x": {"y" 1}}"#,
+            r#"{"x": "\u12"}"#,
+            r#"{"x": 01}"#,
+            r#"{"x": -}"#,
+            r#"{"x": 1.}"#,
+            r#"{"x": tru}"#,
+            r#"{"x": [1 2]}"#,
+            r#"{"x": {1: 2}}"#,
+            r#"{"x": 1} 2"#,
+            "[[[1]]] [",
+        ] {
+            let expected = Value::from_text(json.as_bytes()).unwrap_err().to_string();
+            let err = Value::from_text_projected(json.as_bytes(), &["/a"]).unwrap_err();
+            assert_eq!(err.to_string(), expected, "{json}");
+        }
+        let json = "[".repeat(200) + &"]".repeat(200);
+        let expected = Value::from_text(json.as_bytes()).unwrap_err().to_string();
+        for pointer in ["", "/0", "/0/0/0", "/1"] {
+            let err = Value::from_text_projected(json.as_bytes(), &[pointer]).unwrap_err();
+            assert_eq!(err.to_string(), expected, "{pointer}");
+        }
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Values built from selected parts of JSON text.
+
+use std::collections::HashMap;
+
+use crate::{Value, ValueRef};
+
+/// A value built from the selected parts of JSON text, along with the original indices of the
+/// array elements that were kept.
+///
+/// Arrays on the way to a selected value keep only the selected elements, so their indices in
+/// [`value`](Self::value) may differ from those in the text. [`pointer`](Self::pointer) looks
+/// up values by their pointers into the text.
+///
+/// Returned by [`Value::from_text_projected`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectedValue {
+    value: Value,
+    /// The original indices of the kept elements of each array on the way to a selected value,
+    /// by the JSON pointer of the array in the text.
+    indices: HashMap<String, Vec<usize>>,
+}
+
+impl ProjectedValue {
+    pub(crate) fn new(value: Value, indices: HashMap<String, Vec<usize>>) -> Self {
+        Self { value, indices }
+    }
+
+    /// Returns the built value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the built value, dropping the original indices.
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Looks up a value by a JSON pointer into the text.
+    ///
+    /// Returns `None` if the value is neither selected nor on the way to a selected value.
+    pub fn pointer(&self, pointer: &str) -> Option<ValueRef<'_>> {
+        if pointer.is_empty() {
+            return Some(self.value.as_ref());
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+        let mut value = self.value.as_ref();
+        let mut end = 0;
+        for token in pointer.split('/').skip(1) {
+            let path = &pointer[..end];
+            end += 1 + token.len();
+            value = match value {
+                ValueRef::Array(array) => {
+                    if token.starts_with('+') || (token.starts_with('0') && token.len() != 1) {
+                        return None;
+                    }
+                    let index = token.parse().ok()?;
+                    match self.indices.get(path) {
+                        Some(indices) => array.get(indices.binary_search(&index).ok()?)?,
+                        None => array.get(index)?,
+                    }
+                }
+                _ => value.get(token.replace("~1", "/").replace("~0", "~").as_str())?,
+            };
+        }
+        Some(value)
+    }
+}
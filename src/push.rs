@@ -0,0 +1,439 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Incremental parsing of JSON text delivered in chunks.
+
+use crate::error::advance;
+use crate::parser::{self, ParseOptions};
+use crate::{Builder, Error, Value};
+
+/// A parser that accepts JSON text in chunks of arbitrary size.
+///
+/// Since values are stored in post-order, each value is written into the builder as soon as it
+/// is complete. Only an unfinished string, number or literal at the end of a chunk is buffered
+/// until the next chunk.
+///
+/// JSON5 extensions are not supported and the `json5` option is ignored.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::PushParser;
+///
+/// let mut parser = PushParser::new();
+/// for chunk in [&br#"{"na"#[..], br#"me": ["fo"#, br#"o", 1"#, br#"2]}"#] {
+///     parser.feed(chunk).unwrap();
+/// }
+/// let value = parser.finish().unwrap();
+/// assert_eq!(value.to_string(), r#"{"name":["foo",12]}"#);
+/// ```
+#[derive(Debug)]
+pub struct PushParser {
+    builder: Builder,
+    options: ParseOptions,
+    /// The options to parse a single token, without the size limit of the whole value.
+    token_options: ParseOptions,
+    /// What is expected next.
+    state: State,
+    /// The containers being parsed, `true` for objects.
+    stack: Vec<bool>,
+    /// The unfinished token at the end of the last chunk.
+    pending: Vec<u8>,
+    /// The kind of the pending token.
+    token: Token,
+    /// The line and column of the first byte not consumed yet.
+    location: (usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// A value.
+    Value,
+    /// A value or `]` after `[`.
+    FirstElement,
+    /// A key or `}` after `{`.
+    FirstKey,
+    /// A key after `,`.
+    Key,
+    /// A `:` after a key.
+    Colon,
+    /// A `,` or the end of the enclosing container.
+    Comma,
+    /// Only whitespace after the value.
+    Done,
+    /// An error has occurred.
+    Failed,
+}
+
+/// The kind of a token and the state to find its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    /// A string, with whether the last byte is an unfinished escape.
+    String {
+        escaped: bool,
+    },
+    Number,
+    Literal,
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushParser {
+    /// Creates a new parser that accepts the same input as `serde_json`.
+    pub fn new() -> Self {
+        Self::with_options(ParseOptions::default())
+    }
+
+    /// Creates a new parser with the given options.
+    pub fn with_options(options: ParseOptions) -> Self {
+        PushParser {
+            builder: options.builder(0),
+            token_options: options.clone().max_size(usize::MAX),
+            options,
+            state: State::Value,
+            stack: vec![],
+            pending: vec![],
+            token: Token::Number,
+            location: (1, 1),
+        }
+    }
+
+    /// Parses the next chunk of JSON text.
+    ///
+    /// After an error, the parser rejects any further input.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        if self.pending.is_empty() {
+            return self.run(chunk, false);
+        }
+        // parse the pending token only when it is complete
+        match self.token.find_end(chunk) {
+            Some(end) => {
+                let mut data = std::mem::take(&mut self.pending);
+                data.extend_from_slice(&chunk[..end]);
+                self.run(&data, false)?;
+                self.feed(&chunk[end..])
+            }
+            None => {
+                self.pending.extend_from_slice(chunk);
+                Ok(())
+            }
+        }
+    }
+
+    /// Finishes parsing and returns the value.
+    pub fn finish(mut self) -> Result<Value, Error> {
+        let pending = std::mem::take(&mut self.pending);
+        self.run(&pending, true)?;
+        let message = match self.state {
+            State::Done => return Ok(self.builder.finish()),
+            State::Failed => "parse error in previous input",
+            State::Value | State::FirstElement => "EOF while parsing a value",
+            State::FirstKey | State::Key | State::Colon => "EOF while parsing an object",
+            State::Comma if self.stack.last() == Some(&true) => "EOF while parsing an object",
+            State::Comma => "EOF while parsing a list",
+        };
+        Err(Error::parse(message, &[], 0).relocate(self.location))
+    }
+
+    /// Parses the text, buffering the unfinished token at the end unless `eof` is set.
+    fn run(&mut self, data: &[u8], eof: bool) -> Result<(), Error> {
+        let mut pos = 0;
+        let result = self.run_inner(data, eof, &mut pos);
+        if let Err(e) = result {
+            self.state = State::Failed;
+            return Err(e.relocate(self.location));
+        }
+        self.location = advance(self.location, &data[..pos]);
+        self.pending.extend_from_slice(&data[pos..]);
+        Ok(())
+    }
+
+    /// Parses the text from `pos`, leaving `pos` at the unfinished token or the error.
+    ///
+    /// Errors are located in the text.
+    fn run_inner(&mut self, data: &[u8], eof: bool, pos: &mut usize) -> Result<(), Error> {
+        let error = |message: &str, pos: usize| Error::parse(message, data, pos);
+        while let Some(&c) = data.get(*pos) {
+            if matches!(c, b' ' | b'\n' | b'\r' | b'\t') {
+                *pos += 1;
+                continue;
+            }
+            match (self.state, c) {
+                (State::Failed, _) => return Err(error("parse error in previous input", *pos)),
+                (State::Done, _) => return Err(error("trailing characters", *pos)),
+                (State::Value | State::FirstElement, b'{' | b'[') => {
+                    if self.stack.len() >= self.options.max_depth {
+                        return Err(error("recursion limit exceeded", *pos));
+                    }
+                    *pos += 1;
+                    if c == b'{' {
+                        self.builder.begin_object();
+                        self.stack.push(true);
+                        self.state = State::FirstKey;
+                    } else {
+                        self.builder.begin_array();
+                        self.stack.push(false);
+                        self.state = State::FirstElement;
+                    }
+                    continue;
+                }
+                (State::FirstElement, b']') => {
+                    *pos += 1;
+                    self.builder.end_array();
+                    self.stack.pop();
+                }
+                (State::Value | State::FirstElement, _) => {
+                    let token = match c {
+                        b'"' => Token::String { escaped: false },
+                        b'-' | b'0'..=b'9' => Token::Number,
+                        b't' | b'f' | b'n' => Token::Literal,
+                        _ => return Err(error("expected value", *pos)),
+                    };
+                    if !self.parse_token(data, eof, pos, token)? {
+                        return Ok(());
+                    }
+                }
+                (State::FirstKey | State::Key, b'"') => {
+                    if !self.parse_token(data, eof, pos, Token::String { escaped: false })? {
+                        return Ok(());
+                    }
+                    self.state = State::Colon;
+                    continue;
+                }
+                (State::FirstKey, b'}') => {
+                    *pos += 1;
+                    self.builder
+                        .try_end_object()
+                        .map_err(|e| error(&e.to_string(), *pos - 1))?;
+                    self.stack.pop();
+                }
+                (State::FirstKey | State::Key, _) => {
+                    return Err(error("key must be a string", *pos));
+                }
+                (State::Colon, b':') => {
+                    *pos += 1;
+                    self.state = State::Value;
+                    continue;
+                }
+                (State::Colon, _) => return Err(error("expected `:`", *pos)),
+                (State::Comma, _) => {
+                    *pos += 1;
+                    let is_object = self.stack.last() == Some(&true);
+                    match (c, is_object) {
+                        (b',', true) => self.state = State::Key,
+                        (b',', false) => self.state = State::Value,
+                        (b'}', true) => {
+                            self.builder
+                                .try_end_object()
+                                .map_err(|e| error(&e.to_string(), *pos - 1))?;
+                            self.stack.pop();
+                        }
+                        (b']', false) => {
+                            self.builder.end_array();
+                            self.stack.pop();
+                        }
+                        (_, true) => return Err(error("expected `,` or `}`", *pos)),
+                        (_, false) => return Err(error("expected `,` or `]`", *pos)),
+                    }
+                    if c == b',' {
+                        continue;
+                    }
+                }
+            }
+            // a value is finished
+            if self.builder.encoded_len() > self.options.max_size {
+                return Err(error("size limit exceeded", *pos));
+            }
+            self.state = if self.stack.is_empty() {
+                State::Done
+            } else {
+                State::Comma
+            };
+        }
+        Ok(())
+    }
+
+    /// Parses the token at `pos` into the builder.
+    ///
+    /// Returns false if the token may continue after the text and `eof` is not set.
+    fn parse_token(
+        &mut self,
+        data: &[u8],
+        eof: bool,
+        pos: &mut usize,
+        mut token: Token,
+    ) -> Result<bool, Error> {
+        // the first byte is part of the token
+        let rest = &data[*pos..];
+        if !eof && token.find_end(&rest[1..]).is_none() {
+            self.token = token;
+            return Ok(false);
+        }
+        let end = parser::parse_prefix(rest, &mut self.builder, &self.token_options, true)
+            .map_err(|e| e.relocate(advance((1, 1), &data[..*pos])))?
+            .expect("token is not empty");
+        *pos += end;
+        Ok(true)
+    }
+}
+
+impl Token {
+    /// Returns the end of the token in the text following its known part, or `None` if the
+    /// token may continue after the text.
+    ///
+    /// The end includes the closing quote of a string and the byte following a number or a
+    /// literal, so that errors are reported as in [`Value::from_text`].
+    fn find_end(&mut self, text: &[u8]) -> Option<usize> {
+        match self {
+            Token::String { escaped } => {
+                for (i, &c) in text.iter().enumerate() {
+                    match c {
+                        _ if *escaped => *escaped = false,
+                        b'\\' => *escaped = true,
+                        b'"' => return Some(i + 1),
+                        _ => {}
+                    }
+                }
+                None
+            }
+            Token::Number => text
+                .iter()
+                .position(|&c| !matches!(c, b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-'))
+                .map(|i| i + 1),
+            Token::Literal => text
+                .iter()
+                .position(|c| !c.is_ascii_alphabetic())
+                .map(|i| i + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the text split at every possible position, and in single bytes.
+    fn parse_chunks(json: &str) -> Result<Value, String> {
+        let json = json.as_bytes();
+        let mut results = vec![];
+        for i in 0..=json.len() {
+            let mut parser = PushParser::new();
+            let result = parser
+                .feed(&json[..i])
+                .and_then(|_| parser.feed(&json[i..]))
+                .and_then(|_| parser.finish());
+            results.push(result.map_err(|e| e.to_string()));
+        }
+        let mut parser = PushParser::new();
+        let result = json
+            .iter()
+            .try_for_each(|&c| parser.feed(&[c]))
+            .and_then(|_| parser.finish());
+        results.push(result.map_err(|e| e.to_string()));
+        for result in &results[1..] {
+            assert_eq!(result, &results[0], "{}", String::from_utf8_lossy(json));
+        }
+        results.pop().unwrap()
+    }
+
+    #[test]
+    fn values() {
+        for json in [
+            "null",
+            " true ",
+            "false",
+            "0",
+            "-12.5e3",
+            "18446744073709551616",
+            r#""a\"b\\c\u00e9\ud83d\ude00中文""#,
+            "[]",
+            "{}",
+            r#"[1, [2, [3]], {"a": {"b": []}}, "x"]"#,
+            r#"{"b": 1, "a": [true, false, null], "c": {"d": "e"}}"#,
+            "\n[\r\n1\t,\n2\n]\n",
+        ] {
+            let expected = Value::from_text(json.as_bytes()).unwrap();
+            assert_eq!(parse_chunks(json).unwrap(), expected, "{json}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for json in [
+            "",
+            " ",
+            "[",
+            "[1",
+            "[1,",
+            "{",
+            r#"{"a""#,
+            r#"{"a":"#,
+            r#"{"a":1"#,
+            r#""abc"#,
+            "[1 2]",
+            "[1,]",
+            r#"{"a" 1}"#,
+            r#"{1: 2}"#,
+            "{\"a\":1]",
+            "[1}",
+            "nul",
+            "nulll",
+            "tru,",
+            "1.2.3",
+            "-",
+            "01",
+            "1 2",
+            "[\"\\x\"]",
+            "\"\u{1}\"",
+            "\n\n  [1,\n  x]",
+        ] {
+            let expected = Value::from_text(json.as_bytes()).unwrap_err().to_string();
+            assert_eq!(parse_chunks(json).unwrap_err(), expected, "{json}");
+        }
+    }
+
+    #[test]
+    fn options() {
+        let options = ParseOptions::new().max_depth(2);
+        let mut parser = PushParser::with_options(options.clone());
+        parser.feed(b"[[1]]").unwrap();
+        parser.finish().unwrap();
+        let mut parser = PushParser::with_options(options);
+        let err = parser.feed(b"[[[1]]]").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "recursion limit exceeded at line 1 column 3"
+        );
+
+        let options = ParseOptions::new().max_size(32);
+        let mut parser = PushParser::with_options(options.clone());
+        parser.feed(b"[1, 2, 3").unwrap();
+        let err = parser.feed(b", 4, 5, 6]").unwrap_err();
+        let expected = Value::from_text_with(b"[1, 2, 3, 4, 5, 6]", &options).unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string());
+
+        let options = ParseOptions::new().duplicate_key_policy(crate::DuplicateKeyPolicy::Error);
+        let mut parser = PushParser::with_options(options);
+        let err = parser.feed(br#"{"a": 1, "a": 2}"#).unwrap_err();
+        assert!(err.to_string().starts_with("duplicate key"), "{err}");
+        // the parser is unusable after an error
+        assert!(parser.feed(b" ").is_ok());
+        assert!(parser.feed(b"1").is_err());
+        assert!(parser.finish().is_err());
+    }
+}
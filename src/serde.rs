@@ -16,13 +16,29 @@
 
 use std::fmt::{self, Display};
 
-use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
 use serde::ser::{self, Impossible, SerializeMap, SerializeSeq};
+use serde_json::value::RawValue;
 
-use crate::{ArrayRef, Builder, NumberRef, ObjectRef, Value, ValueRef};
+use crate::{ArrayRef, Builder, Error, NumberRef, ObjectRef, Value, ValueRef};
+
+/// The struct name and field name `serde_json` uses to serialize a [`RawValue`].
+const RAW_VALUE_TOKEN: &str = "$serde_json::private::RawValue";
 
 /// Convert a value that `impl Serialize` into `jsonbb::Value`.
-pub fn to_value<T: ser::Serialize>(value: T) -> Result<Value, fmt::Error> {
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// let value = jsonbb::to_value(BTreeMap::from([("a", vec![1.0, 2.5])])).unwrap();
+/// assert_eq!(value.to_string(), r#"{"a":[1.0,2.5]}"#);
+///
+/// let err = jsonbb::to_value(BTreeMap::from([("a", vec![1.0, f64::NAN])])).unwrap_err();
+/// assert_eq!(err.to_string(), "Infinite or NaN values are not JSON numbers: NaN at /a/1");
+/// ```
+pub fn to_value<T: ser::Serialize>(value: T) -> Result<Value, Error> {
     let mut builder = Builder::<Vec<u8>>::new();
     value.serialize(&mut builder)?;
     Ok(builder.finish())
@@ -51,6 +67,9 @@ impl ser::Serialize for ValueRef<'_> {
             Self::String(s) => serializer.serialize_str(s.as_str()),
             Self::Array(v) => v.serialize(serializer),
             Self::Object(o) => o.serialize(serializer),
+            Self::RawJson(r) => RawValue::from_string(r.as_str().to_owned())
+                .map_err(ser::Error::custom)?
+                .serialize(serializer),
         }
     }
 }
@@ -174,8 +193,7 @@ impl<'de, W: AsMut<Vec<u8>>> Visitor<'de> for BuilderVisitor<'_, W> {
         while visitor.next_key_seed(&mut *self.0)?.is_some() {
             visitor.next_value_seed(&mut *self.0)?;
         }
-        self.0.end_object();
-        Ok(())
+        self.0.try_end_object().map_err(de::Error::custom)
     }
 }
 
@@ -196,7 +214,7 @@ impl<'de, W: AsMut<Vec<u8>>> DeserializeSeed<'de> for &mut Builder<W> {
 impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     type Ok = ();
 
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     type SerializeSeq = Self;
 
@@ -268,13 +286,11 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.add_f64(v as _);
-        Ok(())
+        self.try_add_f64(v as _).map_err(Error::serialize)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.add_f64(v as _);
-        Ok(())
+        self.try_add_f64(v as _).map_err(Error::serialize)
     }
 
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
@@ -350,7 +366,9 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
     {
         self.begin_object();
         self.add_string(variant);
-        value.serialize(&mut *self)?;
+        value
+            .serialize(&mut *self)
+            .map_err(|e| e.in_field(variant))?;
         self.end_object();
         Ok(())
     }
@@ -420,7 +438,7 @@ impl<W: AsMut<Vec<u8>>> ser::Serializer for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTuple for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -438,7 +456,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTuple for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTupleStruct for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
@@ -456,14 +474,16 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTupleStruct for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeTupleVariant for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
+        let (level, _) = self.position();
         ser::SerializeSeq::serialize_element(self, value)
+            .map_err(|e| in_key(e, self.key_at(self.container_position(level))))
     }
 
     #[inline]
@@ -476,7 +496,7 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeTupleVariant for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeMap for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
@@ -491,44 +511,56 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeMap for &mut Builder<W> {
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(&mut **self)
+        let position = self.position();
+        value
+            .serialize(&mut **self)
+            .map_err(|e| in_key(e, self.key_at(position)))
     }
 
     #[inline]
     fn end(self) -> Result<(), Self::Error> {
-        self.end_object();
-        Ok(())
+        self.try_end_object().map_err(Error::serialize)
     }
 }
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeStruct for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
+        if key == RAW_VALUE_TOKEN {
+            // the text of a `RawValue`, added without a key and turned into raw JSON by `end`
+            return value.serialize(&mut **self);
+        }
         ser::SerializeMap::serialize_entry(self, key, value)
     }
 
     #[inline]
     fn end(self) -> Result<(), Self::Error> {
+        let (level, len) = self.position();
+        if level > 0 && (len - self.container_position(level).1) % 2 == 1 {
+            return self.end_raw_value().map_err(Error::serialize);
+        }
         ser::SerializeMap::end(self)
     }
 }
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeStructVariant for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
+        let (level, _) = self.position();
         ser::SerializeStruct::serialize_field(self, key, value)
+            .map_err(|e| in_key(e, self.key_at(self.container_position(level))))
     }
 
     #[inline]
@@ -541,14 +573,17 @@ impl<W: AsMut<Vec<u8>>> ser::SerializeStructVariant for &mut Builder<W> {
 
 impl<W: AsMut<Vec<u8>>> ser::SerializeSeq for &mut Builder<W> {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(&mut **self)
+        let position = self.position();
+        value
+            .serialize(&mut **self)
+            .map_err(|e| e.in_field(self.index_at(position)))
     }
 
     #[inline]
@@ -567,7 +602,7 @@ where
     W: AsMut<Vec<u8>>,
 {
     type Ok = ();
-    type Error = std::fmt::Error;
+    type Error = Error;
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
@@ -766,14 +801,21 @@ where
     }
 }
 
-fn key_must_be_a_string() -> std::fmt::Error {
-    // TODO: better error message
-    std::fmt::Error
+fn key_must_be_a_string() -> Error {
+    Error::serialize("key must be a string")
 }
 
-fn invalid_number() -> std::fmt::Error {
-    // TODO: better error message
-    std::fmt::Error
+fn invalid_number() -> Error {
+    Error::serialize("number out of range")
+}
+
+/// Prepends the key to the path of the error, if any.
+#[cold]
+fn in_key(e: Error, key: Option<String>) -> Error {
+    match key {
+        Some(key) => e.in_field(key),
+        None => e,
+    }
 }
 
 #[cfg(test)]
@@ -928,4 +970,47 @@ mod tests {
         test_map_key(42u64);
         // test_map_key(42u128); // not supported by serde_json
     }
+
+    #[test]
+    fn test_to_value_error() {
+        /// Test that serializing `value` fails with `expected` message.
+        #[track_caller]
+        fn test(value: impl Serialize, expected: &str) {
+            let err = to_value(&value).unwrap_err();
+            assert!(matches!(err, crate::Error::Serialize { .. }));
+            assert_eq!(err.to_string(), expected);
+        }
+
+        #[derive(Serialize)]
+        struct TestStruct {
+            id: i32,
+            values: Vec<f64>,
+        }
+
+        #[derive(Serialize)]
+        enum TestEnum {
+            B(f64),
+            C(i32, f64),
+            D { x: f64 },
+        }
+
+        let nan = "Infinite or NaN values are not JSON numbers: NaN";
+        test(f64::NAN, nan);
+        test(vec![1.0, f64::NAN], &format!("{nan} at /1"));
+        let s = TestStruct {
+            id: 1,
+            values: vec![1.0, 2.0, f64::NAN],
+        };
+        test(&s, &format!("{nan} at /values/2"));
+        test([(), ()].map(|_| &s), &format!("{nan} at /0/values/2"));
+        test(TestEnum::B(f64::NAN), &format!("{nan} at /B"));
+        test(TestEnum::C(1, f64::NAN), &format!("{nan} at /C/1"));
+        test(TestEnum::D { x: f64::NAN }, &format!("{nan} at /D/x"));
+        test(
+            HashMap::from([("a/b~", vec![TestEnum::D { x: f64::NAN }])]),
+            &format!("{nan} at /a~1b~0/0/D/x"),
+        );
+        test(HashMap::from([((), 1)]), "key must be a string");
+        test(vec![vec![u128::MAX]], "number out of range at /0/0");
+    }
 }
@@ -0,0 +1,328 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Source locations of the values in JSON text.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// The location of a value in the JSON text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    /// The byte offset of the first byte of the value.
+    pub start: usize,
+    /// The byte offset after the last byte of the value.
+    pub end: usize,
+    /// The 1-based line number of the first byte.
+    pub line: usize,
+    /// The 1-based column number of the first byte, counted in bytes.
+    pub column: usize,
+}
+
+impl Span {
+    /// Returns the byte range of the value.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+/// The source locations of all values in JSON text, looked up by JSON pointer.
+///
+/// The pointers are the same as accepted by [`ValueRef::pointer`], with `""` for the root value.
+/// If an object has duplicate keys, only the locations of the last one and the values in it are
+/// kept.
+///
+/// Returned by [`Value::from_text_with_spans`].
+///
+/// [`ValueRef::pointer`]: crate::ValueRef::pointer
+/// [`Value::from_text_with_spans`]: crate::Value::from_text_with_spans
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Spans {
+    /// The values in the order they start, with the root value first.
+    ///
+    /// Values under a replaced duplicate key are left unreachable.
+    nodes: Vec<Node>,
+    /// The number of reachable values.
+    len: usize,
+}
+
+/// A value and its children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    span: Span,
+    children: Children,
+}
+
+/// The indices of the children of a value in [`Spans::nodes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Children {
+    None,
+    Array(Vec<usize>),
+    Object(HashMap<Box<str>, usize>),
+}
+
+impl Spans {
+    /// Returns the location of the value at the JSON pointer.
+    pub fn get(&self, pointer: &str) -> Option<Span> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        if pointer.is_empty() {
+            return Some(self.nodes[0].span);
+        }
+        if !pointer.starts_with('/') {
+            return None;
+        }
+        let mut node = &self.nodes[0];
+        for token in pointer.split('/').skip(1) {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let index = match &node.children {
+                Children::Object(map) => *map.get(token.as_str())?,
+                Children::Array(list) => {
+                    if token.starts_with('+') || (token.starts_with('0') && token.len() != 1) {
+                        return None;
+                    }
+                    *list.get(token.parse::<usize>().ok()?)?
+                }
+                Children::None => return None,
+            };
+            node = &self.nodes[index];
+        }
+        Some(node.span)
+    }
+
+    /// Returns an iterator over the JSON pointers and locations of all values, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, Span)> + '_ {
+        // (index, length of the pointer to the parent, token)
+        let mut stack = vec![];
+        if !self.nodes.is_empty() {
+            stack.push((0, 0, String::new()));
+        }
+        let mut pointer = String::new();
+        std::iter::from_fn(move || {
+            let (index, parent_len, token) = stack.pop()?;
+            pointer.truncate(parent_len);
+            pointer.push_str(&token);
+            let node = &self.nodes[index];
+            let len = pointer.len();
+            match &node.children {
+                Children::None => {}
+                Children::Array(list) => stack.extend(
+                    list.iter()
+                        .enumerate()
+                        .map(|(i, &c)| (c, len, format!("/{i}"))),
+                ),
+                Children::Object(map) => stack.extend(map.iter().map(|(k, &c)| {
+                    (
+                        c,
+                        len,
+                        format!("/{}", k.replace('~', "~0").replace('/', "~1")),
+                    )
+                })),
+            }
+            Some((pointer.clone(), node.span))
+        })
+    }
+
+    /// Returns the number of values.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there is no value.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of values in the subtree of the node.
+    fn subtree_len(&self, index: usize) -> usize {
+        1 + match &self.nodes[index].children {
+            Children::None => 0,
+            Children::Array(list) => list.iter().map(|&c| self.subtree_len(c)).sum(),
+            Children::Object(map) => map.values().map(|&c| self.subtree_len(c)).sum(),
+        }
+    }
+}
+
+/// Records the locations of values while parsing.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    spans: Spans,
+    /// The indices of the containers being parsed.
+    stack: Vec<usize>,
+    /// The key of the next value in an object.
+    key: Option<Box<str>>,
+    /// The index of the last started value.
+    current: usize,
+    /// The offset up to which lines have been counted.
+    scanned: usize,
+    /// The 1-based line number at `scanned`.
+    line: usize,
+    /// The offset of the first byte of that line.
+    line_start: usize,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Recorder {
+            line: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the key of the next value.
+    pub fn key(&mut self, key: String) {
+        self.key = Some(key.into());
+    }
+
+    /// Starts a value at the offset, and adds it to the enclosing container.
+    pub fn start(&mut self, json: &[u8], offset: usize) {
+        for (i, &c) in json[self.scanned..offset].iter().enumerate() {
+            if c == b'\n' {
+                self.line += 1;
+                self.line_start = self.scanned + i + 1;
+            }
+        }
+        self.scanned = offset;
+        let index = self.spans.nodes.len();
+        self.spans.nodes.push(Node {
+            span: Span {
+                start: offset,
+                end: offset,
+                line: self.line,
+                column: offset - self.line_start + 1,
+            },
+            children: Children::None,
+        });
+        self.spans.len += 1;
+        self.current = index;
+        let Some(&parent) = self.stack.last() else {
+            return;
+        };
+        let replaced = match &mut self.spans.nodes[parent].children {
+            Children::Array(list) => {
+                list.push(index);
+                None
+            }
+            Children::Object(map) => {
+                let key = self.key.take().expect("missing key");
+                map.insert(key, index)
+            }
+            Children::None => unreachable!("not a container"),
+        };
+        if let Some(replaced) = replaced {
+            self.spans.len -= self.spans.subtree_len(replaced);
+        }
+    }
+
+    /// Makes the last started value a container and enters it.
+    pub fn enter(&mut self, is_object: bool) {
+        self.spans.nodes[self.current].children = if is_object {
+            Children::Object(HashMap::new())
+        } else {
+            Children::Array(vec![])
+        };
+        self.stack.push(self.current);
+    }
+
+    /// Ends the last started value at the offset, which is not a container being parsed.
+    pub fn end(&mut self, offset: usize) {
+        self.spans.nodes[self.current].span.end = offset;
+    }
+
+    /// Ends the innermost container at the offset.
+    pub fn exit(&mut self, offset: usize) {
+        let index = self.stack.pop().expect("no container");
+        self.spans.nodes[index].span.end = offset;
+    }
+
+    pub fn finish(self) -> Spans {
+        self.spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Value;
+
+    #[test]
+    fn spans() {
+        let json = r#"{
+  "a": [1, -2.5e3, "x\"y"],
+  "b/c": {"~d": true, "ef": null},
+  "g": [[], {}, [{"h": false}]]
+}"#;
+        let (value, spans) = Value::from_text_with_spans(json.as_bytes()).unwrap();
+        assert_eq!(spans.len(), 14);
+        assert_eq!(spans.iter().count(), 14);
+        for (pointer, span) in spans.iter() {
+            let expected = value.pointer(&pointer).unwrap();
+            let actual = Value::from_text(&json.as_bytes()[span.range()]).unwrap();
+            assert_eq!(actual.as_ref(), expected, "{pointer}");
+            let line_start = json[..span.start].rfind('\n').map_or(0, |i| i + 1);
+            assert_eq!(span.line, json[..span.start].matches('\n').count() + 1);
+            assert_eq!(span.column, span.start - line_start + 1);
+        }
+        let span = spans.get("/b~1c/~0d").unwrap();
+        assert_eq!((span.line, span.column), (3, 17));
+        assert_eq!(&json[span.range()], "true");
+        assert_eq!(&json[spans.get("/b~1c/ef").unwrap().range()], "null");
+        assert_eq!(&json[spans.get("/a/2").unwrap().range()], r#""x\"y""#);
+        assert_eq!(
+            &json[spans.get("/g/2/0").unwrap().range()],
+            r#"{"h": false}"#
+        );
+        assert_eq!(spans.get("").unwrap().range(), 0..json.len());
+        for pointer in ["/x", "a", "/a/3", "/a/01", "/a/+1", "/a/0/0"] {
+            assert_eq!(spans.get(pointer), None, "{pointer}");
+        }
+    }
+
+    #[test]
+    fn scalars() {
+        for json in ["1", " \"s\" ", "\ntrue\n"] {
+            let (value, spans) = Value::from_text_with_spans(json.as_bytes()).unwrap();
+            let span = spans.get("").unwrap();
+            assert_eq!(spans.len(), 1);
+            assert_eq!(json[span.range()].parse::<Value>().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let json = r#"{"a": 1, "a": [2]}"#;
+        let (value, spans) = Value::from_text_with_spans(json.as_bytes()).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[2]}"#);
+        assert_eq!(&json[spans.get("/a").unwrap().range()], "[2]");
+        assert!(spans.get("/a/0").is_some());
+
+        // values under a replaced key are dropped
+        let json = r#"{"a": [1, {"b": 2}], "c": {"a": 3, "a": 4}, "a": 5}"#;
+        let (value, spans) = Value::from_text_with_spans(json.as_bytes()).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":5,"c":{"a":4}}"#);
+        let mut pointers: Vec<_> = spans.iter().map(|(p, _)| p).collect();
+        pointers.sort();
+        assert_eq!(pointers, ["", "/a", "/c", "/c/a"]);
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans.get("/a/0"), None);
+        assert_eq!(&json[spans.get("/a").unwrap().range()], "5");
+        assert_eq!(&json[spans.get("/c/a").unwrap().range()], "4");
+    }
+
+    #[test]
+    fn invalid() {
+        let err = Value::from_text_with_spans(b"[1, {\"a\": }]").unwrap_err();
+        assert_eq!(err.to_string(), "expected value at line 1 column 11");
+    }
+}
@@ -0,0 +1,676 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reading a stream of JSON documents.
+
+use std::io::{self, Read};
+use std::ops::Range;
+
+use crate::error::advance;
+use crate::parser::{self, ParseOptions};
+use crate::{Builder, Error, Value};
+
+/// The minimum number of bytes to read at a time.
+const READ_SIZE: usize = 8 * 1024;
+
+/// How documents are delimited in a [`StreamReader`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamFormat {
+    /// Documents separated by any whitespace, or not separated at all if unambiguous.
+    ///
+    /// An error stops the stream since the end of the invalid document is unknown.
+    #[default]
+    Concatenated,
+    /// One document per line, also known as NDJSON or JSON Lines. Blank lines are skipped.
+    ///
+    /// An error only affects its line and reading continues with the next line.
+    Lines,
+}
+
+/// A reader that parses a stream of JSON documents.
+///
+/// The reader is an iterator of [`Value`]s. To avoid allocating for each document,
+/// [`read_into`](Self::read_into) appends the documents into a shared buffer instead.
+///
+/// Errors report the line and column in the whole stream.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::{StreamFormat, StreamReader};
+///
+/// let text = "{\"a\": 1}\n[2]\n\n\"3\"\n";
+/// let reader = StreamReader::new(text.as_bytes()).with_format(StreamFormat::Lines);
+/// let values = reader.collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(values.len(), 3);
+/// assert_eq!(values[1].to_string(), "[2]");
+///
+/// let mut reader = StreamReader::new("1 2 [".as_bytes());
+/// assert_eq!(reader.next().unwrap().unwrap().as_u64(), Some(1));
+/// assert_eq!(reader.next().unwrap().unwrap().as_u64(), Some(2));
+/// let err = reader.next().unwrap().unwrap_err();
+/// assert_eq!(err.to_string(), "EOF while parsing a value at line 1 column 6");
+/// assert!(reader.next().is_none());
+/// ```
+pub struct StreamReader<R> {
+    reader: R,
+    format: StreamFormat,
+    options: ParseOptions,
+    /// The bytes read but not parsed yet, starting from `pos`.
+    buf: Vec<u8>,
+    pos: usize,
+    /// The number of bytes after `pos` known to contain no newline.
+    scanned: usize,
+    /// The progress of scanning for the end of the document at `pos`.
+    scan: Scan,
+    /// Whether the reader has reached the end.
+    eof: bool,
+    /// Whether the stream is stopped by an error.
+    stopped: bool,
+    /// Whether the rest of the current line is skipped after it exceeded the size limit.
+    skipping: bool,
+    /// The 1-based line and column of `pos` in the stream.
+    location: (usize, usize),
+}
+
+impl<R: Read> StreamReader<R> {
+    /// Creates a new reader of whitespace-concatenated JSON documents.
+    pub fn new(reader: R) -> Self {
+        StreamReader {
+            reader,
+            format: StreamFormat::default(),
+            options: ParseOptions::default(),
+            buf: Vec::new(),
+            pos: 0,
+            scanned: 0,
+            scan: Scan::default(),
+            eof: false,
+            stopped: false,
+            skipping: false,
+            location: (1, 1),
+        }
+    }
+
+    /// Sets how documents are delimited.
+    pub fn with_format(mut self, format: StreamFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the options to parse each document with.
+    ///
+    /// The size limit applies to each document. In [`StreamFormat::Lines`], it also limits the
+    /// length of each line, so that a long line is rejected without being buffered as a whole.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Returns the underlying reader.
+    ///
+    /// Bytes that have been read but not parsed yet are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Parses the next document and appends its jsonbb encoding to the buffer.
+    ///
+    /// Returns the range of the document in the buffer, or `None` at the end of the stream.
+    /// The buffer is left unchanged on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{StreamReader, ValueRef};
+    ///
+    /// let mut reader = StreamReader::new(r#"{"a": 1} {"a": 2}"#.as_bytes());
+    /// let mut buffer = vec![];
+    /// let mut ranges = vec![];
+    /// while let Some(range) = reader.read_into(&mut buffer) {
+    ///     ranges.push(range.unwrap());
+    /// }
+    /// let value = ValueRef::from_bytes(&buffer[ranges[1].clone()]);
+    /// assert_eq!(value.to_string(), r#"{"a":2}"#);
+    /// ```
+    pub fn read_into(&mut self, buffer: &mut Vec<u8>) -> Option<Result<Range<usize>, Error>> {
+        let start = buffer.len();
+        let result = self.read_with(|json, options, eof| {
+            buffer.truncate(start);
+            let mut builder = options.apply(Builder::<&mut Vec<u8>>::appending(buffer));
+            let Some(end) = parser::parse_prefix(json, &mut builder, options, eof)? else {
+                return Ok(None);
+            };
+            builder.finish();
+            Ok(Some((end, start..buffer.len())))
+        });
+        if !matches!(result, Some(Ok(_))) {
+            buffer.truncate(start);
+        }
+        result
+    }
+
+    /// Parses the next document with the given function.
+    ///
+    /// The function parses the first document of the text, see [`parser::parse_prefix`].
+    fn read_with<T>(
+        &mut self,
+        mut parse: impl FnMut(&[u8], &ParseOptions, bool) -> Result<Option<(usize, T)>, Error>,
+    ) -> Option<Result<T, Error>> {
+        if self.stopped {
+            return None;
+        }
+        let result = match self.format {
+            StreamFormat::Concatenated => self.read_document(&mut parse),
+            StreamFormat::Lines => self.read_line(&mut parse),
+        };
+        match result {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.stopped = true;
+                None
+            }
+            Err(e) => {
+                if self.format == StreamFormat::Concatenated || matches!(e, Error::Io(_)) {
+                    self.stopped = true;
+                }
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn read_document<T>(
+        &mut self,
+        parse: &mut impl FnMut(&[u8], &ParseOptions, bool) -> Result<Option<(usize, T)>, Error>,
+    ) -> Result<Option<T>, Error> {
+        loop {
+            // parse only when the document may be complete, or when the buffered text has doubled
+            // so that the size limit is still checked, to parse each byte a bounded number of times
+            let len = self.buf.len() - self.pos;
+            let complete = self.eof || self.scan_document();
+            if complete || len >= READ_SIZE.max(2 * self.scan.parsed) {
+                match parse(&self.buf[self.pos..], &self.options, self.eof) {
+                    Ok(Some((end, value))) => {
+                        self.consume(end);
+                        return Ok(Some(value));
+                    }
+                    Ok(None) if self.eof => return Ok(None),
+                    Ok(None) => self.scan.parsed = len,
+                    Err(e) => return Err(e.relocate(self.location)),
+                }
+            }
+            // a possible end may be followed by more bytes to scan
+            if !complete || self.scan.len == len {
+                self.fill()?;
+            }
+        }
+    }
+
+    /// Scans the document at `pos` for its end, resuming where the last scan stopped.
+    ///
+    /// Returns `true` if the document may be complete in the scanned bytes. Only containers,
+    /// strings and comments are tracked, and the syntax is left to the parser.
+    fn scan_document(&mut self) -> bool {
+        let json5 = self.options.json5;
+        let scan = &mut self.scan;
+        let json = &self.buf[self.pos..];
+        while let Some(&c) = json.get(scan.len) {
+            scan.len += 1;
+            match scan.state {
+                ScanState::String { quote, escaped } => {
+                    if escaped {
+                        scan.state = ScanState::String {
+                            quote,
+                            escaped: false,
+                        };
+                    } else if c == b'\\' {
+                        scan.state = ScanState::String {
+                            quote,
+                            escaped: true,
+                        };
+                    } else if c == quote {
+                        scan.state = ScanState::Value;
+                        if scan.depth == 0 {
+                            return true;
+                        }
+                    }
+                }
+                ScanState::LineComment if c == b'\n' => scan.state = ScanState::Value,
+                ScanState::LineComment => {}
+                ScanState::BlockComment { star } if star && c == b'/' => {
+                    scan.state = ScanState::Value;
+                }
+                ScanState::BlockComment { .. } => {
+                    scan.state = ScanState::BlockComment { star: c == b'*' };
+                }
+                ScanState::Scalar { .. }
+                    if c.is_ascii_alphanumeric() || matches!(c, b'+' | b'-' | b'.') => {}
+                ScanState::Scalar { .. } => {
+                    // the delimiter is scanned again as part of what follows
+                    scan.len -= 1;
+                    scan.state = ScanState::Value;
+                    return true;
+                }
+                ScanState::Value => match c {
+                    b'"' => {
+                        scan.state = ScanState::String {
+                            quote: c,
+                            escaped: false,
+                        }
+                    }
+                    b'\'' if json5 => {
+                        scan.state = ScanState::String {
+                            quote: c,
+                            escaped: false,
+                        }
+                    }
+                    b'/' if json5 => match json.get(scan.len) {
+                        Some(b'/') => {
+                            scan.len += 1;
+                            scan.state = ScanState::LineComment;
+                        }
+                        Some(b'*') => {
+                            scan.len += 1;
+                            scan.state = ScanState::BlockComment { star: false };
+                        }
+                        Some(_) => return true,
+                        None => {
+                            scan.len -= 1;
+                            return false;
+                        }
+                    },
+                    b'{' | b'[' => scan.depth += 1,
+                    b'}' | b']' if scan.depth > 1 => scan.depth -= 1,
+                    b'}' | b']' => {
+                        scan.depth = 0;
+                        return true;
+                    }
+                    c if c.is_ascii_whitespace() => {}
+                    _ if scan.depth == 0 => {
+                        scan.state = ScanState::Scalar {
+                            number: !c.is_ascii_alphabetic(),
+                        };
+                    }
+                    _ => {}
+                },
+            }
+        }
+        // a literal may be complete at the end, but a number may continue
+        matches!(scan.state, ScanState::Scalar { number: false })
+    }
+
+    fn read_line<T>(
+        &mut self,
+        parse: &mut impl FnMut(&[u8], &ParseOptions, bool) -> Result<Option<(usize, T)>, Error>,
+    ) -> Result<Option<T>, Error> {
+        let max_size = self.options.max_size;
+        loop {
+            let rest = &self.buf[self.pos + self.scanned..];
+            let newline = rest.iter().position(|&c| c == b'\n');
+            if self.skipping {
+                // the rest of a line that exceeded the size limit
+                match newline {
+                    Some(i) => {
+                        self.consume(self.scanned + i + 1);
+                        self.skipping = false;
+                    }
+                    None if self.eof => return Ok(None),
+                    None => {
+                        self.consume(self.buf.len() - self.pos);
+                        self.fill()?;
+                    }
+                }
+                continue;
+            }
+            let len = match newline {
+                Some(i) => self.scanned + i,
+                None if self.eof && self.pos == self.buf.len() => return Ok(None),
+                None if self.eof => self.buf.len() - self.pos,
+                None if self.buf.len() - self.pos > max_size => {
+                    let e = Error::parse("size limit exceeded", &self.buf[self.pos..], max_size);
+                    let e = e.relocate(self.location);
+                    self.consume(self.buf.len() - self.pos);
+                    self.skipping = true;
+                    return Err(e);
+                }
+                None => {
+                    self.scanned = self.buf.len() - self.pos;
+                    self.fill()?;
+                    continue;
+                }
+            };
+            let line = &self.buf[self.pos..self.pos + len];
+            // a line over the size limit is rejected without parsing it
+            let parsed = if len > max_size {
+                Err(Error::parse("size limit exceeded", line, max_size))
+            } else {
+                parse(line, &self.options, true)
+            };
+            let result = match parsed {
+                Ok(Some((end, value))) => {
+                    match line[end..].iter().position(|c| !c.is_ascii_whitespace()) {
+                        Some(i) => {
+                            let e = Error::parse("trailing characters", line, end + i);
+                            Err(e.relocate(self.location))
+                        }
+                        None => Ok(Some(value)),
+                    }
+                }
+                Ok(None) => Ok(None),
+                Err(e) => Err(e.relocate(self.location)),
+            };
+            // skip the newline
+            self.consume((len + 1).min(self.buf.len() - self.pos));
+            match result {
+                Ok(None) => continue, // blank line
+                result => return result,
+            }
+        }
+    }
+
+    /// Reads more bytes into the buffer, discarding the parsed bytes.
+    fn fill(&mut self) -> io::Result<()> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        let len = self.buf.len();
+        // documents are parsed once they may be complete, so a fixed amount is read at a time
+        self.buf.resize(len + READ_SIZE, 0);
+        let result = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        let n = *result.as_ref().unwrap_or(&0);
+        self.buf.truncate(len + n);
+        self.eof = n == 0;
+        result.map(|_| ())
+    }
+
+    /// Advances the position over the parsed bytes.
+    fn consume(&mut self, len: usize) {
+        self.location = advance(self.location, &self.buf[self.pos..self.pos + len]);
+        self.pos += len;
+        self.scanned = 0;
+        self.scan = Scan::default();
+    }
+}
+
+/// The progress of scanning a document in [`StreamFormat::Concatenated`].
+#[derive(Debug, Default)]
+struct Scan {
+    /// The number of bytes scanned after the start of the document.
+    len: usize,
+    /// The nesting depth of containers.
+    depth: usize,
+    state: ScanState,
+    /// The number of bytes buffered when the document was last parsed and found incomplete.
+    parsed: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    /// Between tokens.
+    #[default]
+    Value,
+    /// In a string, with whether the last byte is an unfinished escape.
+    String { quote: u8, escaped: bool },
+    /// In a `//` comment.
+    LineComment,
+    /// In a `/* */` comment, with whether the last byte is `*`.
+    BlockComment { star: bool },
+    /// In a number or literal at the top level.
+    Scalar { number: bool },
+}
+
+impl<R: Read> Iterator for StreamReader<R> {
+    type Item = Result<Value, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_with(|json, options, eof| {
+            let mut builder = options.builder(0);
+            let end = parser::parse_prefix(json, &mut builder, options, eof)?;
+            Ok(end.map(|end| (end, builder.finish())))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader that returns at most one byte at a time.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn read_all(reader: impl Read, format: StreamFormat) -> Vec<Result<String, String>> {
+        StreamReader::new(reader)
+            .with_format(format)
+            .map(|r| r.map(|v| v.to_string()).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn concatenated() {
+        let text = "1 2.5\n[\"a\",\n{\"b\": null}]{\"c\":\"中文\"}\"s\"true\n -3 ";
+        let expected = [
+            "1",
+            "2.5",
+            r#"["a",{"b":null}]"#,
+            r#"{"c":"中文"}"#,
+            r#""s""#,
+            "true",
+            "-3",
+        ];
+        for values in [
+            read_all(text.as_bytes(), StreamFormat::Concatenated),
+            read_all(Trickle(text.as_bytes()), StreamFormat::Concatenated),
+        ] {
+            let values: Vec<_> = values.into_iter().map(Result::unwrap).collect();
+            assert_eq!(values, expected);
+        }
+        assert!(read_all(" \n ".as_bytes(), StreamFormat::Concatenated).is_empty());
+    }
+
+    #[test]
+    fn concatenated_scan() {
+        // brackets and quotes in strings and comments do not end a document early
+        let text = "{a: '}]\\'', /* } */ b: \"\\\"]\"} // ]\n[1]/**/'x'+1 null";
+        let expected = [r#"{"a":"}]'","b":"\"]"}"#, "[1]", r#""x""#, "1", "null"];
+        let options = ParseOptions::new().json5(true);
+        for reader in [
+            &mut text.as_bytes() as &mut dyn Read,
+            &mut Trickle(text.as_bytes()),
+        ] {
+            let values: Vec<_> = StreamReader::new(reader)
+                .with_options(options.clone())
+                .map(|r| r.unwrap().to_string())
+                .collect();
+            assert_eq!(values, expected);
+        }
+
+        // a document read a byte at a time is parsed a few times rather than once per byte
+        let text = format!("[{}] [2]", ["1"; 3000].join(","));
+        let mut reader = StreamReader::new(Trickle(text.as_bytes()));
+        let mut parses = 0;
+        let value = reader.read_with(|json, options, eof| {
+            parses += 1;
+            let mut builder = options.builder(0);
+            let end = parser::parse_prefix(json, &mut builder, options, eof)?;
+            Ok(end.map(|end| (end, builder.finish())))
+        });
+        assert_eq!(value.unwrap().unwrap().as_array().unwrap().len(), 3000);
+        assert!(parses <= 3, "{parses}");
+
+        // the size limit is checked before the document ends
+        let text = format!("[{}]", "1,".repeat(1 << 20));
+        let mut reader =
+            StreamReader::new(text.as_bytes()).with_options(ParseOptions::new().max_size(64));
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(err.to_string().starts_with("size limit exceeded"), "{err}");
+        assert!(reader.buf.capacity() < 1 << 16);
+    }
+
+    #[test]
+    fn lines() {
+        let text = "{\"a\": 1}\r\n\n  [2, 3]  \n\"x\" \"y\"\n{\"b\":\n4\n5";
+        for values in [
+            read_all(text.as_bytes(), StreamFormat::Lines),
+            read_all(Trickle(text.as_bytes()), StreamFormat::Lines),
+        ] {
+            assert_eq!(
+                values,
+                [
+                    Ok(r#"{"a":1}"#.into()),
+                    Ok("[2,3]".into()),
+                    Err("trailing characters at line 4 column 5".into()),
+                    Err("EOF while parsing a value at line 5 column 6".into()),
+                    Ok("4".into()),
+                    Ok("5".into()),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn error_location() {
+        let text = "[1]\n  {\"a\": 1,\n  \"b\" 2}\n3";
+        for values in [
+            read_all(text.as_bytes(), StreamFormat::Concatenated),
+            read_all(Trickle(text.as_bytes()), StreamFormat::Concatenated),
+        ] {
+            assert_eq!(
+                values,
+                [
+                    Ok("[1]".into()),
+                    Err("expected `:` at line 3 column 7".into()),
+                ]
+            );
+        }
+        let values = read_all("[1] [2, x]".as_bytes(), StreamFormat::Concatenated);
+        assert_eq!(values[1], Err("expected value at line 1 column 9".into()));
+    }
+
+    #[test]
+    fn read_into() {
+        let text = "{\"a\": [1, 2]}\n\"b\"\n[{}]\n";
+        let mut reader = StreamReader::new(Trickle(text.as_bytes()));
+        let mut buffer = b"prefix".to_vec();
+        let mut ranges = vec![];
+        while let Some(range) = reader.read_into(&mut buffer) {
+            ranges.push(range.unwrap());
+        }
+        let values: Vec<_> = ranges
+            .into_iter()
+            .map(|range| crate::ValueRef::from_bytes(&buffer[range]).to_string())
+            .collect();
+        assert_eq!(values, [r#"{"a":[1,2]}"#, r#""b""#, "[{}]"]);
+
+        // the buffer is unchanged on error
+        let mut reader = StreamReader::new("[1, 2".as_bytes());
+        let mut buffer = vec![];
+        assert!(reader.read_into(&mut buffer).unwrap().is_err());
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn options() {
+        let text = "{a: 1}\n[1, 2, 3, 4, 5, 6]\n";
+        let options = ParseOptions::new().json5(true).max_size(32);
+        let values: Vec<_> = StreamReader::new(text.as_bytes())
+            .with_format(StreamFormat::Lines)
+            .with_options(options)
+            .map(|r| r.is_ok())
+            .collect();
+        assert_eq!(values, [true, false]);
+    }
+
+    #[test]
+    fn long_line() {
+        let text = "[1]\n"
+            .as_bytes()
+            .chain(io::repeat(b' ').take(1 << 20))
+            .chain("\n[2]\n".as_bytes());
+        let mut reader = StreamReader::new(text)
+            .with_format(StreamFormat::Lines)
+            .with_options(ParseOptions::new().max_size(64));
+        assert_eq!(reader.next().unwrap().unwrap().to_string(), "[1]");
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "size limit exceeded at line 2 column 65");
+        assert_eq!(reader.next().unwrap().unwrap().to_string(), "[2]");
+        assert!(reader.next().is_none());
+        // the long line is not buffered as a whole
+        assert!(reader.buf.capacity() < 1 << 16);
+
+        // a terminated line is limited as well
+        let text = format!("{:100}\n[3]", "4");
+        let values: Vec<_> = StreamReader::new(text.as_bytes())
+            .with_format(StreamFormat::Lines)
+            .with_options(ParseOptions::new().max_size(64))
+            .map(|r| r.map(|v| v.to_string()).map_err(|e| e.to_string()))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                Err("size limit exceeded at line 1 column 65".into()),
+                Ok("[3]".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn complete_at_end_of_buffer() {
+        /// A reader that returns the chunks one per read and then fails.
+        struct Chunks<'a>(&'a [&'a str]);
+        impl Read for Chunks<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let Some((chunk, rest)) = self.0.split_first() else {
+                    return Err(io::Error::other("broken"));
+                };
+                buf[..chunk.len()].copy_from_slice(chunk.as_bytes());
+                self.0 = rest;
+                Ok(chunk.len())
+            }
+        }
+        // documents are returned without reading past them, except for a number
+        let mut reader = StreamReader::new(Chunks(&["[1]", " {\"a\"", ":2}", "\"s\"true", "3"]));
+        let values: Vec<_> = reader
+            .by_ref()
+            .take(4)
+            .map(|r| r.unwrap().to_string())
+            .collect();
+        assert_eq!(values, ["[1]", r#"{"a":2}"#, r#""s""#, "true"]);
+        assert!(matches!(reader.next(), Some(Err(Error::Io(_)))));
+    }
+
+    #[test]
+    fn io_error() {
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("broken"))
+            }
+        }
+        let mut reader = StreamReader::new(Failing).with_format(StreamFormat::Lines);
+        assert!(matches!(reader.next(), Some(Err(Error::Io(_)))));
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of untrusted jsonbb encoding.
+
+use std::ops::Range;
+
+use bytes::Buf;
+
+use crate::entry::*;
+use crate::value_ref::{OBJECT_LENGTH_FIRST, OBJECT_PRESERVE_ORDER};
+use crate::{Entry, Error, KeyOrder};
+
+/// Checks that the bytes are a valid jsonbb encoding, so that reading the value never panics.
+///
+/// Every entry must point into the data before it. Children of a container must point into
+/// disjoint ranges of the data of the container, so that every byte is checked at most once and
+/// validation takes linear time.
+pub(crate) fn validate(bytes: &[u8]) -> Result<(), Error> {
+    if bytes.len() < 4 {
+        return Err(Error::decode("missing root entry"));
+    }
+    let (data, root) = bytes.split_at(bytes.len() - 4);
+    // the values to check, with the data that their entries point into
+    let mut stack = vec![(data, Entry::from(root))];
+    while let Some((data, entry)) = stack.pop() {
+        match entry.tag() {
+            Entry::NULL_TAG | Entry::FALSE_TAG | Entry::TRUE_TAG => {}
+            Entry::NUMBER_TAG => validate_number(data, entry.offset())?,
+            Entry::STRING_TAG => _ = validate_string(data, entry.offset())?,
+            Entry::RAW_JSON_TAG => {
+                let json = validate_string(data, entry.offset())?;
+                if crate::parser::check_syntax(json.as_bytes()).is_err() {
+                    return Err(Error::decode("invalid raw JSON"));
+                }
+                // the value after the text is a complete encoding
+                let value_start = entry.offset() + 4 + json.len();
+                let value = data
+                    .get(value_start..value_start + 4)
+                    .map(|len| value_start + 4..value_start + 4 + get_u32(len, 0) as usize)
+                    .and_then(|range| data.get(range))
+                    .ok_or_else(|| Error::decode("raw JSON value out of bounds"))?;
+                if value.len() < 4 {
+                    return Err(Error::decode("missing raw JSON value"));
+                }
+                let (value_data, root) = value.split_at(value.len() - 4);
+                stack.push((value_data, Entry::from(root)));
+            }
+            Entry::ARRAY_TAG => {
+                let array = container(data, entry.offset())?;
+                let len = get_u32(array, array.len() - 8) as usize;
+                let entries_start = (array.len() - 8)
+                    .checked_sub(4 * len)
+                    .ok_or_else(|| Error::decode("array length out of bounds"))?;
+                let children = &array[..entries_start];
+                let mut ranges = Vec::with_capacity(len);
+                for i in 0..len {
+                    let entry = Entry::from(&array[entries_start + 4 * i..][..4]);
+                    ranges.push(extent(children, entry)?);
+                    stack.push((children, entry));
+                }
+                check_disjoint(ranges)?;
+            }
+            Entry::OBJECT_TAG => {
+                let object = container(data, entry.offset())?;
+                let raw_len = get_u32(object, object.len() - 8);
+                let len = (raw_len & !(OBJECT_LENGTH_FIRST | OBJECT_PRESERVE_ORDER)) as usize;
+                let order = if raw_len & OBJECT_LENGTH_FIRST != 0 {
+                    KeyOrder::LengthFirst
+                } else {
+                    KeyOrder::Lexicographic
+                };
+                let permutation_len = if raw_len & OBJECT_PRESERVE_ORDER != 0 {
+                    4 * len
+                } else {
+                    0
+                };
+                let entries_start = (object.len() - 8)
+                    .checked_sub(permutation_len + 8 * len)
+                    .ok_or_else(|| Error::decode("object length out of bounds"))?;
+                let children = &object[..entries_start];
+                let mut ranges = Vec::with_capacity(2 * len);
+                let mut prev_key = None;
+                for i in 0..len {
+                    let kentry = Entry::from(&object[entries_start + 8 * i..][..4]);
+                    let ventry = Entry::from(&object[entries_start + 8 * i + 4..][..4]);
+                    if kentry.tag() != Entry::STRING_TAG {
+                        return Err(Error::decode("object key is not a string"));
+                    }
+                    let key = validate_string(children, kentry.offset())?;
+                    if prev_key.is_some_and(|prev| order.cmp(prev, key).is_ge()) {
+                        return Err(Error::decode("object keys are not sorted or unique"));
+                    }
+                    prev_key = Some(key);
+                    ranges.push(extent(children, kentry)?);
+                    ranges.push(extent(children, ventry)?);
+                    stack.push((children, ventry));
+                }
+                check_disjoint(ranges)?;
+                if permutation_len != 0 {
+                    let permutation = &object[object.len() - 8 - permutation_len..];
+                    let mut seen = vec![false; len];
+                    for i in 0..len {
+                        let index = get_u32(permutation, 4 * i) as usize;
+                        if index >= len || std::mem::replace(&mut seen[index], true) {
+                            return Err(Error::decode("invalid object permutation"));
+                        }
+                    }
+                }
+            }
+            _ => unreachable!("entry tag has 3 bits"),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the range of the data that the entry points to, without checking its content.
+fn extent(data: &[u8], entry: Entry) -> Result<Range<usize>, Error> {
+    let offset = entry.offset();
+    let range = match entry.tag() {
+        Entry::NULL_TAG | Entry::FALSE_TAG | Entry::TRUE_TAG => offset..offset,
+        Entry::NUMBER_TAG => {
+            let Some(&tag) = data.get(offset) else {
+                return Err(Error::decode("number out of bounds"));
+            };
+            offset..offset + 1 + number_size(tag)
+        }
+        Entry::STRING_TAG => {
+            let Some(len) = data.get(offset..offset + 4) else {
+                return Err(Error::decode("string out of bounds"));
+            };
+            offset..offset + 4 + get_u32(len, 0) as usize
+        }
+        Entry::RAW_JSON_TAG => {
+            let Some(len) = data.get(offset..offset + 4) else {
+                return Err(Error::decode("string out of bounds"));
+            };
+            let value_start = offset + 4 + get_u32(len, 0) as usize;
+            let Some(len) = data.get(value_start..value_start + 4) else {
+                return Err(Error::decode("raw JSON value out of bounds"));
+            };
+            offset..value_start + 4 + get_u32(len, 0) as usize
+        }
+        Entry::ARRAY_TAG | Entry::OBJECT_TAG => {
+            let container = container(data, offset)?;
+            offset - container.len()..offset
+        }
+        _ => unreachable!("entry tag has 3 bits"),
+    };
+    if range.end > data.len() {
+        return Err(Error::decode("value out of bounds"));
+    }
+    Ok(range)
+}
+
+/// Checks that the ranges of the children of a container do not overlap.
+fn check_disjoint(mut ranges: Vec<Range<usize>>) -> Result<(), Error> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_unstable_by_key(|range| range.start);
+    if ranges.windows(2).any(|w| w[0].end > w[1].start) {
+        return Err(Error::decode("overlapping values in container"));
+    }
+    Ok(())
+}
+
+/// Checks the number at the offset.
+fn validate_number(data: &[u8], offset: usize) -> Result<(), Error> {
+    let Some(&tag) = data.get(offset) else {
+        return Err(Error::decode("number out of bounds"));
+    };
+    if !matches!(
+        tag,
+        NUMBER_ZERO | NUMBER_I8 | NUMBER_I16 | NUMBER_I32 | NUMBER_I64 | NUMBER_U64 | NUMBER_F64
+    ) {
+        return Err(Error::decode("invalid number tag"));
+    }
+    let Some(mut number) = data.get(offset + 1..offset + 1 + number_size(tag)) else {
+        return Err(Error::decode("number out of bounds"));
+    };
+    if tag == NUMBER_F64 && !number.get_f64_ne().is_finite() {
+        return Err(Error::decode("non-finite number"));
+    }
+    Ok(())
+}
+
+/// Checks the string at the offset and returns it.
+fn validate_string(data: &[u8], offset: usize) -> Result<&str, Error> {
+    let Some(len) = data.get(offset..offset + 4) else {
+        return Err(Error::decode("string out of bounds"));
+    };
+    let len = get_u32(len, 0) as usize;
+    let Some(bytes) = data.get(offset + 4..offset + 4 + len) else {
+        return Err(Error::decode("string out of bounds"));
+    };
+    std::str::from_utf8(bytes).map_err(|_| Error::decode("invalid UTF-8 in string"))
+}
+
+/// Returns the array or object that ends at the offset.
+///
+/// The returned slice has at least 8 bytes for the length and size.
+fn container(data: &[u8], end: usize) -> Result<&[u8], Error> {
+    if end < 8 || end > data.len() {
+        return Err(Error::decode("container out of bounds"));
+    }
+    let size = get_u32(data, end - 4) as usize;
+    if size < 8 || size > end {
+        return Err(Error::decode("container size out of bounds"));
+    }
+    Ok(&data[end - size..end])
+}
+
+fn get_u32(data: &[u8], offset: usize) -> u32 {
+    (&data[offset..offset + 4]).get_u32_ne()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Builder, KeyOrder, Value, ValueRef};
+
+    fn values() -> Vec<Value> {
+        let json = r#"{"b": [1, -2, 300, 70000, 5000000000, 18446744073709551615, 1.5, null],
+            "a": {"x": "中文", "yy": true, "": false}, "c": [[], {}, ""]}"#;
+        let mut values = vec![];
+        for (key_order, preserve_order) in [
+            (KeyOrder::Lexicographic, false),
+            (KeyOrder::LengthFirst, false),
+            (KeyOrder::Lexicographic, true),
+        ] {
+            let mut builder = Builder::<Vec<u8>>::new()
+                .with_key_order(key_order)
+                .with_preserve_order(preserve_order);
+            let mut deserializer = serde_json::Deserializer::from_str(json);
+            serde::de::DeserializeSeed::deserialize(&mut builder, &mut deserializer).unwrap();
+            values.push(builder.finish());
+        }
+        values.push(Value::from(()));
+        values.push(Value::from("x"));
+        values
+    }
+
+    #[test]
+    fn valid() {
+        for value in values() {
+            assert_eq!(Value::try_from_bytes(value.as_bytes()).unwrap(), value);
+        }
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/data");
+        for path in std::fs::read_dir(dir).unwrap() {
+            let json = std::fs::read(path.unwrap().path()).unwrap();
+            let value = Value::from_text(&json).unwrap();
+            ValueRef::try_from_bytes(value.as_bytes()).unwrap();
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        for bytes in [&[][..], &[0, 0, 0], &[0xff; 4], &[1, 0, 0, 0x20]] {
+            let err = ValueRef::try_from_bytes(bytes).unwrap_err();
+            assert!(matches!(err, crate::Error::Decode { .. }), "{err}");
+        }
+        // unsorted keys
+        let value: Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+        let mut bytes = value.as_bytes().to_vec();
+        let pos = bytes.iter().position(|&b| b == b'a').unwrap();
+        bytes[pos] = b'c';
+        let err = ValueRef::try_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid jsonbb encoding: object keys are not sorted or unique"
+        );
+    }
+
+    #[test]
+    fn shared_children() {
+        // nested arrays whose two entries point to the same child, which would be visited an
+        // exponential number of times if sharing were allowed
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&0u32.to_ne_bytes());
+        bytes.extend_from_slice(&8u32.to_ne_bytes());
+        for _ in 0..26 {
+            let child = crate::Entry::array(bytes.len());
+            bytes.extend_from_slice(child.as_bytes());
+            bytes.extend_from_slice(child.as_bytes());
+            bytes.extend_from_slice(&2u32.to_ne_bytes());
+            bytes.extend_from_slice(&(bytes.len() as u32 + 4).to_ne_bytes());
+        }
+        bytes.extend_from_slice(crate::Entry::array(bytes.len()).as_bytes());
+        assert_eq!(bytes.len(), 428);
+        let err = ValueRef::try_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid jsonbb encoding: overlapping values in container"
+        );
+
+        // an object whose key and value are the same string
+        let value: Value = r#"{"a": "a"}"#.parse().unwrap();
+        let mut bytes = value.as_bytes().to_vec();
+        let entries = bytes.len() - 4 - 8 - 8;
+        let key = bytes[entries..entries + 4].to_vec();
+        bytes[entries + 4..entries + 8].copy_from_slice(&key);
+        let err = ValueRef::try_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid jsonbb encoding: overlapping values in container"
+        );
+    }
+
+    #[test]
+    fn corrupted() {
+        // any corruption is either rejected or still readable without panic
+        for value in values() {
+            for i in 0..value.as_bytes().len() {
+                for bit in 0..8 {
+                    let mut bytes = value.as_bytes().to_vec();
+                    bytes[i] ^= 1 << bit;
+                    if let Ok(value) = ValueRef::try_from_bytes(&bytes) {
+                        _ = value.to_string();
+                        _ = value.to_owned();
+                    }
+                }
+                _ = ValueRef::try_from_bytes(&value.as_bytes()[i..]).map(|v| v.to_string());
+                _ = ValueRef::try_from_bytes(&value.as_bytes()[..i]).map(|v| v.to_string());
+            }
+        }
+    }
+}
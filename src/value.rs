@@ -55,35 +55,184 @@ impl Value {
         })
     }
 
+    /// Creates a JSON number from an f64 value, returning an error if the value is infinite or
+    /// NaN.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// assert_eq!(Value::try_from_f64(1.5).unwrap().as_f64(), Some(1.5));
+    /// assert!(Value::try_from_f64(f64::NAN).is_err());
+    /// ```
+    pub fn try_from_f64(v: f64) -> Result<Self, Error> {
+        let mut builder = Builder::with_capacity(1 + 8 + 4);
+        builder.try_add_f64(v)?;
+        Ok(builder.finish())
+    }
+
     /// Deserialize an instance of `Value` from bytes of JSON text.
-    pub fn from_text(json: &[u8]) -> serde_json::Result<Self> {
-        use ::serde::de::DeserializeSeed;
+    pub fn from_text(json: &[u8]) -> Result<Self, Error> {
+        Self::from_text_with_policy(json, DuplicateKeyPolicy::default())
+    }
 
-        let mut builder = Builder::with_capacity(json.len());
-        let mut deserializer = serde_json::Deserializer::from_slice(json);
-        builder.deserialize(&mut deserializer)?;
-        deserializer.end()?;
+    /// Deserialize an instance of `Value` from bytes of JSON text, handling duplicate keys
+    /// with the given policy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{DuplicateKeyPolicy, Value};
+    ///
+    /// let json = br#"{"a": 1, "a": 2}"#;
+    /// let value = Value::from_text_with_policy(json, DuplicateKeyPolicy::FirstWins).unwrap();
+    /// assert_eq!(value.to_string(), r#"{"a":1}"#);
+    /// assert!(Value::from_text_with_policy(json, DuplicateKeyPolicy::Error).is_err());
+    /// ```
+    pub fn from_text_with_policy(json: &[u8], policy: DuplicateKeyPolicy) -> Result<Self, Error> {
+        Self::from_text_with(json, &ParseOptions::new().duplicate_key_policy(policy))
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text with the given options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::{ParseOptions, Value};
+    ///
+    /// let options = ParseOptions::new().json5(true);
+    /// let value = Value::from_text_with(b"{'a': 1, // comment\n b: [2,]}", &options).unwrap();
+    /// assert_eq!(value.to_string(), r#"{"a":1,"b":[2]}"#);
+    /// ```
+    pub fn from_text_with(json: &[u8], options: &ParseOptions) -> Result<Self, Error> {
+        let mut builder = options.builder(json.len());
+        parser::parse(json, &mut builder, options)?;
         Ok(builder.finish())
     }
 
+    /// Deserialize an instance of `Value` from bytes of JSON text, building only the values at
+    /// the given JSON pointers.
+    ///
+    /// The rest of the text is checked for syntax but not stored. Objects and arrays on the way to
+    /// a selected value keep only the members and elements on the way, so look up values with
+    /// [`ProjectedValue::pointer`], which maps array indices in the text to those in the value.
+    /// Pointers that do not match any value are ignored.
+    ///
+    /// Returns an error if the root value is not selected and is not an object or array.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let json = br#"{"id": 1, "user": {"name": "a", "bio": "..."}, "tags": ["x", "y", "z"]}"#;
+    /// let value = Value::from_text_projected(json, &["/id", "/user/name", "/tags/1"]).unwrap();
+    /// assert_eq!(value.value().to_string(), r#"{"id":1,"tags":["y"],"user":{"name":"a"}}"#);
+    /// assert_eq!(value.pointer("/tags/1").unwrap().as_str(), Some("y"));
+    /// assert_eq!(value.pointer("/tags/0"), None);
+    /// assert_eq!(value.pointer("/user/bio"), None);
+    /// ```
+    pub fn from_text_projected(json: &[u8], pointers: &[&str]) -> Result<ProjectedValue, Error> {
+        let mut builder = Builder::with_capacity(0);
+        let indices = parser::parse_projected(json, &mut builder, pointers)?;
+        Ok(ProjectedValue::new(builder.finish(), indices))
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text, along with the location of
+    /// every value in the text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let json = b"{\n  \"port\": \"80\"\n}";
+    /// let (value, spans) = Value::from_text_with_spans(json).unwrap();
+    /// if !value.pointer("/port").unwrap().is_number() {
+    ///     let span = spans.get("/port").unwrap();
+    ///     let message = format!("line {}, column {}: expected a number", span.line, span.column);
+    ///     assert_eq!(message, "line 2, column 11: expected a number");
+    /// }
+    /// ```
+    pub fn from_text_with_spans(json: &[u8]) -> Result<(Self, Spans), Error> {
+        let mut builder = Builder::with_capacity(json.len());
+        let spans = parser::parse_with_spans(json, &mut builder)?;
+        Ok((builder.finish(), spans))
+    }
+
     /// Deserialize an instance of `Value` from bytes of JSON text.
     #[cfg(feature = "simd-json")]
-    pub fn from_text_mut(json: &mut [u8]) -> simd_json::Result<Self> {
+    pub fn from_text_mut(json: &mut [u8]) -> Result<Self, Error> {
+        Self::from_text_mut_with_policy(json, DuplicateKeyPolicy::default())
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text, handling duplicate keys
+    /// with the given policy.
+    #[cfg(feature = "simd-json")]
+    pub fn from_text_mut_with_policy(
+        json: &mut [u8],
+        policy: DuplicateKeyPolicy,
+    ) -> Result<Self, Error> {
+        use ::serde::de::DeserializeSeed;
+
+        let mut builder = Builder::with_capacity(json.len()).with_duplicate_key_policy(policy);
+        simd_json::Deserializer::from_slice(json)
+            .and_then(|mut deserializer| builder.deserialize(&mut deserializer))
+            .map_err(|e| Error::from_simd_json(e, json))?;
+        Ok(builder.finish())
+    }
+
+    /// Deserialize an instance of `Value` from bytes of JSON text using `sonic-rs`.
+    ///
+    /// Values are fed into the builder through `sonic-rs`'s serde interface, without building
+    /// an intermediate DOM.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value = jsonbb::Value::from_text_sonic(br#"{"a": [1, 2.5, "x"]}"#).unwrap();
+    /// assert_eq!(value.to_string(), r#"{"a":[1,2.5,"x"]}"#);
+    /// ```
+    #[cfg(feature = "sonic-rs")]
+    pub fn from_text_sonic(json: &[u8]) -> Result<Self, Error> {
         use ::serde::de::DeserializeSeed;
 
         let mut builder = Builder::with_capacity(json.len());
-        let mut deserializer = simd_json::Deserializer::from_slice(json)?;
+        let mut deserializer = sonic_rs::Deserializer::from_slice(json);
         builder.deserialize(&mut deserializer)?;
+        deserializer.end()?;
         Ok(builder.finish())
     }
 
     /// Creates a JSON `Value` from bytes of jsonbb encoding.
+    ///
+    /// The bytes are not validated. Use [`try_from_bytes`] for untrusted input.
+    ///
+    /// [`try_from_bytes`]: #method.try_from_bytes
     pub fn from_bytes(bytes: &[u8]) -> Self {
         Self {
             buffer: bytes.into(),
         }
     }
 
+    /// Creates a JSON `Value` from bytes of jsonbb encoding, returning an error if the bytes
+    /// are not a valid encoding.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::Value;
+    ///
+    /// let value: Value = r#"{"a": [1, "b"]}"#.parse().unwrap();
+    /// assert_eq!(Value::try_from_bytes(value.as_bytes()).unwrap(), value);
+    /// assert!(Value::try_from_bytes(&value.as_bytes()[1..]).is_err());
+    /// ```
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        ValueRef::try_from_bytes(bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
     /// Returns a reference to the value.
     pub fn as_ref(&self) -> ValueRef<'_> {
         ValueRef::from_bytes(&self.buffer)
@@ -565,10 +714,12 @@ impl<W: AsMut<Vec<u8>>> Builder<W> {
 }
 
 impl FromStr for Value {
-    type Err = serde_json::Error;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::from_text(s.as_bytes())
+        let mut builder = Builder::with_capacity(s.len());
+        parser::parse_str(s, &mut builder)?;
+        Ok(builder.finish())
     }
 }
 
@@ -645,14 +796,24 @@ impl From<isize> for Value {
 }
 
 impl From<f32> for Value {
+    /// Creates a JSON number from an f32 value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is infinite or NaN. Use [`Value::try_from_f64`] to handle them.
     fn from(v: f32) -> Self {
         Self::from(v as f64)
     }
 }
 
 impl From<f64> for Value {
+    /// Creates a JSON number from an f64 value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is infinite or NaN. Use [`Value::try_from_f64`] to handle them.
     fn from(v: f64) -> Self {
-        Self::from_builder(1 + 8 + 4, |b| b.add_f64(v))
+        Self::try_from_f64(v).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
@@ -701,11 +862,57 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[cfg(feature = "simd-json")]
+    fn from_text_mut() {
+        let mut json = br#"{"a": [1, 2.5, "x"]}"#.to_vec();
+        let value = Value::from_text_mut(&mut json).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[1,2.5,"x"]}"#);
+        let mut json = b"[1,\n 2,]".to_vec();
+        let err = Value::from_text_mut(&mut json).unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 2, .. }), "{err}");
+    }
+
+    #[test]
+    #[cfg(feature = "sonic-rs")]
+    fn from_text_sonic() {
+        let mut texts = vec![
+            br#"{"a":1,"b":[-1,2.5,1e300,18446744073709551615],"a":"\u00e9\n"}"#.to_vec(),
+            b"[[], {}, null, true, false]".to_vec(),
+        ];
+        // sonic-rs always rounds floats correctly
+        if cfg!(feature = "float_roundtrip") {
+            let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/data");
+            for path in std::fs::read_dir(dir).unwrap() {
+                texts.push(std::fs::read(path.unwrap().path()).unwrap());
+            }
+        }
+        for json in texts {
+            let expected = Value::from_text(&json).unwrap();
+            let value = Value::from_text_sonic(&json).unwrap();
+            assert!(value == expected);
+        }
+        Value::from_text_sonic(b"[1,]").unwrap_err();
+        let err = Value::from_text_sonic(b"[1,\n 2] 3").unwrap_err();
+        assert!(matches!(err, Error::Parse { line: 2, .. }), "{err}");
+    }
+
+    #[test]
+    #[should_panic = "Infinite or NaN"]
     fn from_nan() {
         _ = Value::from(f64::NAN);
     }
 
+    #[test]
+    fn try_from_f64() {
+        for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                Value::try_from_f64(v),
+                Err(Error::NonFinite { .. })
+            ));
+        }
+        assert_eq!(Value::try_from_f64(-0.5).unwrap(), Value::from(-0.5));
+    }
+
     #[test]
     #[should_panic]
     fn from_inf() {
@@ -20,7 +20,7 @@ use bytes::Buf;
 use serde_json::Number;
 
 /// A reference to a JSON value.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy)]
 pub enum ValueRef<'a> {
     // NOTE: Order matters!
     // we follow postgresql's order:
@@ -37,63 +37,84 @@ pub enum ValueRef<'a> {
     Array(ArrayRef<'a>),
     /// Represents a JSON object.
     Object(ObjectRef<'a>),
+    /// Represents JSON text added by [`Builder::add_raw_json`], which is printed verbatim.
+    ///
+    /// All other methods, such as the `is_*` and `as_*` accessors, [`get`](Self::get) and the
+    /// comparisons, see the value of the text as returned by [`RawJsonRef::value`].
+    RawJson(RawJsonRef<'a>),
 }
 
 impl<'a> ValueRef<'a> {
     /// Creates a `ValueRef` from a byte slice.
+    ///
+    /// The bytes are not validated. Use [`try_from_bytes`] for untrusted input.
+    ///
+    /// [`try_from_bytes`]: #method.try_from_bytes
     pub fn from_bytes(bytes: &[u8]) -> ValueRef<'_> {
         let entry = Entry::from(&bytes[bytes.len() - 4..]);
         ValueRef::from_slice(bytes, entry)
     }
 
+    /// Creates a `ValueRef` from a byte slice, returning an error if the bytes are not a valid
+    /// jsonbb encoding.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<ValueRef<'_>, Error> {
+        validate::validate(bytes)?;
+        Ok(Self::from_bytes(bytes))
+    }
+
     /// Returns true if the value is a null. Returns false otherwise.
     pub fn is_null(self) -> bool {
-        matches!(self, Self::Null)
+        matches!(self.resolve(), Self::Null)
     }
 
     /// Returns true if the value is a boolean. Returns false otherwise.
     pub fn is_boolean(self) -> bool {
-        matches!(self, Self::Bool(_))
+        matches!(self.resolve(), Self::Bool(_))
     }
 
     /// Returns true if the value is a number. Returns false otherwise.
     pub fn is_number(self) -> bool {
-        matches!(self, Self::Number(_))
+        matches!(self.resolve(), Self::Number(_))
     }
 
     /// Returns true if the value is an integer between zero and `u64::MAX`.
     pub fn is_u64(self) -> bool {
-        matches!(self, Self::Number(n) if n.is_u64())
+        matches!(self.resolve(), Self::Number(n) if n.is_u64())
     }
 
     /// Returns true if the value is an integer between `i64::MIN` and `i64::MAX`.
     pub fn is_i64(self) -> bool {
-        matches!(self, Self::Number(n) if n.is_i64())
+        matches!(self.resolve(), Self::Number(n) if n.is_i64())
     }
 
     /// Returns true if the value is a number that can be represented by f64.
     pub fn is_f64(self) -> bool {
-        matches!(self, Self::Number(n) if n.is_f64())
+        matches!(self.resolve(), Self::Number(n) if n.is_f64())
     }
 
     /// Returns true if the value is a string. Returns false otherwise.
     pub fn is_string(self) -> bool {
-        matches!(self, Self::String(_))
+        matches!(self.resolve(), Self::String(_))
     }
 
     /// Returns true if the value is an array. Returns false otherwise.
     pub fn is_array(self) -> bool {
-        matches!(self, Self::Array(_))
+        matches!(self.resolve(), Self::Array(_))
     }
 
     /// Returns true if the value is an object. Returns false otherwise.
     pub fn is_object(self) -> bool {
-        matches!(self, Self::Object(_))
+        matches!(self.resolve(), Self::Object(_))
+    }
+
+    /// Returns true if the value is raw JSON text. Returns false otherwise.
+    pub fn is_raw_json(self) -> bool {
+        matches!(self, Self::RawJson(_))
     }
 
     /// If the value is `null`, returns `()`. Returns `None` otherwise.
     pub fn as_null(self) -> Option<()> {
-        match self {
+        match self.resolve() {
             Self::Null => Some(()),
             _ => None,
         }
@@ -101,7 +122,7 @@ impl<'a> ValueRef<'a> {
 
     /// If the value is a boolean, returns the associated bool. Returns `None` otherwise.
     pub fn as_bool(self) -> Option<bool> {
-        match self {
+        match self.resolve() {
             Self::Bool(b) => Some(b),
             _ => None,
         }
@@ -109,7 +130,7 @@ impl<'a> ValueRef<'a> {
 
     /// If the value is a number, returns the associated number. Returns `None` otherwise.
     pub fn as_number(self) -> Option<NumberRef<'a>> {
-        match self {
+        match self.resolve() {
             Self::Number(n) => Some(n),
             _ => None,
         }
@@ -117,31 +138,22 @@ impl<'a> ValueRef<'a> {
 
     /// If the value is an integer, returns the associated u64. Returns `None` otherwise.
     pub fn as_u64(self) -> Option<u64> {
-        match self {
-            Self::Number(n) => n.as_u64(),
-            _ => None,
-        }
+        self.as_number()?.as_u64()
     }
 
     /// If the value is an integer, returns the associated i64. Returns `None` otherwise.
     pub fn as_i64(self) -> Option<i64> {
-        match self {
-            Self::Number(n) => n.as_i64(),
-            _ => None,
-        }
+        self.as_number()?.as_i64()
     }
 
     /// If the value is a float, returns the associated f64. Returns `None` otherwise.
     pub fn as_f64(self) -> Option<f64> {
-        match self {
-            Self::Number(n) => n.as_f64(),
-            _ => None,
-        }
+        self.as_number()?.as_f64()
     }
 
     /// If the value is a string, returns the associated str. Returns `None` otherwise.
     pub fn as_str(self) -> Option<&'a str> {
-        match self {
+        match self.resolve() {
             Self::String(s) => Some(s.as_str()),
             _ => None,
         }
@@ -149,7 +161,7 @@ impl<'a> ValueRef<'a> {
 
     /// If the value is an array, returns the associated array. Returns `None` otherwise.
     pub fn as_array(self) -> Option<ArrayRef<'a>> {
-        match self {
+        match self.resolve() {
             Self::Array(a) => Some(a),
             _ => None,
         }
@@ -157,12 +169,28 @@ impl<'a> ValueRef<'a> {
 
     /// If the value is an object, returns the associated map. Returns `None` otherwise.
     pub fn as_object(self) -> Option<ObjectRef<'a>> {
-        match self {
+        match self.resolve() {
             Self::Object(o) => Some(o),
             _ => None,
         }
     }
 
+    /// If the value is raw JSON text, returns the associated text. Returns `None` otherwise.
+    pub fn as_raw_json(self) -> Option<RawJsonRef<'a>> {
+        match self {
+            Self::RawJson(r) => Some(r),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of raw JSON text, or the value itself otherwise.
+    pub(crate) fn resolve(self) -> Self {
+        match self {
+            Self::RawJson(r) => r.value(),
+            _ => self,
+        }
+    }
+
     /// Creates owned `Value` from `ValueRef`.
     pub fn to_owned(self) -> Value {
         self.into()
@@ -191,6 +219,12 @@ impl<'a> ValueRef<'a> {
                 let ptr = entry.offset();
                 Self::Object(ObjectRef::from_slice(data, ptr))
             }
+            Entry::RAW_JSON_TAG => {
+                let ptr = entry.offset();
+                Self::RawJson(RawJsonRef::from_bytes(
+                    &data[ptr..ptr + raw_json_size(&data[ptr..])],
+                ))
+            }
             _ => panic!("invalid entry"),
         }
     }
@@ -204,6 +238,7 @@ impl<'a> ValueRef<'a> {
             Self::String(s) => s.as_slice(),
             Self::Array(a) => a.as_slice(),
             Self::Object(o) => o.as_slice(),
+            Self::RawJson(r) => r.as_slice(),
         }
     }
 
@@ -216,6 +251,7 @@ impl<'a> ValueRef<'a> {
             Self::String(_) => Entry::string(offset),
             Self::Array(a) => Entry::array(offset + a.as_slice().len()),
             Self::Object(o) => Entry::object(offset + o.as_slice().len()),
+            Self::RawJson(_) => Entry::raw_json(offset),
         }
     }
 
@@ -238,7 +274,7 @@ impl<'a> ValueRef<'a> {
     /// A string index can be used to access a value in an object,
     /// and a usize index can be used to access an element of an array.
     pub fn get(self, index: impl Index) -> Option<ValueRef<'a>> {
-        index.index_into(self)
+        index.index_into(self.resolve())
     }
 
     /// Looks up a value by a JSON Pointer.
@@ -261,7 +297,7 @@ impl<'a> ValueRef<'a> {
             .split('/')
             .skip(1)
             .map(|x| x.replace("~1", "/").replace("~0", "~"))
-            .try_fold(self, |target, token| match target {
+            .try_fold(self, |target, token| match target.resolve() {
                 Self::Object(map) => map.get(&token),
                 Self::Array(list) => parse_index(&token).and_then(|x| list.get(x)),
                 _ => None,
@@ -269,6 +305,72 @@ impl<'a> ValueRef<'a> {
     }
 }
 
+impl ValueRef<'_> {
+    /// Returns the rank of the type in PostgreSQL's order:
+    /// `Object > Array > Boolean > Number > String > Null`.
+    fn type_rank(self) -> u8 {
+        match self.resolve() {
+            Self::Null => 0,
+            Self::String(_) => 1,
+            Self::Number(_) => 2,
+            Self::Bool(_) => 3,
+            Self::Array(_) => 4,
+            Self::Object(_) => 5,
+            Self::RawJson(_) => unreachable!("raw JSON is resolved"),
+        }
+    }
+}
+
+impl PartialEq for ValueRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.resolve(), other.resolve()) {
+            (Self::Null, Self::Null) => true,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Object(a), Self::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ValueRef<'_> {}
+
+impl PartialOrd for ValueRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ValueRef<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.resolve(), other.resolve()) {
+            (Self::String(a), Self::String(b)) => a.cmp(&b),
+            (Self::Number(a), Self::Number(b)) => a.cmp(&b),
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(&b),
+            (Self::Array(a), Self::Array(b)) => a.cmp(&b),
+            (Self::Object(a), Self::Object(b)) => a.cmp(&b),
+            (a, b) => a.type_rank().cmp(&b.type_rank()),
+        }
+    }
+}
+
+impl Hash for ValueRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let value = self.resolve();
+        value.type_rank().hash(state);
+        match value {
+            Self::String(s) => s.hash(state),
+            Self::Number(n) => n.hash(state),
+            Self::Bool(b) => b.hash(state),
+            Self::Array(a) => a.hash(state),
+            Self::Object(o) => o.hash(state),
+            Self::Null | Self::RawJson(_) => {}
+        }
+    }
+}
+
 impl fmt::Debug for ValueRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -278,6 +380,7 @@ impl fmt::Debug for ValueRef<'_> {
             Self::String(s) => s.as_str().fmt(f),
             Self::Array(a) => a.fmt(f),
             Self::Object(o) => o.fmt(f),
+            Self::RawJson(r) => f.write_str(r.as_str()),
         }
     }
 }
@@ -285,7 +388,7 @@ impl fmt::Debug for ValueRef<'_> {
 /// Display a JSON value as a string.
 impl fmt::Display for ValueRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        serialize_in_json(self, f)
+        writer::display(*self, f)
     }
 }
 
@@ -303,6 +406,7 @@ impl From<ValueRef<'_>> for serde_json::Value {
                     .map(|(k, v)| (k.to_owned(), Self::from(v)))
                     .collect(),
             ),
+            ValueRef::RawJson(r) => Self::from(r.value()),
         }
     }
 }
@@ -335,6 +439,91 @@ impl<'a> StringRef<'a> {
     }
 }
 
+/// A reference to JSON text added by [`Builder::add_raw_json`].
+///
+/// The text is stored along with its encoded value, so it can be printed verbatim and read
+/// without parsing. Raw JSON is compared and hashed by its value.
+#[derive(Clone, Copy)]
+pub struct RawJsonRef<'a> {
+    // # layout
+    // | len (u32) | text    | value len (u32) | value     |
+    // |    4      | 0..len  |       4         | 0..len    |
+    data: &'a [u8],
+}
+
+/// Returns the size of the raw JSON data at the start of the slice.
+pub(crate) fn raw_json_size(data: &[u8]) -> usize {
+    let text_end = 4 + (&data[..4]).get_u32_ne() as usize;
+    text_end + 4 + (&data[text_end..]).get_u32_ne() as usize
+}
+
+impl<'a> RawJsonRef<'a> {
+    /// Creates a `RawJsonRef` from a byte slice that contains the text and value with length
+    /// prefixes.
+    pub(crate) fn from_bytes(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the JSON text.
+    pub fn as_str(&self) -> &'a str {
+        let len = (&self.data[..4]).get_u32_ne() as usize;
+        // SAFETY: the text is checked when it is added
+        unsafe { std::str::from_utf8_unchecked(&self.data[4..4 + len]) }
+    }
+
+    /// Returns the value of the JSON text.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let mut builder = jsonbb::Builder::<Vec<u8>>::new();
+    /// builder.add_raw_json(r#"{"a": [1, 2]}"#).unwrap();
+    /// let value = builder.finish();
+    /// let raw = value.as_ref().as_raw_json().unwrap();
+    /// assert_eq!(raw.value().pointer("/a/1").unwrap().as_u64(), Some(2));
+    /// ```
+    pub fn value(&self) -> ValueRef<'a> {
+        let text_end = 4 + self.as_str().len();
+        ValueRef::from_bytes(&self.data[text_end + 4..])
+    }
+
+    /// Returns the value of the JSON text as an owned `Value`.
+    pub fn parse(&self) -> Value {
+        self.value().to_owned()
+    }
+
+    /// Returns the entire data as a slice including the length prefixes.
+    pub(crate) fn as_slice(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl PartialEq for RawJsonRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl Eq for RawJsonRef<'_> {}
+
+impl PartialOrd for RawJsonRef<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RawJsonRef<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.value().cmp(&other.value())
+    }
+}
+
+impl Hash for RawJsonRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value().hash(state);
+    }
+}
+
 /// A reference to a JSON number.
 #[derive(Clone, Copy)]
 pub struct NumberRef<'a> {
@@ -360,6 +549,21 @@ impl NumberRef<'_> {
         }
     }
 
+    /// Returns the number in its stored representation.
+    pub(crate) fn to_primitive(self) -> Primitive {
+        let mut data = self.data;
+        match data.get_u8() {
+            NUMBER_ZERO => Primitive::I64(0),
+            NUMBER_I8 => Primitive::I64(data.get_i8() as i64),
+            NUMBER_I16 => Primitive::I64(data.get_i16_ne() as i64),
+            NUMBER_I32 => Primitive::I64(data.get_i32_ne() as i64),
+            NUMBER_I64 => Primitive::I64(data.get_i64_ne()),
+            NUMBER_U64 => Primitive::U64(data.get_u64_ne()),
+            NUMBER_F64 => Primitive::F64(data.get_f64_ne()),
+            t => panic!("invalid number tag: {t}"),
+        }
+    }
+
     /// If the number is an integer, returns the associated u64. Returns `None` otherwise.
     pub fn as_u64(self) -> Option<u64> {
         self.to_number().as_u64()
@@ -406,6 +610,13 @@ impl NumberRef<'_> {
     }
 }
 
+/// A number in its stored representation.
+pub(crate) enum Primitive {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
 impl fmt::Debug for NumberRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.to_number().fmt(f)
@@ -541,7 +752,7 @@ impl fmt::Debug for ArrayRef<'_> {
 /// Display a JSON array as a string.
 impl fmt::Display for ArrayRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        serialize_in_json(self, f)
+        writer::display(ValueRef::Array(*self), f)
     }
 }
 
@@ -590,9 +801,20 @@ pub struct ObjectRef<'a> {
     // |<-------------- data (size) --------------->|^ptr
     //
     // entries are ordered by key and each key is unique.
+    // the highest bit of len is set if keys are ordered by length first.
+    //
+    // if the second highest bit of len is set, the insertion order is kept as a permutation
+    // of entries after them:
+    // | elements | [kptr, vptr] x len | [index] x len | len | size |
+    // |          |     4 x 2 x len    |    4 x len    |  4  |  4   |
     data: &'a [u8],
 }
 
+/// The flag in the number of entries of an object indicating [`KeyOrder::LengthFirst`].
+pub(crate) const OBJECT_LENGTH_FIRST: u32 = 1 << 31;
+/// The flag in the number of entries of an object indicating that the insertion order is kept.
+pub(crate) const OBJECT_PRESERVE_ORDER: u32 = 1 << 30;
+
 impl<'a> ObjectRef<'a> {
     /// Returns the value associated with the given key, or `None` if the key is not present.
     ///
@@ -604,16 +826,8 @@ impl<'a> ObjectRef<'a> {
     /// assert!(object.get("c").is_none());
     /// ```
     pub fn get(self, key: &str) -> Option<ValueRef<'a>> {
-        // do binary search since entries are ordered by key
-        let entries = self.entries();
-        let idx = entries
-            .binary_search_by_key(&key, |&(kentry, _)| {
-                ValueRef::from_slice(self.data, kentry)
-                    .as_str()
-                    .expect("key must be string")
-            })
-            .ok()?;
-        let (_, ventry) = entries[idx];
+        let idx = self.search(key).ok()?;
+        let (_, ventry) = self.entries()[idx];
         Some(ValueRef::from_slice(self.data, ventry))
     }
 
@@ -627,15 +841,7 @@ impl<'a> ObjectRef<'a> {
     /// assert_eq!(object.contains_key("c"), false);
     /// ```
     pub fn contains_key(self, key: &str) -> bool {
-        // do binary search since entries are ordered by key
-        let entries = self.entries();
-        entries
-            .binary_search_by_key(&key, |&(kentry, _)| {
-                ValueRef::from_slice(self.data, kentry)
-                    .as_str()
-                    .expect("key must be string")
-            })
-            .is_ok()
+        self.search(key).is_ok()
     }
 
     /// Returns the number of elements in the object.
@@ -647,7 +853,7 @@ impl<'a> ObjectRef<'a> {
     /// assert_eq!(object.len(), 2);
     /// ```
     pub fn len(self) -> usize {
-        (&self.data[self.data.len() - 8..]).get_u32_ne() as usize
+        (self.raw_len() & !(OBJECT_LENGTH_FIRST | OBJECT_PRESERVE_ORDER)) as usize
     }
 
     /// Returns `true` if the object contains no elements.
@@ -662,8 +868,33 @@ impl<'a> ObjectRef<'a> {
         self.len() == 0
     }
 
+    /// Returns the order of keys in the object.
+    ///
+    /// # Examples
+    /// ```
+    /// let json: jsonbb::Value = r#"{"a": 1, "b": 2}"#.parse().unwrap();
+    /// assert_eq!(json.as_object().unwrap().key_order(), jsonbb::KeyOrder::Lexicographic);
+    /// ```
+    pub fn key_order(self) -> KeyOrder {
+        if self.raw_len() & OBJECT_LENGTH_FIRST != 0 {
+            KeyOrder::LengthFirst
+        } else {
+            KeyOrder::Lexicographic
+        }
+    }
+
+    /// Returns `true` if the object keeps the insertion order of keys.
+    ///
+    /// See [`Builder::with_preserve_order`].
+    pub fn preserves_order(self) -> bool {
+        self.raw_len() & OBJECT_PRESERVE_ORDER != 0
+    }
+
     /// Returns an iterator over the object's key-value pairs.
     ///
+    /// Pairs are yielded in insertion order if the object [preserves order](Self::preserves_order),
+    /// otherwise in the [key order](Self::key_order) of the object.
+    ///
     /// # Examples
     /// ```
     /// let json: jsonbb::Value = r#"{"b": 2, "a": 1}"#.parse().unwrap();
@@ -671,10 +902,14 @@ impl<'a> ObjectRef<'a> {
     /// assert_eq!(kvs, [("a", 1), ("b", 2)]);
     /// ```
     pub fn iter(self) -> impl ExactSizeIterator<Item = (&'a str, ValueRef<'a>)> {
-        self.entries().iter().map(move |&(kentry, ventry)| {
-            let k = ValueRef::from_slice(self.data, kentry);
-            let v = ValueRef::from_slice(self.data, ventry);
-            (k.as_str().expect("key must be string"), v)
+        let entries = self.entries();
+        let permutation = self.permutation();
+        (0..entries.len()).map(move |i| {
+            let index = match permutation {
+                Some(p) => (&p[4 * i..]).get_u32_ne() as usize,
+                None => i,
+            };
+            self.entry_at(entries[index])
         })
     }
 
@@ -715,13 +950,95 @@ impl<'a> ObjectRef<'a> {
         }
     }
 
+    /// Returns the number of entries with flags in the high bits.
+    fn raw_len(self) -> u32 {
+        (&self.data[self.data.len() - 8..]).get_u32_ne()
+    }
+
+    /// Binary searches the key in the entries.
+    fn search(self, key: &str) -> Result<usize, usize> {
+        // do binary search since entries are ordered by key
+        let order = self.key_order();
+        self.entries().binary_search_by(|&(kentry, _)| {
+            let k = ValueRef::from_slice(self.data, kentry)
+                .as_str()
+                .expect("key must be string");
+            order.cmp(k, key)
+        })
+    }
+
+    /// Returns an iterator over the key-value pairs in the key order, ignoring insertion order.
+    pub(crate) fn stored_iter(self) -> impl ExactSizeIterator<Item = (&'a str, ValueRef<'a>)> {
+        self.entries()
+            .iter()
+            .map(move |&entry| self.entry_at(entry))
+    }
+
+    /// Returns the key-value pair of an entry.
+    fn entry_at(self, (kentry, ventry): (Entry, Entry)) -> (&'a str, ValueRef<'a>) {
+        let k = ValueRef::from_slice(self.data, kentry);
+        let v = ValueRef::from_slice(self.data, ventry);
+        (k.as_str().expect("key must be string"), v)
+    }
+
+    /// Returns an iterator over the key-value pairs in lexicographic order of keys, regardless of
+    /// the key order.
+    fn lexicographic_iter(self) -> impl Iterator<Item = (&'a str, ValueRef<'a>)> {
+        let mut prev: Option<usize> = None;
+        std::iter::from_fn(move || {
+            let i = match self.key_order() {
+                KeyOrder::Lexicographic => prev.map_or(0, |i| i + 1),
+                KeyOrder::LengthFirst => self.next_lexicographic(prev.map(|i| self.key_at(i)))?,
+            };
+            let entry = *self.entries().get(i)?;
+            prev = Some(i);
+            Some(self.entry_at(entry))
+        })
+    }
+
+    /// Returns the index of the smallest key greater than `prev` in a length-first object.
+    ///
+    /// Keys of the same length are sorted bytewise, so each group of them is binary searched.
+    fn next_lexicographic(self, prev: Option<&str>) -> Option<usize> {
+        let entries = self.entries();
+        let mut next: Option<usize> = None;
+        let mut start = 0;
+        while start < entries.len() {
+            let len = self.key_at(start).len();
+            let end =
+                start + entries[start..].partition_point(|&e| self.entry_at(e).0.len() == len);
+            let i = start
+                + entries[start..end]
+                    .partition_point(|&e| prev.is_some_and(|prev| self.entry_at(e).0 <= prev));
+            if i < end && next.is_none_or(|next| self.key_at(i) < self.key_at(next)) {
+                next = Some(i);
+            }
+            start = end;
+        }
+        next
+    }
+
+    /// Returns the key of the entry at the given index in the key order.
+    fn key_at(self, index: usize) -> &'a str {
+        self.entry_at(self.entries()[index]).0
+    }
+
     /// Returns the key-value entries.
     fn entries(self) -> &'a [(Entry, Entry)] {
         let len = self.len();
-        let base = self.data.len() - 8 - 8 * len;
+        let base = self.data.len() - 8 - self.permutation().map_or(0, |p| p.len()) - 8 * len;
         let slice = &self.data[base..base + 8 * len];
         unsafe { std::slice::from_raw_parts(slice.as_ptr() as _, len) }
     }
+
+    /// Returns the indices of entries in insertion order, if the object preserves order.
+    fn permutation(self) -> Option<&'a [u8]> {
+        if !self.preserves_order() {
+            return None;
+        }
+        let end = self.data.len() - 8;
+        Some(&self.data[end - 4 * self.len()..end])
+    }
 }
 
 impl fmt::Debug for ObjectRef<'_> {
@@ -733,7 +1050,7 @@ impl fmt::Debug for ObjectRef<'_> {
 /// Display a JSON object as a string.
 impl fmt::Display for ObjectRef<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        serialize_in_json(self, f)
+        writer::display(ValueRef::Object(*self), f)
     }
 }
 
@@ -742,7 +1059,11 @@ impl PartialEq for ObjectRef<'_> {
         if self.len() != other.len() {
             return false;
         }
-        self.iter().eq(other.iter())
+        if self.key_order() == other.key_order() {
+            self.stored_iter().eq(other.stored_iter())
+        } else {
+            self.iter().all(|(k, v)| other.get(k) == Some(v))
+        }
     }
 }
 
@@ -754,11 +1075,20 @@ impl PartialOrd for ObjectRef<'_> {
     }
 }
 
+/// Objects with more pairs are greater. Objects with the same key order are compared pair by pair
+/// in that order, so [`KeyOrder::LengthFirst`] objects are ordered as PostgreSQL orders `jsonb`.
+/// Objects with different key orders are compared pair by pair in lexicographic order of keys.
+///
+/// Note that the ordering is not transitive across key orders, so objects sorted together should
+/// be built with the same key order.
 impl Ord for ObjectRef<'_> {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // Object with n pairs > object with n - 1 pairs
         match self.len().cmp(&other.len()) {
-            std::cmp::Ordering::Equal => self.iter().cmp(other.iter()),
+            std::cmp::Ordering::Equal if self.key_order() == other.key_order() => {
+                self.stored_iter().cmp(other.stored_iter())
+            }
+            std::cmp::Ordering::Equal => self.lexicographic_iter().cmp(other.lexicographic_iter()),
             ord => ord,
         }
     }
@@ -766,53 +1096,14 @@ impl Ord for ObjectRef<'_> {
 
 impl Hash for ObjectRef<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        for (k, v) in self.iter() {
-            k.hash(state);
-            v.hash(state);
-        }
-    }
-}
-
-/// Serialize a value in JSON format.
-fn serialize_in_json(value: &impl ::serde::Serialize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    use std::io;
-
-    struct WriterFormatter<'a, 'b: 'a> {
-        inner: &'a mut fmt::Formatter<'b>,
-    }
-
-    impl<'a, 'b> io::Write for WriterFormatter<'a, 'b> {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            // Safety: the serializer below only emits valid utf8 when using
-            // the default formatter.
-            let s = unsafe { std::str::from_utf8_unchecked(buf) };
-            self.inner.write_str(s).map_err(io_error)?;
-            Ok(buf.len())
-        }
-
-        fn flush(&mut self) -> io::Result<()> {
-            Ok(())
-        }
-    }
-
-    fn io_error(_: fmt::Error) -> io::Error {
-        // Error value does not matter because Display impl just maps it
-        // back to fmt::Error.
-        io::Error::other("fmt error")
-    }
-
-    let alternate = f.alternate();
-    let mut wr = WriterFormatter { inner: f };
-    if alternate {
-        // {:#}
-        value
-            .serialize(&mut serde_json::Serializer::pretty(&mut wr))
-            .map_err(|_| fmt::Error)
-    } else {
-        // {}
-        value
-            .serialize(&mut serde_json::Serializer::new(&mut wr))
-            .map_err(|_| fmt::Error)
+        // sum the hashes of pairs to be independent of the key order, consistent with `PartialEq`
+        let sum = self.stored_iter().fold(0u64, |sum, pair| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            pair.hash(&mut hasher);
+            sum.wrapping_add(hasher.finish())
+        });
+        self.len().hash(state);
+        sum.hash(state);
     }
 }
 
@@ -0,0 +1,742 @@
+// Copyright 2025 RisingWave Labs
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A JSON text writer that walks the jsonbb layout directly.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::value_ref::Primitive;
+use crate::{ArrayRef, Error, KeyOrder, NumberRef, ObjectRef, Value, ValueRef};
+
+/// Options for writing a JSON value as text.
+///
+/// The default options produce compact output identical to `serde_json`.
+///
+/// # Example
+///
+/// ```
+/// use jsonbb::{FloatFormat, WriteOptions};
+///
+/// let value: jsonbb::Value = r#"{"a": [1, 2.5], "b": "</script>"}"#.parse().unwrap();
+///
+/// let mut buf = vec![];
+/// value.to_writer(&mut buf, &WriteOptions::new()).unwrap();
+/// assert_eq!(buf, br#"{"a":[1,2.5],"b":"</script>"}"#);
+///
+/// let mut buf = vec![];
+/// let options = WriteOptions::new()
+///     .indent(4)
+///     .escape_script_tags(true)
+///     .float_format(FloatFormat::Fixed(2))
+///     .trailing_newline(true);
+/// value.to_writer(&mut buf, &options).unwrap();
+/// assert_eq!(
+///     String::from_utf8(buf).unwrap(),
+///     "{\n    \"a\": [\n        1,\n        2.50\n    ],\n    \"b\": \"<\\/script>\"\n}\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    indent: Option<usize>,
+    ascii_only: bool,
+    escape_slash: bool,
+    escape_script_tags: bool,
+    float_format: FloatFormat,
+    trailing_newline: bool,
+}
+
+/// How floating point numbers are formatted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest representation that roundtrips, the same as `serde_json`.
+    ///
+    /// e.g. `1.0`, `0.1`, `1e+300`.
+    #[default]
+    Shortest,
+    /// The shortest representation that roundtrips, but never in exponent notation.
+    ///
+    /// e.g. `1`, `0.1`, `1000000000000000000000`.
+    Plain,
+    /// A fixed number of digits after the decimal point.
+    ///
+    /// e.g. `1.00`, `0.10` for `Fixed(2)`.
+    Fixed(usize),
+    /// The shortest representation that roundtrips, laid out as jq 1.6 prints numbers.
+    ///
+    /// Exponent notation is used only for very small or large numbers, and integral values have
+    /// no fractional part. e.g. `1`, `0.0001`, `1e-05`, `9223372036854776000`, `1e+17`.
+    Jq,
+}
+
+impl WriteOptions {
+    /// Creates options for compact output.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates options for pretty output indented with 2 spaces, the same as `{:#}`.
+    pub fn pretty() -> Self {
+        Self::new().indent(2)
+    }
+
+    /// Puts each array element and object entry on its own line, indented by `width` spaces
+    /// per nesting level.
+    pub fn indent(mut self, width: usize) -> Self {
+        self.indent = Some(width);
+        self
+    }
+
+    /// Escapes all non-ASCII characters as `\uXXXX`.
+    pub fn ascii_only(mut self, enable: bool) -> Self {
+        self.ascii_only = enable;
+        self
+    }
+
+    /// Escapes every `/` as `\/`.
+    pub fn escape_slash(mut self, enable: bool) -> Self {
+        self.escape_slash = enable;
+        self
+    }
+
+    /// Escapes `</` as `<\/`, so that the output can be embedded in an HTML `<script>` element.
+    pub fn escape_script_tags(mut self, enable: bool) -> Self {
+        self.escape_script_tags = enable;
+        self
+    }
+
+    /// Sets how floating point numbers are formatted.
+    pub fn float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Appends a newline after the value.
+    pub fn trailing_newline(mut self, enable: bool) -> Self {
+        self.trailing_newline = enable;
+        self
+    }
+}
+
+impl<'a> ValueRef<'a> {
+    /// Writes the value as JSON text into the writer.
+    ///
+    /// Output is buffered internally, so there is no need to wrap the writer in a
+    /// [`std::io::BufWriter`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use jsonbb::WriteOptions;
+    ///
+    /// let value: jsonbb::Value = r#"{"name": "jsonbb"}"#.parse().unwrap();
+    /// let mut buf = vec![];
+    /// value.as_ref().to_writer(&mut buf, &WriteOptions::new()).unwrap();
+    /// assert_eq!(buf, br#"{"name":"jsonbb"}"#);
+    /// ```
+    pub fn to_writer(
+        self,
+        mut writer: impl io::Write,
+        options: &WriteOptions,
+    ) -> Result<(), Error> {
+        let mut w = Writer::new(Vec::with_capacity(BUFFER_SIZE), options);
+        w.inner = Some(&mut writer);
+        w.write_document(self)?;
+        Ok(w.flush()?)
+    }
+
+    /// Returns the value as JSON text in the layout of PostgreSQL's `jsonb_out`.
+    ///
+    /// Object keys are printed in length-first order and numbers in plain decimal notation.
+    ///
+    /// # Compatibility
+    ///
+    /// Numbers are stored as 64-bit integers or floats rather than PostgreSQL's `numeric`, so the
+    /// output differs from `jsonb_out` for numbers that lose their scale or precision: `1.50` is
+    /// printed as `1.5`, and a number with more than 17 significant digits is printed as the
+    /// nearest float. Other values are printed as `jsonb_out` does, byte for byte.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"b": [1, 2.5e3], "a": null}"#.parse().unwrap();
+    /// assert_eq!(value.as_ref().to_pg_text(), r#"{"a": null, "b": [1, 2500]}"#);
+    /// ```
+    pub fn to_pg_text(self) -> String {
+        self.write_pg(None)
+    }
+
+    /// Returns the value as indented JSON text in the layout of PostgreSQL's `jsonb_pretty`.
+    ///
+    /// Numbers differ from PostgreSQL as described in [`to_pg_text`](Self::to_pg_text).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let value: jsonbb::Value = r#"{"a": [], "b": [1, 2]}"#.parse().unwrap();
+    /// assert_eq!(
+    ///     value.as_ref().to_pg_pretty(),
+    ///     "{\n    \"a\": [\n    ],\n    \"b\": [\n        1,\n        2\n    ]\n}"
+    /// );
+    /// ```
+    pub fn to_pg_pretty(self) -> String {
+        self.write_pg(Some(4))
+    }
+
+    fn write_pg(self, indent: Option<usize>) -> String {
+        let options = WriteOptions {
+            indent,
+            float_format: FloatFormat::Plain,
+            ..Default::default()
+        };
+        let mut w = Writer::new(Vec::with_capacity(self.capacity()), &options);
+        w.postgres = true;
+        w.write_document(self).expect("failed to write to vec");
+        // SAFETY: the writer only emits valid UTF-8.
+        unsafe { String::from_utf8_unchecked(w.buf) }
+    }
+}
+
+impl Value {
+    /// Writes the value as JSON text into the writer.
+    ///
+    /// See [`ValueRef::to_writer`] for details.
+    pub fn to_writer(&self, writer: impl io::Write, options: &WriteOptions) -> Result<(), Error> {
+        self.as_ref().to_writer(writer, options)
+    }
+
+    /// Returns the value as JSON text in the layout of PostgreSQL's `jsonb_out`.
+    ///
+    /// See [`ValueRef::to_pg_text`] for details.
+    pub fn to_pg_text(&self) -> String {
+        self.as_ref().to_pg_text()
+    }
+
+    /// Returns the value as indented JSON text in the layout of PostgreSQL's `jsonb_pretty`.
+    ///
+    /// See [`ValueRef::to_pg_pretty`] for details.
+    pub fn to_pg_pretty(&self) -> String {
+        self.as_ref().to_pg_pretty()
+    }
+}
+
+/// Display a value as JSON text, pretty printed if `{:#}` is used.
+pub(crate) fn display(value: ValueRef<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let options = if f.alternate() {
+        WriteOptions::pretty()
+    } else {
+        WriteOptions::new()
+    };
+    let mut w = Writer::new(Vec::with_capacity(value.capacity()), &options);
+    w.write_document(value).map_err(|_| fmt::Error)?;
+    // SAFETY: the writer only emits valid UTF-8.
+    f.write_str(unsafe { std::str::from_utf8_unchecked(&w.buf) })
+}
+
+const NO: u8 = 0;
+const BB: u8 = b'b'; // \x08
+const TT: u8 = b't'; // \x09
+const NN: u8 = b'n'; // \x0A
+const FF: u8 = b'f'; // \x0C
+const RR: u8 = b'r'; // \x0D
+const QU: u8 = b'"'; // \x22
+const BS: u8 = b'\\'; // \x5C
+const UU: u8 = b'u'; // \x00...\x1F except the ones above
+const SL: u8 = b'/'; // \x2F, only with `escape_slash`
+const SCRIPT: u8 = b'<'; // \x2F following a `<`, only with `escape_script_tags`
+const NON_ASCII: u8 = b'U'; // leading bytes of non-ASCII characters, only with `ascii_only`
+
+/// Lookup table of escape sequences. A value of `NO` means that the byte does not need to be
+/// escaped, otherwise the byte should be escaped as `\` followed by the value.
+// https://github.com/serde-rs/json/blob/v1.0.107/src/ser.rs#L2060
+static ESCAPE: [u8; 256] = [
+    //   1   2   3   4   5   6   7   8   9   A   B   C   D   E   F
+    UU, UU, UU, UU, UU, UU, UU, UU, BB, TT, NN, UU, FF, RR, UU, UU, // 0
+    UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, UU, // 1
+    NO, NO, QU, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 2
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 3
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 4
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, BS, NO, NO, NO, // 5
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 6
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 7
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 8
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // 9
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // A
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // B
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // C
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // D
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // E
+    NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, NO, // F
+];
+
+impl WriteOptions {
+    /// Returns the escape table for these options.
+    fn escape_table(&self) -> [u8; 256] {
+        let mut table = ESCAPE;
+        if self.escape_script_tags {
+            table[b'/' as usize] = SCRIPT;
+        }
+        if self.escape_slash {
+            table[b'/' as usize] = SL;
+        }
+        if self.ascii_only {
+            table[0xC0..].fill(NON_ASCII);
+        }
+        table
+    }
+}
+
+/// The size of the internal buffer before flushing to the inner writer.
+const BUFFER_SIZE: usize = 8 * 1024;
+
+struct Writer<'w, 'o> {
+    /// Output is first written to this buffer.
+    buf: Vec<u8>,
+    /// The buffer is flushed to this writer when it is full.
+    /// If `None`, all output is kept in the buffer.
+    inner: Option<&'w mut dyn io::Write>,
+    options: &'o WriteOptions,
+    /// The escape table built from options.
+    escape: [u8; 256],
+    /// Current nesting level.
+    level: usize,
+    /// Whether to follow the format of PostgreSQL.
+    postgres: bool,
+}
+
+impl<'w, 'o> Writer<'w, 'o> {
+    fn new(buf: Vec<u8>, options: &'o WriteOptions) -> Self {
+        Writer {
+            buf,
+            inner: None,
+            options,
+            escape: options.escape_table(),
+            level: 0,
+            postgres: false,
+        }
+    }
+
+    fn write_document(&mut self, value: ValueRef<'_>) -> io::Result<()> {
+        self.write_value(value)?;
+        if self.options.trailing_newline {
+            self.buf.push(b'\n');
+        }
+        Ok(())
+    }
+
+    /// Flushes the buffer to the inner writer.
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(inner) = &mut self.inner {
+            inner.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+        Ok(())
+    }
+
+    fn write_value(&mut self, value: ValueRef<'_>) -> io::Result<()> {
+        match value {
+            ValueRef::Null => self.buf.extend_from_slice(b"null"),
+            ValueRef::Bool(true) => self.buf.extend_from_slice(b"true"),
+            ValueRef::Bool(false) => self.buf.extend_from_slice(b"false"),
+            ValueRef::Number(n) => self.write_number(n),
+            ValueRef::String(s) => self.write_string(s.as_str()),
+            ValueRef::Array(a) => return self.write_array(a),
+            ValueRef::Object(o) => return self.write_object(o),
+            ValueRef::RawJson(r) => {
+                // options other than the trailing newline may change the text
+                let options = WriteOptions {
+                    trailing_newline: false,
+                    ..self.options.clone()
+                };
+                if self.postgres || options != WriteOptions::default() {
+                    return self.write_value(r.value());
+                }
+                self.buf.extend_from_slice(r.as_str().as_bytes())
+            }
+        }
+        if self.buf.len() >= BUFFER_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn write_number(&mut self, n: NumberRef<'_>) {
+        match n.to_primitive() {
+            Primitive::I64(v) => self
+                .buf
+                .extend_from_slice(itoa::Buffer::new().format(v).as_bytes()),
+            Primitive::U64(v) => self
+                .buf
+                .extend_from_slice(itoa::Buffer::new().format(v).as_bytes()),
+            Primitive::F64(v) => match self.options.float_format {
+                FloatFormat::Shortest => self
+                    .buf
+                    .extend_from_slice(zmij::Buffer::new().format_finite(v).as_bytes()),
+                format => self.write_float_slow(v, format),
+            },
+        }
+    }
+
+    #[cold]
+    fn write_float_slow(&mut self, v: f64, format: FloatFormat) {
+        match format {
+            FloatFormat::Shortest => unreachable!(),
+            // PostgreSQL's numeric has no negative zero.
+            FloatFormat::Plain if self.postgres && v == 0.0 => self.buf.push(b'0'),
+            FloatFormat::Plain => write!(self.buf, "{v}").unwrap(),
+            FloatFormat::Fixed(precision) => write!(self.buf, "{v:.precision$}").unwrap(),
+            FloatFormat::Jq => self.write_float_jq(v),
+        }
+    }
+
+    /// Writes a float as jq's `jvp_dtoa_fmt` does.
+    fn write_float_jq(&mut self, v: f64) {
+        // the shortest digits and the decimal exponent, e.g. `1.5e-7`
+        let exp = format!("{:e}", v.abs());
+        let (mantissa, exponent) = exp.split_once('e').unwrap();
+        let digits = mantissa.replace('.', "");
+        let (ndigits, decpt) = (digits.len() as i32, exponent.parse::<i32>().unwrap() + 1);
+        if v.is_sign_negative() {
+            self.buf.push(b'-');
+        }
+        if decpt <= -4 || decpt > ndigits + 15 {
+            let (first, rest) = digits.split_at(1);
+            let sign = if decpt > 0 { '+' } else { '-' };
+            let point = if rest.is_empty() { "" } else { "." };
+            write!(
+                self.buf,
+                "{first}{point}{rest}e{sign}{:02}",
+                (decpt - 1).abs()
+            )
+            .unwrap();
+        } else if decpt <= 0 {
+            write!(self.buf, "0.{}{digits}", "0".repeat(-decpt as usize)).unwrap();
+        } else if decpt >= ndigits {
+            write!(
+                self.buf,
+                "{digits}{}",
+                "0".repeat((decpt - ndigits) as usize)
+            )
+            .unwrap();
+        } else {
+            let (int, frac) = digits.split_at(decpt as usize);
+            write!(self.buf, "{int}.{frac}").unwrap();
+        }
+    }
+
+    fn write_array(&mut self, array: ArrayRef<'_>) -> io::Result<()> {
+        self.buf.push(b'[');
+        if array.is_empty() {
+            self.write_empty_newline();
+            self.buf.push(b']');
+            return Ok(());
+        }
+        self.level += 1;
+        for (i, v) in array.iter().enumerate() {
+            self.write_separator(i == 0);
+            self.write_value(v)?;
+        }
+        self.level -= 1;
+        self.write_newline();
+        self.buf.push(b']');
+        Ok(())
+    }
+
+    fn write_object(&mut self, object: ObjectRef<'_>) -> io::Result<()> {
+        self.buf.push(b'{');
+        if object.is_empty() {
+            self.write_empty_newline();
+            self.buf.push(b'}');
+            return Ok(());
+        }
+        self.level += 1;
+        if self.postgres
+            && (object.key_order() != KeyOrder::LengthFirst || object.preserves_order())
+        {
+            // PostgreSQL orders keys by length first, then bytewise.
+            let mut entries: Vec<_> = object.iter().collect();
+            entries.sort_by(|(k1, _), (k2, _)| KeyOrder::LengthFirst.cmp(k1, k2));
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                self.write_entry(i == 0, k, v)?;
+            }
+        } else {
+            for (i, (k, v)) in object.iter().enumerate() {
+                self.write_entry(i == 0, k, v)?;
+            }
+        }
+        self.level -= 1;
+        self.write_newline();
+        self.buf.push(b'}');
+        Ok(())
+    }
+
+    fn write_entry(&mut self, first: bool, key: &str, value: ValueRef<'_>) -> io::Result<()> {
+        self.write_separator(first);
+        self.write_string(key);
+        if self.options.indent.is_some() || self.postgres {
+            self.buf.extend_from_slice(b": ");
+        } else {
+            self.buf.push(b':');
+        }
+        self.write_value(value)
+    }
+
+    /// Writes the separator before an array element or object entry.
+    fn write_separator(&mut self, first: bool) {
+        if !first {
+            self.buf.push(b',');
+            if self.postgres && self.options.indent.is_none() {
+                self.buf.push(b' ');
+            }
+        }
+        self.write_newline();
+    }
+
+    /// Writes a newline inside an empty container, as PostgreSQL does if pretty printing.
+    fn write_empty_newline(&mut self) {
+        if self.postgres {
+            self.write_newline();
+        }
+    }
+
+    /// Writes a newline and indentation if pretty printing.
+    fn write_newline(&mut self) {
+        if let Some(width) = self.options.indent {
+            self.buf.push(b'\n');
+            self.buf.resize(self.buf.len() + width * self.level, b' ');
+        }
+    }
+
+    fn write_string(&mut self, s: &str) {
+        let bytes = s.as_bytes();
+        self.buf.push(b'"');
+        let mut start = 0;
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            let escape = self.escape[byte as usize];
+            if escape == NO {
+                i += 1;
+                continue;
+            }
+            if escape == SCRIPT && (i == 0 || bytes[i - 1] != b'<') {
+                i += 1;
+                continue;
+            }
+            self.buf.extend_from_slice(&bytes[start..i]);
+            match escape {
+                UU => self.write_unicode_escape(byte as u16),
+                NON_ASCII => {
+                    // `i` is at a char boundary since only leading bytes are marked.
+                    let c = s[i..].chars().next().unwrap();
+                    let mut units = [0; 2];
+                    for unit in c.encode_utf16(&mut units) {
+                        self.write_unicode_escape(*unit);
+                    }
+                    i += c.len_utf8() - 1;
+                }
+                SCRIPT => self.buf.extend_from_slice(b"\\/"),
+                _ => self.buf.extend_from_slice(&[b'\\', escape]),
+            }
+            i += 1;
+            start = i;
+        }
+        self.buf.extend_from_slice(&bytes[start..]);
+        self.buf.push(b'"');
+    }
+
+    fn write_unicode_escape(&mut self, unit: u16) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        self.buf.extend_from_slice(&[
+            b'\\',
+            b'u',
+            HEX[(unit >> 12) as usize & 0xF],
+            HEX[(unit >> 8) as usize & 0xF],
+            HEX[(unit >> 4) as usize & 0xF],
+            HEX[unit as usize & 0xF],
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[track_caller]
+    fn write(json: &str, options: &WriteOptions) -> String {
+        let value: Value = json.parse().unwrap();
+        let mut buf = vec![];
+        value.to_writer(&mut buf, options).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn same_as_serde_json() {
+        let json = r#"
+        {
+            "null": null,
+            "bool": [true, false],
+            "number": [0, -1, 255, 18446744073709551615, -9223372036854775808, 1.0, 0.1, 1e300, -2.5e-10],
+            "string": "hello \"world\"\n\t\u0001\u001f / </script> 中文 🦀",
+            "empty": [[], {}],
+            "nested": {"a": {"b": [1, {"c": []}]}}
+        }"#;
+        let value: Value = json.parse().unwrap();
+
+        let mut buf = vec![];
+        value.to_writer(&mut buf, &WriteOptions::new()).unwrap();
+        assert_eq!(buf, serde_json::to_vec(&value).unwrap());
+
+        let mut buf = vec![];
+        value.to_writer(&mut buf, &WriteOptions::pretty()).unwrap();
+        assert_eq!(buf, serde_json::to_vec_pretty(&value).unwrap());
+    }
+
+    #[test]
+    fn indent() {
+        let options = WriteOptions::new().indent(4);
+        assert_eq!(write("[]", &options), "[]");
+        assert_eq!(write("[1]", &options), "[\n    1\n]");
+        assert_eq!(
+            write(r#"{"a":[1,{}]}"#, &options),
+            "{\n    \"a\": [\n        1,\n        {}\n    ]\n}"
+        );
+        let options = WriteOptions::new().indent(0);
+        assert_eq!(write("[1,2]", &options), "[\n1,\n2\n]");
+    }
+
+    #[test]
+    fn escape() {
+        let json = r#""a/b</script>é🦀""#;
+        assert_eq!(write(json, &WriteOptions::new()), r#""a/b</script>é🦀""#);
+        assert_eq!(
+            write(json, &WriteOptions::new().escape_slash(true)),
+            r#""a\/b<\/script>é🦀""#
+        );
+        assert_eq!(
+            write(json, &WriteOptions::new().escape_script_tags(true)),
+            r#""a/b<\/script>é🦀""#
+        );
+        assert_eq!(
+            write(json, &WriteOptions::new().ascii_only(true)),
+            r#""a/b</script>\u00e9\ud83e\udd80""#
+        );
+    }
+
+    #[test]
+    fn float_format() {
+        let json = "[1.0, 0.1, 1e21, -1.5e-7]";
+        assert_eq!(write(json, &WriteOptions::new()), "[1.0,0.1,1e+21,-1.5e-7]");
+        assert_eq!(
+            write(json, &WriteOptions::new().float_format(FloatFormat::Plain)),
+            "[1,0.1,1000000000000000000000,-0.00000015]"
+        );
+        assert_eq!(
+            write(
+                json,
+                &WriteOptions::new().float_format(FloatFormat::Fixed(1))
+            ),
+            "[1.0,0.1,1000000000000000000000.0,-0.0]"
+        );
+        // expected outputs of `jq -c .` in jq 1.6
+        let json =
+            "[1.0, 0.1, 1e21, -1.5e-7, -0.0, 1e16, 9.223372036854776e18, 1.2345e-4, 12.5, 1e300]";
+        assert_eq!(
+            write(json, &WriteOptions::new().float_format(FloatFormat::Jq)),
+            "[1,0.1,1e+21,-1.5e-07,-0,1e+16,9223372036854776000,0.00012345,12.5,1e+300]"
+        );
+    }
+
+    #[test]
+    fn pg_text() {
+        let value: Value =
+            r#"{"aa": [1, {"b": null}], "b": "x\ny\u0001", "c": -0.0, "d": 1.5e-3, "e": [], "f": {}}"#
+                .parse()
+                .unwrap();
+        assert_eq!(
+            value.to_pg_text(),
+            r#"{"b": "x\ny\u0001", "c": 0, "d": 0.0015, "e": [], "f": {}, "aa": [1, {"b": null}]}"#
+        );
+        assert_eq!(
+            value.to_pg_pretty(),
+            r#"{
+    "b": "x\ny\u0001",
+    "c": 0,
+    "d": 0.0015,
+    "e": [
+    ],
+    "f": {
+    },
+    "aa": [
+        1,
+        {
+            "b": null
+        }
+    ]
+}"#
+        );
+
+        let value: Value = "[]".parse().unwrap();
+        assert_eq!(value.to_pg_text(), "[]");
+        assert_eq!(value.to_pg_pretty(), "[\n]");
+
+        let value: Value = r#""中文 / \"""#.parse().unwrap();
+        assert_eq!(value.to_pg_text(), r#""中文 / \"""#);
+
+        // PostgreSQL gives `[1.50, 100, 12345678901234567890.5, 0.0000001]`
+        let value: Value = "[1.50, 1e2, 12345678901234567890.5, 1E-7]".parse().unwrap();
+        assert_eq!(
+            value.to_pg_text(),
+            "[1.5, 100, 12345678901234567000, 0.0000001]"
+        );
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let options = WriteOptions::new().trailing_newline(true);
+        assert_eq!(write("null", &options), "null\n");
+        let options = WriteOptions::pretty().trailing_newline(true);
+        assert_eq!(write("[null]", &options), "[\n  null\n]\n");
+    }
+
+    #[test]
+    fn raw_json() {
+        let mut builder = crate::Builder::<Vec<u8>>::new();
+        builder.begin_array();
+        builder.add_raw_json(r#"{"b": "é/", "a": [1.50]}"#).unwrap();
+        builder.end_array();
+        let value = builder.finish();
+        let write = |options: &WriteOptions| {
+            let mut buf = vec![];
+            value.to_writer(&mut buf, options).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        // verbatim with default options
+        assert_eq!(write(&WriteOptions::new()), r#"[{"b": "é/", "a": [1.50]}]"#);
+        let options = WriteOptions::new().trailing_newline(true);
+        assert_eq!(write(&options), "[{\"b\": \"é/\", \"a\": [1.50]}]\n");
+
+        // written from the value when any option changes the text
+        let options = WriteOptions::new().ascii_only(true).escape_slash(true);
+        assert_eq!(write(&options), r#"[{"a":[1.5],"b":"\u00e9\/"}]"#);
+        assert_eq!(
+            write(&WriteOptions::pretty()),
+            "[\n  {\n    \"a\": [\n      1.5\n    ],\n    \"b\": \"é/\"\n  }\n]"
+        );
+        let options = WriteOptions::new().float_format(FloatFormat::Fixed(1));
+        assert_eq!(write(&options), r#"[{"a":[1.5],"b":"é/"}]"#);
+        assert_eq!(value.to_pg_text(), r#"[{"a": [1.5], "b": "é/"}]"#);
+    }
+}